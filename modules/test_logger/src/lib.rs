@@ -1,7 +1,10 @@
 // This is a test plugin demonstrating module logging capabilities.
 
 use anyhow::Result;
-use api::{ModuleLogger, TrackingModule, UnifiedTrackingData};
+use vrft_module_sdk::{
+    export_module, HostServices, ModuleDescriptor, ModuleLogger, TrackingModule, TrackingState,
+    UnifiedTrackingData,
+};
 
 pub struct TestLogger {
     frame_count: u64,
@@ -18,7 +21,8 @@ impl TestLogger {
 }
 
 impl TrackingModule for TestLogger {
-    fn initialize(&mut self, logger: ModuleLogger) -> Result<()> {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        let logger = host.logger().clone();
         logger.info("Initializing test logger plugin");
         logger.debug("Debug: Plugin initialization details");
         logger.trace("Trace: Very detailed initialization info");
@@ -26,7 +30,7 @@ impl TrackingModule for TestLogger {
         Ok(())
     }
 
-    fn update(&mut self, _data: &mut UnifiedTrackingData) -> Result<()> {
+    fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
         self.frame_count += 1;
 
         if let Some(logger) = &self.logger {
@@ -70,7 +74,8 @@ impl TrackingModule for TestLogger {
             }
         }
 
-        Ok(())
+        // This plugin never writes tracking data
+        TrackingState::NoNewFrame
     }
 
     fn unload(&mut self) {
@@ -81,10 +86,14 @@ impl TrackingModule for TestLogger {
             ));
         }
     }
-}
 
-#[no_mangle]
-#[allow(improper_ctypes_definitions)]
-pub extern "C" fn create_module() -> *mut dyn TrackingModule {
-    Box::into_raw(Box::new(TestLogger::new()))
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        // Produces no tracking data at all.
+        Some(ModuleDescriptor {
+            name: "Test Logger".to_string(),
+            ..Default::default()
+        })
+    }
 }
+
+export_module!(TestLogger::new(), vendor = "vrft_d");
@@ -1 +1,5 @@
+//! Virtual Desktop reads face tracking from a Windows-only shared memory block, so the
+//! module is empty on other platforms.
+
+#[cfg(windows)]
 pub mod virtual_desktop;
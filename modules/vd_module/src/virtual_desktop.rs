@@ -2,39 +2,110 @@
 // https://github.com/guygodin/VirtualDesktop.VRCFaceTracking
 
 use anyhow::Result;
-use api::{ModuleLogger, TrackingModule, UnifiedExpressions, UnifiedTrackingData};
-use glam::{Quat, Vec2};
+use glam::{Quat, Vec3};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use vrft_module_sdk::{
+    export_module, gaze, ChannelStatus, ConnectionState, FrameSink, HostServices,
+    ModuleCapabilities, ModuleDescriptor, ModuleSettings, TrackingModule, TrackingState,
+    UnifiedExpressions, UnifiedTrackingData,
+};
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
 use windows::Win32::System::Threading::{OpenEventW, WaitForSingleObject, EVENT_ALL_ACCESS};
 
 const BODY_STATE_MAP_NAME: &str = "VirtualDesktop.BodyState";
 const BODY_STATE_EVENT_NAME: &str = "VirtualDesktop.BodyStateEvent";
-const ENABLED_EYE_SMOOTHING: bool = false;
-const ENABLED_CHEEK_CROSSTALK_REDUCTION: bool = false;
-const SMOOTHING_FACTOR: f32 = 0.5;
-
-/// Extracts pitch/yaw Euler angles from a quaternion orientation.
-/// Returns (pitch, yaw) in radians.
-fn quaternion_to_pitch_yaw(q: Quat) -> (f32, f32) {
-    let (x, y, z, w) = (q.x, q.y, q.z, q.w);
-    let magnitude = (x * x + y * y + z * z + w * w).sqrt();
 
+/// Indices into `FaceState::expression_confidences` (XR_FB_face_tracking regions).
+const LOWER_FACE_CONFIDENCE: usize = 0;
+const UPPER_FACE_CONFIDENCE: usize = 1;
+
+/// Expressions written by `update_eye_expressions` and `update_mouth_expressions`.
+const DRIVEN_EXPRESSIONS: &[UnifiedExpressions] = {
+    use UnifiedExpressions::*;
+    &[
+        EyeWideLeft,
+        EyeWideRight,
+        EyeSquintLeft,
+        EyeSquintRight,
+        BrowInnerUpLeft,
+        BrowInnerUpRight,
+        BrowOuterUpLeft,
+        BrowOuterUpRight,
+        BrowPinchLeft,
+        BrowPinchRight,
+        BrowLowererLeft,
+        BrowLowererRight,
+        JawOpen,
+        JawLeft,
+        JawRight,
+        JawForward,
+        MouthClosed,
+        MouthUpperLeft,
+        MouthUpperRight,
+        MouthLowerLeft,
+        MouthLowerRight,
+        MouthCornerPullLeft,
+        MouthCornerPullRight,
+        MouthCornerSlantLeft,
+        MouthCornerSlantRight,
+        MouthFrownLeft,
+        MouthFrownRight,
+        MouthLowerDownLeft,
+        MouthLowerDownRight,
+        MouthUpperUpLeft,
+        MouthUpperUpRight,
+        MouthUpperDeepenLeft,
+        MouthUpperDeepenRight,
+        MouthRaiserUpper,
+        MouthRaiserLower,
+        MouthDimpleLeft,
+        MouthDimpleRight,
+        MouthTightenerLeft,
+        MouthTightenerRight,
+        MouthPressLeft,
+        MouthPressRight,
+        MouthStretchLeft,
+        MouthStretchRight,
+        LipPuckerUpperLeft,
+        LipPuckerUpperRight,
+        LipPuckerLowerLeft,
+        LipPuckerLowerRight,
+        LipFunnelUpperLeft,
+        LipFunnelUpperRight,
+        LipFunnelLowerLeft,
+        LipFunnelLowerRight,
+        LipSuckUpperLeft,
+        LipSuckUpperRight,
+        LipSuckLowerLeft,
+        LipSuckLowerRight,
+        CheekPuffLeft,
+        CheekPuffRight,
+        CheekSuckLeft,
+        CheekSuckRight,
+        CheekSquintLeft,
+        CheekSquintRight,
+        NoseSneerLeft,
+        NoseSneerRight,
+        TongueOut,
+        TongueCurlUp,
+    ]
+};
+
+/// Converts an OpenXR eye orientation into a gaze direction.
+///
+/// OpenXR looks down -Z; the unified convention looks down +Z, so Z is flipped.
+fn quaternion_to_gaze(q: Quat) -> Vec3 {
     // Guard against zero/near-zero magnitude (malformed quaternion)
-    if magnitude < 0.0001 {
-        return (0.0, 0.0);
+    if q.length() < 0.0001 {
+        return gaze::FORWARD;
     }
 
-    let xm = x / magnitude;
-    let ym = y / magnitude;
-    let zm = z / magnitude;
-    let wm = w / magnitude;
-
-    let pitch = (2.0 * (xm * zm - wm * ym)).asin();
-    let yaw = (2.0 * (ym * zm + wm * xm)).atan2(wm * wm - xm * xm - ym * ym + zm * zm);
-
-    (pitch, yaw)
+    let forward = q.normalize() * Vec3::NEG_Z;
+    gaze::normalize(Vec3::new(forward.x, forward.y, -forward.z))
 }
 
 #[repr(C)]
@@ -95,23 +166,75 @@ impl EyeSmoothingState {
     }
 }
 
-pub struct VirtualDesktopModule {
+/// Settings from `module.settings."vd_module.dll"` in config.json.
+#[derive(Debug, Clone, Copy)]
+struct VdSettings {
+    /// Slerp eye rotations towards the previous frame.
+    eye_smoothing: bool,
+    /// Weight of the new rotation when `eye_smoothing` is on.
+    smoothing_factor: f32,
+    /// Suppress the weaker cheek puff when one side clearly dominates.
+    cheek_crosstalk_reduction: bool,
+}
+
+impl Default for VdSettings {
+    fn default() -> Self {
+        Self {
+            eye_smoothing: false,
+            smoothing_factor: 0.5,
+            cheek_crosstalk_reduction: false,
+        }
+    }
+}
+
+impl VdSettings {
+    fn from_settings(settings: &ModuleSettings) -> Self {
+        let defaults = Self::default();
+        Self {
+            eye_smoothing: settings.bool_or("eye_smoothing", defaults.eye_smoothing),
+            smoothing_factor: settings
+                .f32_or("smoothing_factor", defaults.smoothing_factor)
+                .clamp(0.0, 1.0),
+            cheek_crosstalk_reduction: settings.bool_or(
+                "cheek_crosstalk_reduction",
+                defaults.cheek_crosstalk_reduction,
+            ),
+        }
+    }
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "eye_smoothing": { "type": "boolean" },
+                "smoothing_factor": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                "cheek_crosstalk_reduction": { "type": "boolean" }
+            }
+        })
+    }
+}
+
+/// Connection to Virtual Desktop's shared memory plus the per-frame conversion state.
+struct VdReader {
     mapping_handle: HANDLE,
     event_handle: HANDLE,
     face_state_ptr: *const FaceState,
-    logger: Option<ModuleLogger>,
+    host: Option<HostServices>,
     eye_smoothing: EyeSmoothingState,
+    settings: VdSettings,
     last_valid_frame_time: std::time::Instant,
 }
 
-impl VirtualDesktopModule {
-    pub fn new() -> Self {
+impl VdReader {
+    fn new() -> Self {
         Self {
             mapping_handle: HANDLE(std::ptr::null_mut()),
             event_handle: HANDLE(std::ptr::null_mut()),
             face_state_ptr: std::ptr::null(),
-            logger: None,
+            host: None,
             eye_smoothing: EyeSmoothingState::new(),
+            settings: VdSettings::default(),
             last_valid_frame_time: std::time::Instant::now(),
         }
     }
@@ -141,8 +264,9 @@ impl VirtualDesktopModule {
                 self.mapping_handle = HANDLE(std::ptr::null_mut());
             }
         }
-        if let Some(logger) = &self.logger {
-            logger.info("Virtual Desktop Disconnected.");
+        if let Some(host) = &self.host {
+            host.logger().info("Virtual Desktop Disconnected.");
+            host.set_connection_state(ConnectionState::Disconnected);
         }
     }
 
@@ -205,8 +329,9 @@ impl VirtualDesktopModule {
             self.event_handle = event_handle;
             self.last_valid_frame_time = std::time::Instant::now();
 
-            if let Some(logger) = &self.logger {
-                logger.info("Virtual Desktop Connected!");
+            if let Some(host) = &self.host {
+                host.logger().info("Virtual Desktop Connected!");
+                host.set_connection_state(ConnectionState::Connected);
             }
 
             Ok(())
@@ -225,7 +350,7 @@ impl VirtualDesktopModule {
                 * eye_openness_scale;
             data.eye.left.openness = left_openness;
 
-            // Gaze: extract pitch/yaw from quaternion orientation
+            // Gaze: direction from quaternion orientation
             let mut left_quat = Quat::from_xyzw(
                 face_state.left_eye_pose.orientation.x,
                 face_state.left_eye_pose.orientation.y,
@@ -233,26 +358,25 @@ impl VirtualDesktopModule {
                 face_state.left_eye_pose.orientation.w,
             );
 
-            if ENABLED_EYE_SMOOTHING {
+            if self.settings.eye_smoothing {
                 if !self.eye_smoothing.initialized {
                     self.eye_smoothing.left_rot = left_quat;
                 } else {
                     left_quat = self
                         .eye_smoothing
                         .left_rot
-                        .slerp(left_quat, SMOOTHING_FACTOR);
+                        .slerp(left_quat, self.settings.smoothing_factor);
                     self.eye_smoothing.left_rot = left_quat;
                 }
             }
 
-            let (pitch, yaw) = quaternion_to_pitch_yaw(left_quat);
-            data.eye.left.gaze = Vec2::new(pitch, yaw);
+            data.eye.left.gaze = quaternion_to_gaze(left_quat);
 
             data.eye.left.pupil_diameter_mm = 5.0;
+            data.eye.left.status = ChannelStatus::new(true, face_state.left_eye_confidence);
         } else {
-            data.eye.left.openness = 0.5;
-            data.eye.left.pupil_diameter_mm = 2.0;
-            data.eye.left.gaze = glam::Vec2::ZERO;
+            // Leave the previous values in place; downstream holds them while invalid.
+            data.eye.left.status = ChannelStatus::LOST;
         }
 
         if face_state.right_eye_is_valid != 0 {
@@ -262,7 +386,7 @@ impl VirtualDesktopModule {
                 * eye_openness_scale;
             data.eye.right.openness = right_openness;
 
-            // Gaze: extract pitch/yaw Euler angles from quaternion orientation
+            // Gaze: direction from quaternion orientation
             let mut right_quat = Quat::from_xyzw(
                 face_state.right_eye_pose.orientation.x,
                 face_state.right_eye_pose.orientation.y,
@@ -270,7 +394,7 @@ impl VirtualDesktopModule {
                 face_state.right_eye_pose.orientation.w,
             );
 
-            if ENABLED_EYE_SMOOTHING {
+            if self.settings.eye_smoothing {
                 if !self.eye_smoothing.initialized {
                     self.eye_smoothing.right_rot = right_quat;
                     self.eye_smoothing.initialized = true;
@@ -278,19 +402,17 @@ impl VirtualDesktopModule {
                     right_quat = self
                         .eye_smoothing
                         .right_rot
-                        .slerp(right_quat, SMOOTHING_FACTOR);
+                        .slerp(right_quat, self.settings.smoothing_factor);
                     self.eye_smoothing.right_rot = right_quat;
                 }
             }
 
-            let (pitch, yaw) = quaternion_to_pitch_yaw(right_quat);
-            data.eye.right.gaze = Vec2::new(pitch, yaw);
+            data.eye.right.gaze = quaternion_to_gaze(right_quat);
 
             data.eye.right.pupil_diameter_mm = 5.0;
+            data.eye.right.status = ChannelStatus::new(true, face_state.right_eye_confidence);
         } else {
-            data.eye.right.openness = 0.5;
-            data.eye.right.pupil_diameter_mm = 2.0;
-            data.eye.right.gaze = glam::Vec2::ZERO;
+            data.eye.right.status = ChannelStatus::LOST;
         }
 
         // Pupil dilation normalization bounds for downstream consumers
@@ -298,13 +420,38 @@ impl VirtualDesktopModule {
         data.eye.max_dilation = 10.0;
     }
 
+    fn update_face_status(&self, data: &mut UnifiedTrackingData, face_state: &FaceState) {
+        let upper_valid = face_state.is_eye_following_blendshapes_valid != 0;
+        let lower_valid = face_state.face_is_valid != 0;
+        let upper = face_state.expression_confidences[UPPER_FACE_CONFIDENCE];
+        let lower = face_state.expression_confidences[LOWER_FACE_CONFIDENCE];
+
+        let confidence = match (upper_valid, lower_valid) {
+            (true, true) => upper.max(lower),
+            (true, false) => upper,
+            (false, true) => lower,
+            (false, false) => 0.0,
+        };
+        data.face = ChannelStatus::new(upper_valid || lower_valid, confidence);
+
+        // Shapes of a region that is not tracked this frame keep stale weights; mark them
+        // so calibration does not learn from them. Tracked regions overwrite this below.
+        data.shape_confidences.fill(0.0);
+
+        // Virtual Desktop does not stream head pose.
+        data.head.status = ChannelStatus::LOST;
+    }
+
     fn update_eye_expressions(&self, data: &mut UnifiedTrackingData, face_state: &FaceState) {
         let w = &face_state.expression_weights;
         let s = &mut data.shapes;
+        let c = &mut data.shape_confidences;
+        let confidence = face_state.expression_confidences[UPPER_FACE_CONFIDENCE];
 
         macro_rules! map_idx {
             ($unified:ident, $idx:expr) => {
                 s[UnifiedExpressions::$unified as usize].weight = w[$idx];
+                c[UnifiedExpressions::$unified as usize] = confidence;
             };
         }
 
@@ -328,15 +475,18 @@ impl VirtualDesktopModule {
     fn update_mouth_expressions(&self, data: &mut UnifiedTrackingData, face_state: &FaceState) {
         let w = &face_state.expression_weights;
         let s = &mut data.shapes;
+        let c = &mut data.shape_confidences;
+        let confidence = face_state.expression_confidences[LOWER_FACE_CONFIDENCE];
 
-        macro_rules! map_idx {
-            ($unified:ident, $idx:expr) => {
-                s[UnifiedExpressions::$unified as usize].weight = w[$idx];
-            };
-        }
         macro_rules! map_val {
             ($unified:ident, $val:expr) => {
                 s[UnifiedExpressions::$unified as usize].weight = $val;
+                c[UnifiedExpressions::$unified as usize] = confidence;
+            };
+        }
+        macro_rules! map_idx {
+            ($unified:ident, $idx:expr) => {
+                map_val!($unified, w[$idx]);
             };
         }
 
@@ -407,7 +557,7 @@ impl VirtualDesktopModule {
         let mut puff_r = w[3];
 
         // Optional crosstalk reduction: suppress weaker side when asymmetric
-        if ENABLED_CHEEK_CROSSTALK_REDUCTION {
+        if self.settings.cheek_crosstalk_reduction {
             if puff_l > puff_r + 0.1 && puff_r < 0.4 {
                 puff_r = 0.0;
             } else if puff_r > puff_l + 0.1 && puff_l < 0.4 {
@@ -415,8 +565,8 @@ impl VirtualDesktopModule {
             }
         }
 
-        s[UnifiedExpressions::CheekPuffLeft as usize].weight = puff_l;
-        s[UnifiedExpressions::CheekPuffRight as usize].weight = puff_r;
+        map_val!(CheekPuffLeft, puff_l);
+        map_val!(CheekPuffRight, puff_r);
 
         map_idx!(CheekSuckLeft, 6); // CheekSuckL
         map_idx!(CheekSuckRight, 7); // CheekSuckR
@@ -433,25 +583,16 @@ impl VirtualDesktopModule {
     }
 }
 
-impl Default for VirtualDesktopModule {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl TrackingModule for VirtualDesktopModule {
-    fn initialize(&mut self, logger: ModuleLogger) -> Result<()> {
-        logger.info("Initializing Virtual Desktop Module (Background Mode)");
-        self.logger = Some(logger);
-        // We don't block here anymore. Connection is handled in update().
-        Ok(())
-    }
-
-    fn update(&mut self, data: &mut UnifiedTrackingData) -> Result<()> {
-        if !self.is_connected() && self.connect().is_err() {
-            // Sleep to avoid busy loop when not connected
-            thread::sleep(Duration::from_secs(1));
-            return Err(anyhow::anyhow!("Not connected to Virtual Desktop"));
+impl VdReader {
+    /// Waits up to 50 ms for the next frame and converts it into `data`.
+    ///
+    /// Returns without waiting while Virtual Desktop is not running.
+    fn read_frame(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        if !self.is_connected() {
+            self.count("connect_attempts");
+            if self.connect().is_err() {
+                return TrackingState::Disconnected;
+            }
         }
 
         unsafe {
@@ -466,9 +607,21 @@ impl TrackingModule for VirtualDesktopModule {
                     || face_state.right_eye_is_valid != 0
                     || face_state.is_eye_following_blendshapes_valid != 0;
 
+                if !is_valid {
+                    self.count("invalid_frames");
+                }
+
                 if is_valid {
+                    self.count("frames_received");
+                    self.publish_confidences(face_state);
                     self.last_valid_frame_time = std::time::Instant::now();
+                    // The shared memory carries no capture time; the event fires on capture.
+                    data.timestamp_us = self
+                        .host
+                        .as_ref()
+                        .map_or_else(vrft_module_sdk::timestamp_us_now, HostServices::now_us);
                     self.update_eye_data(data, face_state);
+                    self.update_face_status(data, face_state);
 
                     if face_state.is_eye_following_blendshapes_valid != 0 {
                         self.update_eye_expressions(data, face_state);
@@ -490,31 +643,182 @@ impl TrackingModule for VirtualDesktopModule {
                     //     self.last_log_time = Some(now);
                     // }
 
-                    return Ok(());
+                    return TrackingState::NewFrame;
                 }
             }
         }
 
         // Check for timeout
         if self.last_valid_frame_time.elapsed() > Duration::from_secs(10) {
-            if let Some(logger) = &self.logger {
-                logger.warn("Connection timeout. No valid data for 10s. Reconnecting...");
+            if let Some(host) = &self.host {
+                host.logger()
+                    .warn("Connection timeout. No valid data for 10s. Reconnecting...");
             }
+            self.count("connection_timeouts");
             self.disconnect();
-            return Err(anyhow::anyhow!("Connection timeout"));
+            return TrackingState::Reconnecting;
         }
 
         // Timeout or invalid face
-        Err(anyhow::anyhow!("No new frame"))
+        TrackingState::NoNewFrame
     }
 
-    fn unload(&mut self) {
-        self.disconnect();
+    fn count(&self, counter: &str) {
+        if let Some(host) = &self.host {
+            host.increment_counter(counter);
+        }
+    }
+
+    /// Publishes the confidences Virtual Desktop reports, for diagnosing choppy tracking.
+    fn publish_confidences(&self, face_state: &FaceState) {
+        let Some(host) = &self.host else {
+            return;
+        };
+        host.set_gauge("left_eye_confidence", face_state.left_eye_confidence as f64);
+        host.set_gauge(
+            "right_eye_confidence",
+            face_state.right_eye_confidence as f64,
+        );
+        host.set_gauge(
+            "lower_face_confidence",
+            face_state.expression_confidences[LOWER_FACE_CONFIDENCE] as f64,
+        );
+        host.set_gauge(
+            "upper_face_confidence",
+            face_state.expression_confidences[UPPER_FACE_CONFIDENCE] as f64,
+        );
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) {
+        self.settings = VdSettings::from_settings(settings);
+        // Start smoothing from the next sample instead of an old rotation.
+        self.eye_smoothing = EyeSmoothingState::new();
+        if let Some(host) = &self.host {
+            host.logger()
+                .info(&format!("Settings updated: {:?}", self.settings));
+        }
+    }
+}
+
+// SAFETY: The handles and the mapped view belong to the process, and the reader is only
+// used by one thread at a time through its mutex.
+unsafe impl Send for VdReader {}
+
+/// How long to wait before reconnecting while Virtual Desktop is not running.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+struct StreamThread {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+pub struct VirtualDesktopModule {
+    reader: Arc<Mutex<VdReader>>,
+    stream: Option<StreamThread>,
+}
+
+impl VirtualDesktopModule {
+    pub fn new() -> Self {
+        Self {
+            reader: Arc::new(Mutex::new(VdReader::new())),
+            stream: None,
+        }
+    }
+
+    fn stop_streaming(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            stream.stop.store(true, Ordering::SeqCst);
+            let _ = stream.handle.join();
+        }
+    }
+}
+
+impl Default for VirtualDesktopModule {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[no_mangle]
-#[allow(improper_ctypes_definitions)]
-pub extern "C" fn create_module() -> Box<dyn TrackingModule> {
-    Box::new(VirtualDesktopModule::new())
+impl TrackingModule for VirtualDesktopModule {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        host.logger()
+            .info("Initializing Virtual Desktop Module (Background Mode)");
+        host.set_connection_state(ConnectionState::Connecting);
+        self.reader.lock().unwrap().host = Some(host);
+        // We don't block here anymore. Connection is handled when reading frames.
+        Ok(())
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        // The host backs off on its own while we report Disconnected.
+        self.reader.lock().unwrap().read_frame(data)
+    }
+
+    fn start_streaming(&mut self, sink: FrameSink) -> bool {
+        if self.stream.is_some() {
+            return false;
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let reader = self.reader.clone();
+        let spawned = thread::Builder::new()
+            .name("vd_module".to_string())
+            .spawn(move || {
+                // Like the host's polled frame, each frame builds on the previous one.
+                let mut data = UnifiedTrackingData::default();
+                while !stop_flag.load(Ordering::SeqCst) {
+                    let state = reader.lock().unwrap().read_frame(&mut data);
+                    match state {
+                        TrackingState::NewFrame => sink.submit(&data),
+                        TrackingState::NoNewFrame | TrackingState::Reconnecting => {}
+                        TrackingState::Disconnected | TrackingState::Fatal => {
+                            // Wait for Virtual Desktop without delaying unload.
+                            let retry_at = Instant::now() + RECONNECT_DELAY;
+                            while !stop_flag.load(Ordering::SeqCst) && Instant::now() < retry_at {
+                                thread::sleep(Duration::from_millis(50));
+                            }
+                        }
+                    }
+                }
+            });
+
+        match spawned {
+            Ok(handle) => {
+                self.stream = Some(StreamThread { stop, handle });
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn unload(&mut self) {
+        self.stop_streaming();
+        self.reader.lock().unwrap().disconnect();
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+        self.reader.lock().unwrap().configure(settings);
+        Ok(())
+    }
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        // The pupil diameter is a fixed placeholder, and head pose is not streamed.
+        Some(
+            ModuleDescriptor::new(
+                "Virtual Desktop",
+                env!("CARGO_PKG_VERSION"),
+                "Virtual Desktop",
+            )
+            .with_capabilities(ModuleCapabilities {
+                eye_gaze: true,
+                eye_openness: true,
+                ..ModuleCapabilities::NONE
+            })
+            .with_expressions(DRIVEN_EXPRESSIONS)
+            .with_settings_schema(VdSettings::schema()),
+        )
+    }
 }
+
+export_module!(VirtualDesktopModule::new());
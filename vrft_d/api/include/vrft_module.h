@@ -0,0 +1,203 @@
+/*
+ * C ABI for vrft_d native tracking modules.
+ *
+ * Mirrors vrft_d/api/src/abi.rs. Keep both in sync and bump VRFT_ABI_VERSION on any
+ * layout change.
+ *
+ * A module library exports:
+ *
+ *   uint32_t vrft_abi_version(void);
+ *   int32_t  vrft_create_module(VrftModuleVTable *out);
+ *
+ * The host calls vrft_abi_version() first and refuses to load the library if it does not
+ * return VRFT_ABI_VERSION. It then calls vrft_create_module() once per instance. All
+ * functions in the table are called from one host thread at a time.
+ */
+
+#ifndef VRFT_MODULE_H
+#define VRFT_MODULE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define VRFT_ABI_VERSION 12u
+
+#define VRFT_OK 0
+/* Returned by a failed initialize or configure only. update returns a VrftTrackingState,
+ * in which 1 means VRFT_TRACKING_NO_NEW_FRAME; a failed update returns
+ * VRFT_TRACKING_FATAL. */
+#define VRFT_ERROR 1
+/* Returned by initialize, update or configure when the module crashed during the call,
+ * e.g. a caught C++ exception. The host unloads and recreates the instance. Never let an
+ * exception or panic escape into the host. */
+#define VRFT_FAULT (-1)
+
+/* Number of UnifiedExpressions shapes (UnifiedExpressions::Max). */
+#define VRFT_EXPRESSION_COUNT 88
+
+typedef enum VrftLogLevel {
+    VRFT_LOG_ERROR = 1,
+    VRFT_LOG_WARN = 2,
+    VRFT_LOG_INFO = 3,
+    VRFT_LOG_DEBUG = 4,
+    VRFT_LOG_TRACE = 5,
+} VrftLogLevel;
+
+/* `level` is a VrftLogLevel; other values log as VRFT_LOG_INFO. A null `target` or
+ * `message` logs as "unknown" or an empty line. */
+typedef void (*VrftLogCallback)(uint32_t level, const char *target, const char *message);
+
+/* Return values of update. */
+typedef enum VrftTrackingState {
+    VRFT_TRACKING_NEW_FRAME = 0, /* frame holds new data (same as VRFT_OK) */
+    VRFT_TRACKING_NO_NEW_FRAME = 1, /* connected, nothing new since the last call */
+    VRFT_TRACKING_DISCONNECTED = 2, /* device not available; polled less often */
+    VRFT_TRACKING_RECONNECTING = 3, /* connection dropped, being re-established */
+    VRFT_TRACKING_FATAL = 4, /* the module cannot continue and is no longer polled */
+} VrftTrackingState;
+
+typedef enum VrftConnectionState {
+    VRFT_DISCONNECTED = 0,
+    VRFT_CONNECTING = 1,
+    VRFT_CONNECTED = 2,
+} VrftConnectionState;
+
+typedef struct VrftVec3 {
+    float x;
+    float y;
+    float z;
+} VrftVec3;
+
+/* Validity and confidence of one tracking channel. Defaults to { 1, 1.0f }. */
+typedef struct VrftChannelStatus {
+    uint8_t valid; /* Nonzero when the device tracked the channel this frame. */
+    float confidence; /* 0..1 */
+} VrftChannelStatus;
+
+typedef struct VrftSingleEyeData {
+    /* Normalized gaze direction: +X right, +Y up, +Z forward. */
+    VrftVec3 gaze;
+    float pupil_diameter_mm;
+    float openness;
+    VrftChannelStatus status;
+} VrftSingleEyeData;
+
+typedef struct VrftEyeData {
+    VrftSingleEyeData left;
+    VrftSingleEyeData right;
+    float max_dilation;
+    float min_dilation;
+    float left_diameter;
+    float right_diameter;
+} VrftEyeData;
+
+typedef struct VrftHeadData {
+    float head_yaw;
+    float head_pitch;
+    float head_roll;
+    float head_pos_x;
+    float head_pos_y;
+    float head_pos_z;
+    VrftChannelStatus status;
+} VrftHeadData;
+
+typedef struct VrftExpressionShape {
+    float weight;
+} VrftExpressionShape;
+
+/* Host services. The callbacks take context as their first argument and may be called
+ * from any thread; the context and strings stay valid until destroy. */
+typedef struct VrftHostContext {
+    uint32_t struct_size;
+    VrftLogCallback log;
+    /* Log target assigned by the host. */
+    const char *log_target;
+    void *context;
+    /* Host clock in microseconds since the UNIX epoch. */
+    uint64_t (*now_us)(void *context);
+    /* Directory where the module may persist state, or NULL. */
+    const char *state_dir;
+    /* Takes a VrftConnectionState. */
+    void (*set_connection_state)(void *context, uint32_t state);
+    void (*request_recalibration)(void *context);
+    /* Gauges keep their last value; counters add up. */
+    void (*set_gauge)(void *context, const char *name, double value);
+    void (*add_counter)(void *context, const char *name, uint64_t delta);
+} VrftHostContext;
+
+typedef struct VrftTrackingFrame {
+    VrftEyeData eye;
+    VrftHeadData head;
+    /* Status of the expression shapes as a whole. */
+    VrftChannelStatus face;
+    /* Host-owned, shape_count entries each, valid for the duration of the update call only. */
+    VrftExpressionShape *shapes;
+    float *shape_confidences;
+    size_t shape_count;
+    /* Capture time in microseconds since the UNIX epoch; 0 lets the host stamp the frame. */
+    uint64_t timestamp_us;
+} VrftTrackingFrame;
+
+/* Host callback for push-mode modules. submit may be called from any thread, but not
+ * concurrently, until unload returns. The frame only needs to stay valid for the call. */
+typedef struct VrftFrameSink {
+    uint32_t struct_size; /* sizeof(VrftFrameSink) */
+    void *context;
+    void (*submit)(void *context, const VrftTrackingFrame *frame);
+} VrftFrameSink;
+
+/* Channels a module fills in, as flags of VrftModuleDescriptor.capabilities. */
+#define VRFT_CAPABILITY_EYE_GAZE (1u << 0)
+#define VRFT_CAPABILITY_EYE_OPENNESS (1u << 1)
+#define VRFT_CAPABILITY_PUPIL (1u << 2)
+#define VRFT_CAPABILITY_EXPRESSIONS (1u << 3)
+#define VRFT_CAPABILITY_HEAD (1u << 4)
+
+/* Module metadata. Owned by the module and valid until destroy. */
+typedef struct VrftModuleDescriptor {
+    uint32_t struct_size; /* sizeof(VrftModuleDescriptor) */
+    /* Null-terminated UTF-8; NULL is treated as empty. */
+    const char *name;
+    const char *version;
+    const char *vendor;
+    uint32_t capabilities; /* VRFT_CAPABILITY_* flags */
+    /* UnifiedExpressions indices the module drives. */
+    const uint32_t *expressions;
+    size_t expression_count;
+    /* JSON Schema for the module's settings, or NULL. */
+    const char *settings_schema;
+} VrftModuleDescriptor;
+
+typedef struct VrftModuleVTable {
+    uint32_t abi_version; /* must be VRFT_ABI_VERSION */
+    uint32_t struct_size; /* sizeof(VrftModuleVTable) */
+    void *instance;
+    int32_t (*initialize)(void *instance, const VrftHostContext *host);
+    /* Returns a VrftTrackingState. */
+    int32_t (*update)(void *instance, VrftTrackingFrame *frame);
+    void (*unload)(void *instance);
+    /* Frees the instance. Called exactly once. */
+    void (*destroy)(void *instance);
+    /* Optional; may be NULL or return NULL if the module does not describe itself. */
+    const VrftModuleDescriptor *(*describe)(void *instance);
+    /* Optional; receives the module settings as a JSON string. Called before initialize
+     * and whenever the settings change. */
+    int32_t (*configure)(void *instance, const char *settings_json);
+    /* Optional; return nonzero to push frames through sink from the module's own thread
+     * instead of being polled through update. Copy *sink; the pointer itself is temporary.
+     * unload must stop the thread. */
+    uint8_t (*start_streaming)(void *instance, const VrftFrameSink *sink);
+} VrftModuleVTable;
+
+uint32_t vrft_abi_version(void);
+int32_t vrft_create_module(VrftModuleVTable *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VRFT_MODULE_H */
@@ -0,0 +1,995 @@
+//! Stable C ABI for native tracking modules.
+//!
+//! Native modules are plain shared libraries, so nothing Rust-specific (trait objects,
+//! `Box`, `Vec`) may cross the library boundary. A module exports two C symbols instead:
+//!
+//! - `vrft_abi_version() -> u32`: the [`VRFT_ABI_VERSION`] the module was built against.
+//!   The host refuses to go any further if this does not match its own version.
+//! - `vrft_create_module(out: *mut VrftModuleVTable) -> i32`: fills in a function table
+//!   for a freshly created module instance.
+//!
+//! `initialize` receives a [`VrftHostContext`] with the host's log sink and the other host
+//! services (clock, state directory, connection state, recalibration and metrics).
+//!
+//! The table may also provide `describe`, which returns a [`VrftModuleDescriptor`] with the
+//! module's name, version and capabilities, `configure`, which receives the module's
+//! settings as a JSON string, and `start_streaming`, which switches the module to push mode
+//! (see [`VrftFrameSink`]).
+//!
+//! Panics must not unwind into the host. The functions [`export_module`] generates catch
+//! them and return [`VRFT_FAULT`] instead; [`AbiModule`] raises that as a panic on the host
+//! side, where the host contains it and recreates the module.
+//!
+//! The matching C declarations live in `include/vrft_module.h`, so modules can also be
+//! written in C, C++ or Zig. Rust modules implement [`TrackingModule`] and hand the value
+//! to [`export_module`], which generates the table for them.
+
+use anyhow::{bail, Result};
+use glam::Vec3;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::{
+    timestamp_us_now, ChannelStatus, ConnectionState, FrameSink, Host, HostServices, LogCallback,
+    ModuleCapabilities, ModuleDescriptor, ModuleLogger, ModuleSettings, TrackingModule,
+    TrackingState, UnifiedExpressionShape, UnifiedExpressions, UnifiedEyeData, UnifiedHeadData,
+    UnifiedSingleEyeData, UnifiedTrackingData,
+};
+
+/// Version of the C ABI described in this module. Bump on every layout change.
+pub const VRFT_ABI_VERSION: u32 = 12;
+
+/// Symbol name of the exported `vrft_abi_version` function.
+pub const ABI_VERSION_SYMBOL: &[u8] = b"vrft_abi_version\0";
+
+/// Symbol name of the exported `vrft_create_module` function.
+pub const CREATE_MODULE_SYMBOL: &[u8] = b"vrft_create_module\0";
+
+/// Return code for a successful call.
+pub const VRFT_OK: i32 = 0;
+
+/// Return code for a failed `initialize` or `configure`. `update` returns a
+/// [`TrackingState`] instead, where 1 means `NoNewFrame`.
+pub const VRFT_ERROR: i32 = 1;
+
+/// Return code of `initialize`, `update` or `configure` when the module panicked (or, in
+/// C++, threw) during the call. The instance is faulted; the host unloads and recreates it.
+pub const VRFT_FAULT: i32 = -1;
+
+/// Signature of the exported `vrft_abi_version` symbol.
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+
+/// Signature of the exported `vrft_create_module` symbol.
+pub type CreateModuleFn = unsafe extern "C" fn(out: *mut VrftModuleVTable) -> i32;
+
+/// Host facilities handed to a module on initialization.
+///
+/// The callbacks take `context` as their first argument. They may be called from any thread
+/// until `destroy`, and the context and strings stay valid until then as well.
+#[repr(C)]
+pub struct VrftHostContext {
+    /// `size_of::<VrftHostContext>()` as seen by the host.
+    pub struct_size: u32,
+    /// Log sink; `target` should be `log_target`.
+    pub log: LogCallback,
+    /// Null-terminated log target assigned to the module by the host.
+    pub log_target: *const c_char,
+    pub context: *mut c_void,
+    /// Host clock in microseconds since the UNIX epoch.
+    pub now_us: Option<unsafe extern "C" fn(*mut c_void) -> u64>,
+    /// Null-terminated directory where the module may persist state, or null.
+    pub state_dir: *const c_char,
+    /// Takes a [`ConnectionState`] value.
+    pub set_connection_state: Option<unsafe extern "C" fn(*mut c_void, u32)>,
+    pub request_recalibration: Option<unsafe extern "C" fn(*mut c_void)>,
+    /// Takes a null-terminated gauge name and its current value.
+    pub set_gauge: Option<unsafe extern "C" fn(*mut c_void, *const c_char, f64)>,
+    /// Takes a null-terminated counter name and the amount to add.
+    pub add_counter: Option<unsafe extern "C" fn(*mut c_void, *const c_char, u64)>,
+}
+
+/// Module side of [`VrftHostContext`]: forwards [`HostServices`] calls to the host.
+struct RemoteHost {
+    context: *mut c_void,
+    now_us: Option<unsafe extern "C" fn(*mut c_void) -> u64>,
+    set_connection_state: Option<unsafe extern "C" fn(*mut c_void, u32)>,
+    request_recalibration: Option<unsafe extern "C" fn(*mut c_void)>,
+    set_gauge: Option<unsafe extern "C" fn(*mut c_void, *const c_char, f64)>,
+    add_counter: Option<unsafe extern "C" fn(*mut c_void, *const c_char, u64)>,
+}
+
+// SAFETY: The host allows its callbacks to be called from any thread until `destroy`.
+unsafe impl Send for RemoteHost {}
+unsafe impl Sync for RemoteHost {}
+
+impl Host for RemoteHost {
+    fn now_us(&self) -> u64 {
+        match self.now_us {
+            Some(now_us) => unsafe { now_us(self.context) },
+            None => timestamp_us_now(),
+        }
+    }
+
+    fn connection_changed(&self, state: ConnectionState) {
+        if let Some(set_connection_state) = self.set_connection_state {
+            unsafe { set_connection_state(self.context, state as u32) };
+        }
+    }
+
+    fn request_recalibration(&self) {
+        if let Some(request_recalibration) = self.request_recalibration {
+            unsafe { request_recalibration(self.context) };
+        }
+    }
+
+    fn set_gauge(&self, name: &str, value: f64) {
+        if let (Some(set_gauge), Ok(name)) = (self.set_gauge, CString::new(name)) {
+            unsafe { set_gauge(self.context, name.as_ptr(), value) };
+        }
+    }
+
+    fn add_counter(&self, name: &str, delta: u64) {
+        if let (Some(add_counter), Ok(name)) = (self.add_counter, CString::new(name)) {
+            unsafe { add_counter(self.context, name.as_ptr(), delta) };
+        }
+    }
+}
+
+/// Host side of [`VrftHostContext`]: the services the callbacks forward to, and the
+/// strings the context points into.
+struct HostContextOwner {
+    services: HostServices,
+    log_target: CString,
+    state_dir: Option<CString>,
+}
+
+unsafe extern "C" fn host_now_us(context: *mut c_void) -> u64 {
+    if context.is_null() {
+        return timestamp_us_now();
+    }
+    (*(context as *const HostContextOwner)).services.now_us()
+}
+
+unsafe extern "C" fn host_set_connection_state(context: *mut c_void, state: u32) {
+    if context.is_null() {
+        return;
+    }
+    if let Some(state) = ConnectionState::from_raw(state) {
+        (*(context as *const HostContextOwner))
+            .services
+            .set_connection_state(state);
+    }
+}
+
+unsafe extern "C" fn host_request_recalibration(context: *mut c_void) {
+    if context.is_null() {
+        return;
+    }
+    (*(context as *const HostContextOwner))
+        .services
+        .request_recalibration();
+}
+
+unsafe extern "C" fn host_set_gauge(context: *mut c_void, name: *const c_char, value: f64) {
+    if context.is_null() || name.is_null() {
+        return;
+    }
+    let name = CStr::from_ptr(name).to_string_lossy();
+    (*(context as *const HostContextOwner))
+        .services
+        .set_gauge(&name, value);
+}
+
+unsafe extern "C" fn host_add_counter(context: *mut c_void, name: *const c_char, delta: u64) {
+    if context.is_null() || name.is_null() {
+        return;
+    }
+    let name = CStr::from_ptr(name).to_string_lossy();
+    (*(context as *const HostContextOwner))
+        .services
+        .add_counter(&name, delta);
+}
+
+/// [`ChannelStatus`] as it crosses the ABI boundary. `valid` is a byte, nonzero when the
+/// channel was tracked, so no value a module writes can make an invalid `bool`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VrftChannelStatus {
+    pub valid: u8,
+    pub confidence: f32,
+}
+
+impl From<ChannelStatus> for VrftChannelStatus {
+    fn from(status: ChannelStatus) -> Self {
+        Self {
+            valid: status.valid.into(),
+            confidence: status.confidence,
+        }
+    }
+}
+
+impl From<VrftChannelStatus> for ChannelStatus {
+    fn from(status: VrftChannelStatus) -> Self {
+        Self {
+            valid: status.valid != 0,
+            confidence: status.confidence,
+        }
+    }
+}
+
+/// [`UnifiedSingleEyeData`] as it crosses the ABI boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VrftSingleEyeData {
+    pub gaze: Vec3,
+    pub pupil_diameter_mm: f32,
+    pub openness: f32,
+    pub status: VrftChannelStatus,
+}
+
+impl From<UnifiedSingleEyeData> for VrftSingleEyeData {
+    fn from(eye: UnifiedSingleEyeData) -> Self {
+        Self {
+            gaze: eye.gaze,
+            pupil_diameter_mm: eye.pupil_diameter_mm,
+            openness: eye.openness,
+            status: eye.status.into(),
+        }
+    }
+}
+
+impl From<VrftSingleEyeData> for UnifiedSingleEyeData {
+    fn from(eye: VrftSingleEyeData) -> Self {
+        Self {
+            gaze: eye.gaze,
+            pupil_diameter_mm: eye.pupil_diameter_mm,
+            openness: eye.openness,
+            status: eye.status.into(),
+        }
+    }
+}
+
+/// [`UnifiedEyeData`] as it crosses the ABI boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VrftEyeData {
+    pub left: VrftSingleEyeData,
+    pub right: VrftSingleEyeData,
+    pub max_dilation: f32,
+    pub min_dilation: f32,
+    pub left_diameter: f32,
+    pub right_diameter: f32,
+}
+
+impl From<&UnifiedEyeData> for VrftEyeData {
+    fn from(eye: &UnifiedEyeData) -> Self {
+        Self {
+            left: eye.left.into(),
+            right: eye.right.into(),
+            max_dilation: eye.max_dilation,
+            min_dilation: eye.min_dilation,
+            left_diameter: eye.left_diameter,
+            right_diameter: eye.right_diameter,
+        }
+    }
+}
+
+impl From<VrftEyeData> for UnifiedEyeData {
+    fn from(eye: VrftEyeData) -> Self {
+        Self {
+            left: eye.left.into(),
+            right: eye.right.into(),
+            max_dilation: eye.max_dilation,
+            min_dilation: eye.min_dilation,
+            left_diameter: eye.left_diameter,
+            right_diameter: eye.right_diameter,
+        }
+    }
+}
+
+/// [`UnifiedHeadData`] as it crosses the ABI boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VrftHeadData {
+    pub head_yaw: f32,
+    pub head_pitch: f32,
+    pub head_roll: f32,
+    pub head_pos_x: f32,
+    pub head_pos_y: f32,
+    pub head_pos_z: f32,
+    pub status: VrftChannelStatus,
+}
+
+impl From<UnifiedHeadData> for VrftHeadData {
+    fn from(head: UnifiedHeadData) -> Self {
+        Self {
+            head_yaw: head.head_yaw,
+            head_pitch: head.head_pitch,
+            head_roll: head.head_roll,
+            head_pos_x: head.head_pos_x,
+            head_pos_y: head.head_pos_y,
+            head_pos_z: head.head_pos_z,
+            status: head.status.into(),
+        }
+    }
+}
+
+impl From<VrftHeadData> for UnifiedHeadData {
+    fn from(head: VrftHeadData) -> Self {
+        Self {
+            head_yaw: head.head_yaw,
+            head_pitch: head.head_pitch,
+            head_roll: head.head_roll,
+            head_pos_x: head.head_pos_x,
+            head_pos_y: head.head_pos_y,
+            head_pos_z: head.head_pos_z,
+            status: head.status.into(),
+        }
+    }
+}
+
+/// One frame of tracking data as it crosses the ABI boundary.
+///
+/// `shapes` and `shape_confidences` each point at `shape_count` host-owned values that stay
+/// valid for the duration of the `update` call only.
+#[repr(C)]
+pub struct VrftTrackingFrame {
+    pub eye: VrftEyeData,
+    pub head: VrftHeadData,
+    pub face: VrftChannelStatus,
+    pub shapes: *mut UnifiedExpressionShape,
+    pub shape_confidences: *mut f32,
+    pub shape_count: usize,
+    /// Capture time in microseconds since the UNIX epoch; 0 lets the host stamp the frame.
+    pub timestamp_us: u64,
+}
+
+/// Host callback through which a streaming module submits frames.
+///
+/// `submit` may be called from any thread, but not concurrently, until `unload` returns.
+/// The frame and everything it points at only need to stay valid for the call; the host
+/// copies it and never writes through its pointers.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VrftFrameSink {
+    /// `size_of::<VrftFrameSink>()` as seen by the host.
+    pub struct_size: u32,
+    pub context: *mut c_void,
+    pub submit: Option<unsafe extern "C" fn(*mut c_void, *const VrftTrackingFrame)>,
+}
+
+// SAFETY: The host guarantees `submit` may be called from the module's threads.
+unsafe impl Send for VrftFrameSink {}
+unsafe impl Sync for VrftFrameSink {}
+
+/// [`ModuleCapabilities`] flags of [`VrftModuleDescriptor::capabilities`].
+pub const VRFT_CAPABILITY_EYE_GAZE: u32 = 1 << 0;
+pub const VRFT_CAPABILITY_EYE_OPENNESS: u32 = 1 << 1;
+pub const VRFT_CAPABILITY_PUPIL: u32 = 1 << 2;
+pub const VRFT_CAPABILITY_EXPRESSIONS: u32 = 1 << 3;
+pub const VRFT_CAPABILITY_HEAD: u32 = 1 << 4;
+
+fn capability_flags(capabilities: ModuleCapabilities) -> u32 {
+    [
+        (capabilities.eye_gaze, VRFT_CAPABILITY_EYE_GAZE),
+        (capabilities.eye_openness, VRFT_CAPABILITY_EYE_OPENNESS),
+        (capabilities.pupil, VRFT_CAPABILITY_PUPIL),
+        (capabilities.expressions, VRFT_CAPABILITY_EXPRESSIONS),
+        (capabilities.head, VRFT_CAPABILITY_HEAD),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .fold(0, |flags, (_, flag)| flags | flag)
+}
+
+/// Unknown flags are ignored.
+fn capabilities_from_flags(flags: u32) -> ModuleCapabilities {
+    ModuleCapabilities {
+        eye_gaze: flags & VRFT_CAPABILITY_EYE_GAZE != 0,
+        eye_openness: flags & VRFT_CAPABILITY_EYE_OPENNESS != 0,
+        pupil: flags & VRFT_CAPABILITY_PUPIL != 0,
+        expressions: flags & VRFT_CAPABILITY_EXPRESSIONS != 0,
+        head: flags & VRFT_CAPABILITY_HEAD != 0,
+    }
+}
+
+/// Module metadata as it crosses the ABI boundary.
+///
+/// Everything it points at is owned by the module and stays valid until `destroy`.
+#[repr(C)]
+pub struct VrftModuleDescriptor {
+    /// `size_of::<VrftModuleDescriptor>()` as seen by the module.
+    pub struct_size: u32,
+    /// Null-terminated UTF-8 strings; null is treated as empty.
+    pub name: *const c_char,
+    pub version: *const c_char,
+    pub vendor: *const c_char,
+    /// `VRFT_CAPABILITY_*` flags.
+    pub capabilities: u32,
+    /// `expression_count` `UnifiedExpressions` indices the module drives.
+    pub expressions: *const u32,
+    pub expression_count: usize,
+    /// Null-terminated JSON Schema for the module's settings, or null.
+    pub settings_schema: *const c_char,
+}
+
+/// Function table describing one module instance.
+///
+/// Every function receives `instance` as its first argument. `destroy` is called exactly
+/// once, after which the host never touches `instance` again.
+#[repr(C)]
+pub struct VrftModuleVTable {
+    /// Must equal [`VRFT_ABI_VERSION`].
+    pub abi_version: u32,
+    /// `size_of::<VrftModuleVTable>()` as seen by the module.
+    pub struct_size: u32,
+    pub instance: *mut c_void,
+    pub initialize: Option<unsafe extern "C" fn(*mut c_void, *const VrftHostContext) -> i32>,
+    /// Returns a [`TrackingState`] value; `VRFT_OK` is `NewFrame`.
+    pub update: Option<unsafe extern "C" fn(*mut c_void, *mut VrftTrackingFrame) -> i32>,
+    pub unload: Option<unsafe extern "C" fn(*mut c_void)>,
+    pub destroy: Option<unsafe extern "C" fn(*mut c_void)>,
+    /// Optional; may be null or return null if the module does not describe itself.
+    pub describe: Option<unsafe extern "C" fn(*mut c_void) -> *const VrftModuleDescriptor>,
+    /// Optional; receives the settings as a null-terminated JSON string.
+    pub configure: Option<unsafe extern "C" fn(*mut c_void, *const c_char) -> i32>,
+    /// Optional; returns nonzero if the module will push frames through the sink from now
+    /// on instead of being polled through `update`.
+    pub start_streaming: Option<unsafe extern "C" fn(*mut c_void, *const VrftFrameSink) -> u8>,
+}
+
+impl VrftModuleVTable {
+    /// An empty table for `vrft_create_module` to fill in.
+    pub fn empty() -> Self {
+        Self {
+            abi_version: 0,
+            struct_size: 0,
+            instance: std::ptr::null_mut(),
+            initialize: None,
+            update: None,
+            unload: None,
+            destroy: None,
+            describe: None,
+            configure: None,
+            start_streaming: None,
+        }
+    }
+}
+
+/// Module-side state behind `instance`.
+struct ExportedModule<T> {
+    module: T,
+    scratch: UnifiedTrackingData,
+    descriptor: Option<ExportedDescriptor>,
+}
+
+/// A [`VrftModuleDescriptor`] together with the buffers it points into.
+struct ExportedDescriptor {
+    _strings: [CString; 3],
+    _settings_schema: Option<CString>,
+    _expressions: Vec<u32>,
+    raw: VrftModuleDescriptor,
+}
+
+impl ExportedDescriptor {
+    fn new(descriptor: &ModuleDescriptor) -> Self {
+        let c_string = |s: &str| CString::new(s.replace('\0', "")).unwrap_or_default();
+        let strings = [
+            c_string(&descriptor.name),
+            c_string(&descriptor.version),
+            c_string(&descriptor.vendor),
+        ];
+        let expressions: Vec<u32> = descriptor
+            .expressions
+            .iter()
+            .map(|&e| e.index() as u32)
+            .collect();
+        let settings_schema = descriptor
+            .settings_schema
+            .as_ref()
+            .map(|schema| c_string(&schema.to_string()));
+
+        // The CString and Vec buffers live on the heap, so moving them into the struct
+        // keeps these pointers valid.
+        let raw = VrftModuleDescriptor {
+            struct_size: std::mem::size_of::<VrftModuleDescriptor>() as u32,
+            name: strings[0].as_ptr(),
+            version: strings[1].as_ptr(),
+            vendor: strings[2].as_ptr(),
+            capabilities: capability_flags(descriptor.capabilities),
+            expressions: expressions.as_ptr(),
+            expression_count: expressions.len(),
+            settings_schema: settings_schema
+                .as_ref()
+                .map_or(std::ptr::null(), |schema| schema.as_ptr()),
+        };
+
+        Self {
+            _strings: strings,
+            _expressions: expressions,
+            _settings_schema: settings_schema,
+            raw,
+        }
+    }
+}
+
+/// Runs `f`, returning `fallback` if it panics, so the panic does not unwind into the host.
+fn contain<R>(fallback: R, f: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// Writes a function table for `module` into `out`.
+///
+/// Intended to be the whole body of a Rust module's `vrft_create_module`.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of a `VrftModuleVTable`.
+pub unsafe fn export_module<T: TrackingModule + 'static>(
+    out: *mut VrftModuleVTable,
+    module: T,
+) -> i32 {
+    if out.is_null() {
+        return VRFT_ERROR;
+    }
+
+    let descriptor = module.descriptor().as_ref().map(ExportedDescriptor::new);
+    let exported = Box::new(ExportedModule {
+        module,
+        scratch: UnifiedTrackingData::default(),
+        descriptor,
+    });
+
+    out.write(VrftModuleVTable {
+        abi_version: VRFT_ABI_VERSION,
+        struct_size: std::mem::size_of::<VrftModuleVTable>() as u32,
+        instance: Box::into_raw(exported) as *mut c_void,
+        initialize: Some(initialize_trampoline::<T>),
+        update: Some(update_trampoline::<T>),
+        unload: Some(unload_trampoline::<T>),
+        destroy: Some(destroy_trampoline::<T>),
+        describe: Some(describe_trampoline::<T>),
+        configure: Some(configure_trampoline::<T>),
+        start_streaming: Some(start_streaming_trampoline::<T>),
+    });
+
+    VRFT_OK
+}
+
+unsafe extern "C" fn initialize_trampoline<T: TrackingModule>(
+    instance: *mut c_void,
+    host: *const VrftHostContext,
+) -> i32 {
+    if instance.is_null() || host.is_null() {
+        return VRFT_ERROR;
+    }
+    contain(VRFT_FAULT, || initialize_module::<T>(instance, &*host))
+}
+
+unsafe fn initialize_module<T: TrackingModule>(
+    instance: *mut c_void,
+    host: &VrftHostContext,
+) -> i32 {
+    let exported = &mut *(instance as *mut ExportedModule<T>);
+
+    let target = if host.log_target.is_null() {
+        "module".to_string()
+    } else {
+        CStr::from_ptr(host.log_target)
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    let logger = ModuleLogger::new(host.log, target);
+    let error_logger = logger.clone();
+    let services = if (host.struct_size as usize) < std::mem::size_of::<VrftHostContext>() {
+        HostServices::detached(logger)
+    } else {
+        let state_dir = (!host.state_dir.is_null())
+            .then(|| PathBuf::from(CStr::from_ptr(host.state_dir).to_string_lossy().as_ref()));
+        let remote = RemoteHost {
+            context: host.context,
+            now_us: host.now_us,
+            set_connection_state: host.set_connection_state,
+            request_recalibration: host.request_recalibration,
+            set_gauge: host.set_gauge,
+            add_counter: host.add_counter,
+        };
+        HostServices::new(logger, state_dir, Arc::new(remote))
+    };
+    match exported.module.initialize(services) {
+        Ok(()) => VRFT_OK,
+        Err(e) => {
+            error_logger.error(&format!("initialize failed: {:#}", e));
+            VRFT_ERROR
+        }
+    }
+}
+
+unsafe extern "C" fn update_trampoline<T: TrackingModule>(
+    instance: *mut c_void,
+    frame: *mut VrftTrackingFrame,
+) -> i32 {
+    // `VRFT_ERROR` would read as `NoNewFrame` here.
+    if instance.is_null() || frame.is_null() {
+        return TrackingState::Fatal as i32;
+    }
+    contain(VRFT_FAULT, || update_module::<T>(instance, &mut *frame))
+}
+
+unsafe fn update_module<T: TrackingModule>(
+    instance: *mut c_void,
+    frame: &mut VrftTrackingFrame,
+) -> i32 {
+    let exported = &mut *(instance as *mut ExportedModule<T>);
+
+    let shapes: &mut [UnifiedExpressionShape] = if frame.shapes.is_null() {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(frame.shapes, frame.shape_count)
+    };
+
+    let confidences: &mut [f32] = if frame.shape_confidences.is_null() {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(frame.shape_confidences, frame.shape_count)
+    };
+
+    let scratch = &mut exported.scratch;
+    scratch.eye = frame.eye.into();
+    scratch.head = frame.head.into();
+    scratch.face = frame.face.into();
+    scratch.timestamp_us = frame.timestamp_us;
+    let count = shapes.len().min(scratch.shapes.len());
+    scratch.shapes[..count].copy_from_slice(&shapes[..count]);
+    let confidence_count = confidences.len().min(scratch.shape_confidences.len());
+    scratch.shape_confidences[..confidence_count].copy_from_slice(&confidences[..confidence_count]);
+
+    let state = exported.module.update(scratch);
+
+    frame.eye = (&scratch.eye).into();
+    frame.head = scratch.head.into();
+    frame.face = scratch.face.into();
+    frame.timestamp_us = scratch.timestamp_us;
+    shapes[..count].copy_from_slice(&scratch.shapes[..count]);
+    confidences[..confidence_count].copy_from_slice(&scratch.shape_confidences[..confidence_count]);
+
+    state as i32
+}
+
+unsafe extern "C" fn unload_trampoline<T: TrackingModule>(instance: *mut c_void) {
+    if instance.is_null() {
+        return;
+    }
+    let exported = &mut *(instance as *mut ExportedModule<T>);
+    contain((), || exported.module.unload());
+}
+
+unsafe extern "C" fn destroy_trampoline<T: TrackingModule>(instance: *mut c_void) {
+    if instance.is_null() {
+        return;
+    }
+    let exported = Box::from_raw(instance as *mut ExportedModule<T>);
+    contain((), move || drop(exported));
+}
+
+unsafe extern "C" fn describe_trampoline<T: TrackingModule>(
+    instance: *mut c_void,
+) -> *const VrftModuleDescriptor {
+    if instance.is_null() {
+        return std::ptr::null();
+    }
+    let exported = &*(instance as *const ExportedModule<T>);
+    exported
+        .descriptor
+        .as_ref()
+        .map_or(std::ptr::null(), |descriptor| &descriptor.raw)
+}
+
+unsafe extern "C" fn configure_trampoline<T: TrackingModule>(
+    instance: *mut c_void,
+    settings: *const c_char,
+) -> i32 {
+    if instance.is_null() || settings.is_null() {
+        return VRFT_ERROR;
+    }
+    let exported = &mut *(instance as *mut ExportedModule<T>);
+    let settings = match serde_json::from_slice(CStr::from_ptr(settings).to_bytes()) {
+        Ok(value) => ModuleSettings::new(value),
+        Err(_) => return VRFT_ERROR,
+    };
+    contain(VRFT_FAULT, || match exported.module.configure(&settings) {
+        Ok(()) => VRFT_OK,
+        Err(_) => VRFT_ERROR,
+    })
+}
+
+unsafe extern "C" fn start_streaming_trampoline<T: TrackingModule>(
+    instance: *mut c_void,
+    sink: *const VrftFrameSink,
+) -> u8 {
+    if instance.is_null() || sink.is_null() {
+        return 0;
+    }
+    let exported = &mut *(instance as *mut ExportedModule<T>);
+    let remote = *sink;
+    let Some(submit) = remote.submit else {
+        return 0;
+    };
+    if (remote.struct_size as usize) < std::mem::size_of::<VrftFrameSink>() {
+        return 0;
+    }
+
+    let sink = FrameSink::new(move |data| {
+        let remote = &remote;
+        let frame = VrftTrackingFrame {
+            eye: (&data.eye).into(),
+            head: data.head.into(),
+            face: data.face.into(),
+            shapes: data.shapes.as_ptr() as *mut UnifiedExpressionShape,
+            shape_confidences: data.shape_confidences.as_ptr() as *mut f32,
+            shape_count: data.shapes.len().min(data.shape_confidences.len()),
+            timestamp_us: data.timestamp_us,
+        };
+        // SAFETY: The frame is only read for the duration of the call.
+        unsafe { submit(remote.context, &frame) };
+    });
+    contain(false, || exported.module.start_streaming(sink)).into()
+}
+
+/// Host side of [`VrftFrameSink`]: copies the frame and hands it to the boxed [`FrameSink`].
+unsafe extern "C" fn host_submit_trampoline(context: *mut c_void, frame: *const VrftTrackingFrame) {
+    if context.is_null() || frame.is_null() {
+        return;
+    }
+    let sink = &*(context as *const FrameSink);
+    let frame = &*frame;
+
+    let mut data = UnifiedTrackingData {
+        eye: frame.eye.into(),
+        head: frame.head.into(),
+        face: frame.face.into(),
+        timestamp_us: frame.timestamp_us,
+        ..Default::default()
+    };
+    if !frame.shapes.is_null() {
+        let shapes = std::slice::from_raw_parts(frame.shapes, frame.shape_count);
+        let count = shapes.len().min(data.shapes.len());
+        data.shapes[..count].copy_from_slice(&shapes[..count]);
+    }
+    if !frame.shape_confidences.is_null() {
+        let confidences = std::slice::from_raw_parts(frame.shape_confidences, frame.shape_count);
+        let count = confidences.len().min(data.shape_confidences.len());
+        data.shape_confidences[..count].copy_from_slice(&confidences[..count]);
+    }
+    sink.submit(&data);
+}
+
+/// Reads the descriptor of a validated table, if it provides one.
+unsafe fn read_descriptor(vtable: &VrftModuleVTable) -> Option<ModuleDescriptor> {
+    let raw = vtable.describe?(vtable.instance);
+    if raw.is_null() {
+        return None;
+    }
+    let raw = &*raw;
+    if (raw.struct_size as usize) < std::mem::size_of::<VrftModuleDescriptor>() {
+        return None;
+    }
+
+    let string = |ptr: *const c_char| {
+        if ptr.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(ptr).to_string_lossy().into_owned()
+        }
+    };
+    let expressions = if raw.expressions.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(raw.expressions, raw.expression_count)
+            .iter()
+            .filter_map(|&index| UnifiedExpressions::try_from(index as usize).ok())
+            .collect()
+    };
+
+    let settings_schema = if raw.settings_schema.is_null() {
+        None
+    } else {
+        serde_json::from_slice(CStr::from_ptr(raw.settings_schema).to_bytes()).ok()
+    };
+
+    Some(ModuleDescriptor {
+        name: string(raw.name),
+        version: string(raw.version),
+        vendor: string(raw.vendor),
+        capabilities: capabilities_from_flags(raw.capabilities),
+        expressions,
+        settings_schema,
+    })
+}
+
+/// Raises a [`VRFT_FAULT`] returned by `call` as a panic on the host side.
+fn fault(call: &str) -> ! {
+    panic!("module faulted in {}", call)
+}
+
+/// Host-side [`TrackingModule`] that drives a module through its [`VrftModuleVTable`].
+///
+/// The library that produced the table must stay loaded for as long as this value lives.
+///
+/// Calls that return [`VRFT_FAULT`] panic; the caller is expected to contain that.
+pub struct AbiModule {
+    vtable: VrftModuleVTable,
+    /// Behind the host context's pointers; freed only after the instance is destroyed.
+    host: Option<Box<HostContextOwner>>,
+    descriptor: Option<ModuleDescriptor>,
+    /// Sink handed to a streaming module; freed only after the instance is destroyed.
+    sink: Option<Box<FrameSink>>,
+}
+
+// SAFETY: The ABI contract requires modules to tolerate calls from any single host thread;
+// the host never calls into one instance concurrently.
+unsafe impl Send for AbiModule {}
+
+impl AbiModule {
+    /// Validates a table filled in by `vrft_create_module`.
+    ///
+    /// On error the instance is destroyed if the table provides a way to do so.
+    ///
+    /// # Safety
+    ///
+    /// The function pointers in `vtable` must be valid for the lifetime of the returned value.
+    pub unsafe fn from_vtable(vtable: VrftModuleVTable) -> Result<Self> {
+        let problem = if vtable.abi_version != VRFT_ABI_VERSION {
+            Some(format!(
+                "module table has ABI version {}, host expects {}",
+                vtable.abi_version, VRFT_ABI_VERSION
+            ))
+        } else if (vtable.struct_size as usize) < std::mem::size_of::<VrftModuleVTable>() {
+            Some(format!(
+                "module table is {} bytes, host expects at least {}",
+                vtable.struct_size,
+                std::mem::size_of::<VrftModuleVTable>()
+            ))
+        } else if vtable.instance.is_null() {
+            Some("module table has a null instance".to_string())
+        } else if vtable.initialize.is_none()
+            || vtable.update.is_none()
+            || vtable.unload.is_none()
+            || vtable.destroy.is_none()
+        {
+            Some("module table is missing required functions".to_string())
+        } else {
+            None
+        };
+
+        if let Some(problem) = problem {
+            // Only trust the destroy pointer if the table layout is the one we know.
+            if vtable.abi_version == VRFT_ABI_VERSION && !vtable.instance.is_null() {
+                if let Some(destroy) = vtable.destroy {
+                    destroy(vtable.instance);
+                }
+            }
+            bail!(problem);
+        }
+
+        let descriptor = read_descriptor(&vtable);
+        Ok(Self {
+            vtable,
+            host: None,
+            descriptor,
+            sink: None,
+        })
+    }
+}
+
+impl TrackingModule for AbiModule {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        let owner = Box::new(HostContextOwner {
+            log_target: CString::new(host.logger().target()).unwrap_or_default(),
+            state_dir: host
+                .state_dir()
+                .and_then(|dir| CString::new(dir.to_string_lossy().as_bytes()).ok()),
+            services: host,
+        });
+        let context = VrftHostContext {
+            struct_size: std::mem::size_of::<VrftHostContext>() as u32,
+            log: owner.services.logger().callback(),
+            log_target: owner.log_target.as_ptr(),
+            context: &*owner as *const HostContextOwner as *mut c_void,
+            now_us: Some(host_now_us),
+            state_dir: owner
+                .state_dir
+                .as_ref()
+                .map_or(std::ptr::null(), |dir| dir.as_ptr()),
+            set_connection_state: Some(host_set_connection_state),
+            request_recalibration: Some(host_request_recalibration),
+            set_gauge: Some(host_set_gauge),
+            add_counter: Some(host_add_counter),
+        };
+        // The module may keep these pointers, so they have to outlive the call.
+        self.host = Some(owner);
+
+        let initialize = self.vtable.initialize.expect("validated in from_vtable");
+        match unsafe { initialize(self.vtable.instance, &context) } {
+            VRFT_OK => Ok(()),
+            VRFT_FAULT => fault("initialize"),
+            code => bail!("module initialize returned {}", code),
+        }
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        data.shape_confidences.resize(data.shapes.len(), 1.0);
+        let mut frame = VrftTrackingFrame {
+            eye: (&data.eye).into(),
+            head: data.head.into(),
+            face: data.face.into(),
+            shapes: data.shapes.as_mut_ptr(),
+            shape_confidences: data.shape_confidences.as_mut_ptr(),
+            shape_count: data.shapes.len(),
+            timestamp_us: data.timestamp_us,
+        };
+
+        let update = self.vtable.update.expect("validated in from_vtable");
+        let code = unsafe { update(self.vtable.instance, &mut frame) };
+
+        data.eye = frame.eye.into();
+        data.head = frame.head.into();
+        data.face = frame.face.into();
+        data.timestamp_us = frame.timestamp_us;
+
+        if code == VRFT_FAULT {
+            fault("update");
+        }
+        // A code this version does not know means the module is broken.
+        TrackingState::from_raw(code).unwrap_or(TrackingState::Fatal)
+    }
+
+    fn unload(&mut self) {
+        let unload = self.vtable.unload.expect("validated in from_vtable");
+        unsafe { unload(self.vtable.instance) };
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+        let Some(configure) = self.vtable.configure else {
+            return Ok(());
+        };
+        let json = CString::new(settings.value().to_string())?;
+        match unsafe { configure(self.vtable.instance, json.as_ptr()) } {
+            VRFT_OK => Ok(()),
+            VRFT_FAULT => fault("configure"),
+            code => bail!("module configure returned {}", code),
+        }
+    }
+
+    fn start_streaming(&mut self, sink: FrameSink) -> bool {
+        let Some(start_streaming) = self.vtable.start_streaming else {
+            return false;
+        };
+        if self.sink.is_some() {
+            return false;
+        }
+
+        let sink = Box::new(sink);
+        let remote = VrftFrameSink {
+            struct_size: std::mem::size_of::<VrftFrameSink>() as u32,
+            context: &*sink as *const FrameSink as *mut c_void,
+            submit: Some(host_submit_trampoline),
+        };
+        // Keep the sink alive even if the module declines; it may have copied the pointer.
+        self.sink = Some(sink);
+        unsafe { start_streaming(self.vtable.instance, &remote) != 0 }
+    }
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        self.descriptor.clone()
+    }
+}
+
+impl Drop for AbiModule {
+    fn drop(&mut self) {
+        let destroy = self.vtable.destroy.expect("validated in from_vtable");
+        unsafe { destroy(self.vtable.instance) };
+    }
+}
@@ -0,0 +1,126 @@
+//! Self-description of a tracking module: who made it and which data it produces.
+//!
+//! The host uses the descriptor to tell a channel the tracker cannot produce apart from one
+//! that is merely at rest, e.g. "no tongue tracking" versus "tongue at 0".
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{FacialRegion, UnifiedExpressions};
+
+/// Channels a module fills in. Crosses the C ABI as `VRFT_CAPABILITY_*` flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleCapabilities {
+    pub eye_gaze: bool,
+    pub eye_openness: bool,
+    pub pupil: bool,
+    pub expressions: bool,
+    pub head: bool,
+}
+
+impl ModuleCapabilities {
+    pub const NONE: Self = Self {
+        eye_gaze: false,
+        eye_openness: false,
+        pupil: false,
+        expressions: false,
+        head: false,
+    };
+
+    pub const ALL: Self = Self {
+        eye_gaze: true,
+        eye_openness: true,
+        pupil: true,
+        expressions: true,
+        head: true,
+    };
+
+    /// Whether any eye channel is produced.
+    pub fn eye(&self) -> bool {
+        self.eye_gaze || self.eye_openness || self.pupil
+    }
+
+    /// Channels produced by either set.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            eye_gaze: self.eye_gaze || other.eye_gaze,
+            eye_openness: self.eye_openness || other.eye_openness,
+            pupil: self.pupil || other.pupil,
+            expressions: self.expressions || other.expressions,
+            head: self.head || other.head,
+        }
+    }
+}
+
+/// Display metadata and capabilities of a module.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub version: String,
+    pub vendor: String,
+    pub capabilities: ModuleCapabilities,
+    /// Expressions the module actually drives. Only meaningful with `capabilities.expressions`.
+    pub expressions: Vec<UnifiedExpressions>,
+    /// JSON Schema for the module's settings (see [`crate::settings::validate`]).
+    pub settings_schema: Option<Value>,
+}
+
+impl ModuleDescriptor {
+    pub fn new(name: &str, version: &str, vendor: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            vendor: vendor.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_capabilities(mut self, capabilities: ModuleCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Declares the driven expressions and sets `capabilities.expressions` accordingly.
+    pub fn with_expressions(mut self, expressions: &[UnifiedExpressions]) -> Self {
+        self.expressions = expressions.to_vec();
+        self.capabilities.expressions = !expressions.is_empty();
+        self
+    }
+
+    pub fn with_settings_schema(mut self, schema: Value) -> Self {
+        self.settings_schema = Some(schema);
+        self
+    }
+
+    /// What a set of modules produces together, e.g. an eye tracker next to a face tracker.
+    pub fn combine(descriptors: &[ModuleDescriptor]) -> ModuleDescriptor {
+        let mut combined = ModuleDescriptor {
+            name: descriptors
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" + "),
+            ..Default::default()
+        };
+        for descriptor in descriptors {
+            combined.capabilities = combined.capabilities.union(descriptor.capabilities);
+            if descriptor.capabilities.expressions {
+                for &expr in &descriptor.expressions {
+                    if !combined.expressions.contains(&expr) {
+                        combined.expressions.push(expr);
+                    }
+                }
+            }
+        }
+        combined
+    }
+
+    pub fn drives(&self, expr: UnifiedExpressions) -> bool {
+        self.capabilities.expressions && self.expressions.contains(&expr)
+    }
+
+    /// Whether any expression in `region` is driven.
+    pub fn drives_region(&self, region: FacialRegion) -> bool {
+        self.capabilities.expressions && self.expressions.iter().any(|e| e.region() == region)
+    }
+}
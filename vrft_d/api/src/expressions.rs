@@ -0,0 +1,307 @@
+//! `UnifiedExpressions` and its metadata: names, facial regions, sides, mirror pairs and
+//! antagonist pairs.
+//!
+//! Code that needs the name of an expression or wants to work on a group of shapes should
+//! go through here instead of `format!("{:?}")` or hand-written index lists.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Part of the face an expression belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FacialRegion {
+    Eye,
+    Brow,
+    Nose,
+    Cheek,
+    Jaw,
+    Lip,
+    Mouth,
+    Tongue,
+    Throat,
+}
+
+impl FacialRegion {
+    pub const ALL: [FacialRegion; 9] = [
+        FacialRegion::Eye,
+        FacialRegion::Brow,
+        FacialRegion::Nose,
+        FacialRegion::Cheek,
+        FacialRegion::Jaw,
+        FacialRegion::Lip,
+        FacialRegion::Mouth,
+        FacialRegion::Tongue,
+        FacialRegion::Throat,
+    ];
+
+    /// All expressions in this region, in index order.
+    pub fn expressions(self) -> impl Iterator<Item = UnifiedExpressions> {
+        UnifiedExpressions::iter().filter(move |e| e.region() == self)
+    }
+}
+
+/// Side of the face an expression acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+    /// Expressions without a left/right counterpart, like `JawOpen`.
+    Center,
+}
+
+/// Expands to `$region` once per variant, so regions can be repeated alongside variants.
+macro_rules! region_of {
+    ($variant:ident, $region:ident) => {
+        FacialRegion::$region
+    };
+}
+
+macro_rules! unified_expressions {
+    ($( $region:ident => [ $( $variant:ident ),* $(,)? ] ),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[repr(usize)]
+        pub enum UnifiedExpressions {
+            $( $( $variant, )* )*
+            Max,
+        }
+
+        impl UnifiedExpressions {
+            /// Every expression in index order, without `Max`.
+            pub const ALL: [UnifiedExpressions; UnifiedExpressions::Max as usize] =
+                [$( $( UnifiedExpressions::$variant, )* )*];
+
+            const NAMES: [&'static str; UnifiedExpressions::Max as usize] =
+                [$( $( stringify!($variant), )* )*];
+
+            const REGIONS: [FacialRegion; UnifiedExpressions::Max as usize] =
+                [$( $( region_of!($variant, $region), )* )*];
+        }
+    };
+}
+
+unified_expressions! {
+    Eye => [EyeSquintRight, EyeSquintLeft, EyeWideRight, EyeWideLeft],
+    Brow => [
+        BrowPinchRight,
+        BrowPinchLeft,
+        BrowLowererRight,
+        BrowLowererLeft,
+        BrowInnerUpRight,
+        BrowInnerUpLeft,
+        BrowOuterUpRight,
+        BrowOuterUpLeft,
+    ],
+    Nose => [NasalDilationRight, NasalDilationLeft, NasalConstrictRight, NasalConstrictLeft],
+    Cheek => [
+        CheekSquintRight,
+        CheekSquintLeft,
+        CheekPuffRight,
+        CheekPuffLeft,
+        CheekSuckRight,
+        CheekSuckLeft,
+    ],
+    // Jaw exclusive expressions
+    Jaw => [
+        JawOpen,
+        JawRight,
+        JawLeft,
+        JawForward,
+        JawBackward,
+        JawClench,
+        JawMandibleRaise,
+        MouthClosed,
+    ],
+    Lip => [
+        LipSuckUpperRight,
+        LipSuckUpperLeft,
+        LipSuckLowerRight,
+        LipSuckLowerLeft,
+        LipSuckCornerRight,
+        LipSuckCornerLeft,
+        LipFunnelUpperRight,
+        LipFunnelUpperLeft,
+        LipFunnelLowerRight,
+        LipFunnelLowerLeft,
+        LipPuckerUpperRight,
+        LipPuckerUpperLeft,
+        LipPuckerLowerRight,
+        LipPuckerLowerLeft,
+    ],
+    // Upper lip raiser group
+    Mouth => [MouthUpperUpRight, MouthUpperUpLeft, MouthUpperDeepenRight, MouthUpperDeepenLeft],
+    Nose => [NoseSneerRight, NoseSneerLeft],
+    Mouth => [
+        // Lower lip depressor group
+        MouthLowerDownRight,
+        MouthLowerDownLeft,
+        // Mouth direction group
+        MouthUpperRight,
+        MouthUpperLeft,
+        MouthLowerRight,
+        MouthLowerLeft,
+        // Smile group
+        MouthCornerPullRight,
+        MouthCornerPullLeft,
+        MouthCornerSlantRight,
+        MouthCornerSlantLeft,
+        // Sad group
+        MouthFrownRight,
+        MouthFrownLeft,
+        MouthStretchRight,
+        MouthStretchLeft,
+        MouthDimpleRight,
+        MouthDimpleLeft,
+        MouthRaiserUpper,
+        MouthRaiserLower,
+        MouthPressRight,
+        MouthPressLeft,
+        MouthTightenerRight,
+        MouthTightenerLeft,
+    ],
+    Tongue => [
+        TongueOut,
+        TongueUp,
+        TongueDown,
+        TongueRight,
+        TongueLeft,
+        TongueRoll,
+        TongueBendDown,
+        TongueCurlUp,
+        TongueSquish,
+        TongueFlat,
+        TongueTwistRight,
+        TongueTwistLeft,
+    ],
+    // Throat/neck expressions
+    Throat => [SoftPalateClose, ThroatSwallow, NeckFlexRight, NeckFlexLeft],
+}
+
+/// Pairs of expressions that pull the same feature in opposite directions.
+///
+/// Directional pairs like `JawLeft`/`JawRight` are both mirrors and antagonists.
+const ANTAGONISTS: &[(UnifiedExpressions, UnifiedExpressions)] = {
+    use UnifiedExpressions::*;
+    &[
+        (EyeWideRight, EyeSquintRight),
+        (EyeWideLeft, EyeSquintLeft),
+        (BrowInnerUpRight, BrowLowererRight),
+        (BrowInnerUpLeft, BrowLowererLeft),
+        (NasalDilationRight, NasalConstrictRight),
+        (NasalDilationLeft, NasalConstrictLeft),
+        (CheekPuffRight, CheekSuckRight),
+        (CheekPuffLeft, CheekSuckLeft),
+        (JawRight, JawLeft),
+        (JawForward, JawBackward),
+        (MouthUpperRight, MouthUpperLeft),
+        (MouthLowerRight, MouthLowerLeft),
+        (MouthCornerPullRight, MouthFrownRight),
+        (MouthCornerPullLeft, MouthFrownLeft),
+        (TongueUp, TongueDown),
+        (TongueRight, TongueLeft),
+        (TongueCurlUp, TongueBendDown),
+        (TongueFlat, TongueSquish),
+        (TongueTwistRight, TongueTwistLeft),
+    ]
+};
+
+impl UnifiedExpressions {
+    /// Iterates over every expression in index order, without `Max`.
+    pub fn iter() -> impl Iterator<Item = UnifiedExpressions> {
+        Self::ALL.into_iter()
+    }
+
+    /// Index into `UnifiedTrackingData::shapes`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The variant name, e.g. `"JawOpen"`.
+    pub fn name(self) -> &'static str {
+        Self::NAMES.get(self as usize).copied().unwrap_or("Max")
+    }
+
+    pub fn region(self) -> FacialRegion {
+        Self::REGIONS
+            .get(self as usize)
+            .copied()
+            .unwrap_or(FacialRegion::Throat)
+    }
+
+    pub fn side(self) -> Side {
+        let name = self.name();
+        if name.ends_with("Left") {
+            Side::Left
+        } else if name.ends_with("Right") {
+            Side::Right
+        } else {
+            Side::Center
+        }
+    }
+
+    /// The same expression on the other side of the face, e.g. `CheekPuffLeft` for
+    /// `CheekPuffRight`. `None` for centered expressions.
+    pub fn mirror(self) -> Option<UnifiedExpressions> {
+        let name = self.name();
+        let mirrored = match self.side() {
+            Side::Left => format!("{}Right", name.strip_suffix("Left")?),
+            Side::Right => format!("{}Left", name.strip_suffix("Right")?),
+            Side::Center => return None,
+        };
+        mirrored.parse().ok()
+    }
+
+    /// The expression pulling the same feature the opposite way, e.g. `CheekSuckLeft`
+    /// for `CheekPuffLeft`.
+    pub fn antagonist(self) -> Option<UnifiedExpressions> {
+        ANTAGONISTS.iter().find_map(|&(a, b)| {
+            if a == self {
+                Some(b)
+            } else if b == self {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl TryFrom<usize> for UnifiedExpressions {
+    type Error = ();
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Self::ALL.get(value).copied().ok_or(())
+    }
+}
+
+impl fmt::Display for UnifiedExpressions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error returned when parsing an unknown expression name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseExpressionError(pub String);
+
+impl fmt::Display for ParseExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown expression '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseExpressionError {}
+
+impl FromStr for UnifiedExpressions {
+    type Err = ParseExpressionError;
+
+    /// Parses a variant name, e.g. `"JawOpen"`. `Max` is not accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .position(|&name| name == s)
+            .map(|index| Self::ALL[index])
+            .ok_or_else(|| ParseExpressionError(s.to_string()))
+    }
+}
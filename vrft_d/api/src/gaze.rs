@@ -0,0 +1,50 @@
+//! Conversions for the canonical gaze direction.
+//!
+//! `UnifiedSingleEyeData::gaze` is a normalized direction in head space with
+//! +X pointing right, +Y pointing up and +Z pointing forward. Modules and outputs that
+//! think in other representations convert through the helpers here instead of
+//! reinterpreting the vector themselves.
+//!
+//! - Yaw/pitch: radians, yaw positive to the right, pitch positive upwards.
+//! - Projection: the X and Y components of the direction, each in `[-1, 1]`.
+
+use glam::{Vec2, Vec3};
+
+/// Gaze direction when looking straight ahead.
+pub const FORWARD: Vec3 = Vec3::Z;
+
+/// Builds a direction from yaw and pitch in radians.
+pub fn from_yaw_pitch(yaw: f32, pitch: f32) -> Vec3 {
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    Vec3::new(sin_yaw * cos_pitch, sin_pitch, cos_yaw * cos_pitch)
+}
+
+/// Returns `(yaw, pitch)` in radians for a direction.
+pub fn to_yaw_pitch(direction: Vec3) -> Vec2 {
+    let d = direction.try_normalize().unwrap_or(FORWARD);
+    Vec2::new(d.x.atan2(d.z), d.y.clamp(-1.0, 1.0).asin())
+}
+
+/// Builds a forward-facing direction from its X/Y projection.
+///
+/// Projections longer than 1 are scaled back onto the unit circle.
+pub fn from_projection(projection: Vec2) -> Vec3 {
+    let p = if projection.length_squared() > 1.0 {
+        projection.normalize()
+    } else {
+        projection
+    };
+    let z = (1.0 - p.length_squared()).max(0.0).sqrt();
+    Vec3::new(p.x, p.y, z)
+}
+
+/// Returns the X/Y projection of a direction.
+pub fn to_projection(direction: Vec3) -> Vec2 {
+    direction.try_normalize().unwrap_or(FORWARD).truncate()
+}
+
+/// Normalizes `direction`, falling back to [`FORWARD`] for degenerate input.
+pub fn normalize(direction: Vec3) -> Vec3 {
+    direction.try_normalize().unwrap_or(FORWARD)
+}
@@ -0,0 +1,138 @@
+//! Services the host offers to modules.
+//!
+//! A module receives [`HostServices`] in [`TrackingModule::initialize`](crate::TrackingModule::initialize)
+//! and may keep it, or clones of it, for as long as it lives, including on its own threads.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{timestamp_us_now, ModuleLogger};
+
+/// Connection state of a module's device.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    #[default]
+    Disconnected = 0,
+    Connecting = 1,
+    Connected = 2,
+}
+
+impl ConnectionState {
+    /// Parses the value a module passed through the C ABI.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Disconnected),
+            1 => Some(Self::Connecting),
+            2 => Some(Self::Connected),
+            _ => None,
+        }
+    }
+}
+
+/// Host side of [`HostServices`]. The host implements this once per module.
+pub trait Host: Send + Sync {
+    /// Current time on the host clock, in microseconds since the UNIX epoch.
+    fn now_us(&self) -> u64 {
+        timestamp_us_now()
+    }
+
+    fn connection_changed(&self, state: ConnectionState);
+
+    fn request_recalibration(&self);
+
+    fn set_gauge(&self, name: &str, value: f64);
+
+    fn add_counter(&self, name: &str, delta: u64);
+}
+
+/// A host that ignores everything a module reports.
+struct NoHost;
+
+impl Host for NoHost {
+    fn connection_changed(&self, _state: ConnectionState) {}
+
+    fn request_recalibration(&self) {}
+
+    fn set_gauge(&self, _name: &str, _value: f64) {}
+
+    fn add_counter(&self, _name: &str, _delta: u64) {}
+}
+
+/// Host facilities handed to a module on initialization.
+#[derive(Clone)]
+pub struct HostServices {
+    logger: ModuleLogger,
+    state_dir: Option<PathBuf>,
+    host: Arc<dyn Host>,
+}
+
+impl HostServices {
+    pub fn new(logger: ModuleLogger, state_dir: Option<PathBuf>, host: Arc<dyn Host>) -> Self {
+        Self {
+            logger,
+            state_dir,
+            host,
+        }
+    }
+
+    /// Services that only log. Everything else the module reports is dropped.
+    pub fn detached(logger: ModuleLogger) -> Self {
+        Self::new(logger, None, Arc::new(NoHost))
+    }
+
+    pub fn logger(&self) -> &ModuleLogger {
+        &self.logger
+    }
+
+    /// Current time on the host clock, in microseconds since the UNIX epoch. Use it for
+    /// `UnifiedTrackingData::timestamp_us` when the device has no clock of its own.
+    pub fn now_us(&self) -> u64 {
+        self.host.now_us()
+    }
+
+    /// Directory, created by the host, where the module may persist its own configuration
+    /// and state across runs. `None` if the host has no place for it.
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.state_dir.as_deref()
+    }
+
+    /// Tells the host that the device connected, dropped or is being reconnected.
+    pub fn set_connection_state(&self, state: ConnectionState) {
+        self.host.connection_changed(state);
+    }
+
+    /// Asks the host to recalibrate, e.g. after the headset was put on by another user.
+    pub fn request_recalibration(&self) {
+        self.host.request_recalibration();
+    }
+
+    /// Publishes the current value of a gauge, such as the device frame rate or the
+    /// confidence the device reports. The host keeps the last value and its range.
+    pub fn set_gauge(&self, name: &str, value: f64) {
+        self.host.set_gauge(name, value);
+    }
+
+    /// Adds `delta` to a counter, such as frames received or reconnect attempts. The host
+    /// keeps the running total.
+    pub fn add_counter(&self, name: &str, delta: u64) {
+        self.host.add_counter(name, delta);
+    }
+
+    pub fn increment_counter(&self, name: &str) {
+        self.add_counter(name, 1);
+    }
+}
+
+impl fmt::Debug for HostServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostServices")
+            .field("logger", &self.logger.target())
+            .field("state_dir", &self.state_dir)
+            .finish_non_exhaustive()
+    }
+}
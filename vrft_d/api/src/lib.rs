@@ -1,16 +1,93 @@
-mod proxy;
+pub mod abi;
+pub mod descriptor;
+pub mod expressions;
+pub mod gaze;
+pub mod host;
+pub mod proxy;
+pub mod settings;
+pub mod sink;
+pub use descriptor::{ModuleCapabilities, ModuleDescriptor};
+pub use expressions::{FacialRegion, ParseExpressionError, Side, UnifiedExpressions};
+pub use host::{ConnectionState, Host, HostServices};
 pub use proxy::ProxyModule;
+pub use settings::ModuleSettings;
+pub use sink::FrameSink;
 
 use anyhow::Result;
-use glam::Vec2;
+use glam::{Vec2, Vec3};
 use serde::{Deserialize, Serialize};
 
+/// Validity and confidence of one tracking channel (an eye, the face or the head).
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStatus {
+    /// Whether the device tracked this channel for the current frame.
+    pub valid: bool,
+    /// Device-reported confidence in `[0, 1]`.
+    pub confidence: f32,
+}
+
+impl Default for ChannelStatus {
+    fn default() -> Self {
+        Self {
+            valid: true,
+            confidence: 1.0,
+        }
+    }
+}
+
+impl ChannelStatus {
+    /// Status for a channel the device lost this frame.
+    pub const LOST: Self = Self {
+        valid: false,
+        confidence: 0.0,
+    };
+
+    pub fn new(valid: bool, confidence: f32) -> Self {
+        Self {
+            valid,
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+
+    /// True when the channel is valid and at least `min_confidence` confident.
+    pub fn is_trusted(&self, min_confidence: f32) -> bool {
+        self.valid && self.confidence >= min_confidence
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct UnifiedSingleEyeData {
-    pub gaze: Vec2,
+    /// Normalized gaze direction: +X right, +Y up, +Z forward. See [`gaze`] for conversions.
+    pub gaze: Vec3,
     pub pupil_diameter_mm: f32,
     pub openness: f32,
+    #[serde(default)]
+    pub status: ChannelStatus,
+}
+
+impl Default for UnifiedSingleEyeData {
+    fn default() -> Self {
+        Self {
+            gaze: gaze::FORWARD,
+            pupil_diameter_mm: 0.0,
+            openness: 0.0,
+            status: ChannelStatus::default(),
+        }
+    }
+}
+
+impl UnifiedSingleEyeData {
+    /// Gaze as `(yaw, pitch)` in radians, see [`gaze::to_yaw_pitch`].
+    pub fn yaw_pitch(&self) -> Vec2 {
+        gaze::to_yaw_pitch(self.gaze)
+    }
+
+    /// Gaze as an X/Y projection, see [`gaze::to_projection`].
+    pub fn projection(&self) -> Vec2 {
+        gaze::to_projection(self.gaze)
+    }
 }
 
 #[repr(C)]
@@ -40,6 +117,8 @@ pub struct UnifiedHeadData {
     pub head_pos_x: f32,
     pub head_pos_y: f32,
     pub head_pos_z: f32,
+    #[serde(default)]
+    pub status: ChannelStatus,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -47,6 +126,27 @@ pub struct UnifiedTrackingData {
     pub eye: UnifiedEyeData,
     pub shapes: Vec<UnifiedExpressionShape>,
     pub head: UnifiedHeadData,
+    /// Status of the expression shapes as a whole.
+    #[serde(default)]
+    pub face: ChannelStatus,
+    /// Optional per-shape confidence in `[0, 1]`, indexed like `shapes`.
+    ///
+    /// Modules that do not report per-shape confidence leave every entry at 1.0.
+    #[serde(default = "default_shape_confidences")]
+    pub shape_confidences: Vec<f32>,
+    /// Capture time in microseconds since the UNIX epoch, see [`timestamp_us_now`].
+    ///
+    /// Modules should set this to when the device captured the frame. The host stamps
+    /// frames that arrive with 0 on receipt.
+    #[serde(default)]
+    pub timestamp_us: u64,
+    /// Monotonic frame counter assigned by the host; modules do not set it.
+    #[serde(default)]
+    pub sequence: u64,
+}
+
+fn default_shape_confidences() -> Vec<f32> {
+    vec![1.0; UnifiedExpressions::Max as usize]
 }
 
 impl Default for UnifiedTrackingData {
@@ -55,145 +155,36 @@ impl Default for UnifiedTrackingData {
             eye: UnifiedEyeData::default(),
             shapes: vec![UnifiedExpressionShape::default(); UnifiedExpressions::Max as usize],
             head: UnifiedHeadData::default(),
+            face: ChannelStatus::default(),
+            shape_confidences: default_shape_confidences(),
+            timestamp_us: 0,
+            sequence: 0,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
-pub enum UnifiedExpressions {
-    // Eye Gaze Expressions (Unused in shapes)
-
-    // Eye Expressions
-    EyeSquintRight = 0,
-    EyeSquintLeft,
-    EyeWideRight,
-    EyeWideLeft,
-
-    // Eyebrow Expressions
-    BrowPinchRight,
-    BrowPinchLeft,
-    BrowLowererRight,
-    BrowLowererLeft,
-    BrowInnerUpRight,
-    BrowInnerUpLeft,
-    BrowOuterUpRight,
-    BrowOuterUpLeft,
-
-    // Nose Expressions
-    NasalDilationRight,
-    NasalDilationLeft,
-    NasalConstrictRight,
-    NasalConstrictLeft,
-
-    // Cheek Expressions
-    CheekSquintRight,
-    CheekSquintLeft,
-    CheekPuffRight,
-    CheekPuffLeft,
-    CheekSuckRight,
-    CheekSuckLeft,
-
-    // Jaw Exclusive Expressions
-    JawOpen,
-    JawRight,
-    JawLeft,
-    JawForward,
-    JawBackward,
-    JawClench,
-    JawMandibleRaise,
-    MouthClosed,
-
-    // Lip Expressions
-    LipSuckUpperRight,
-    LipSuckUpperLeft,
-    LipSuckLowerRight,
-    LipSuckLowerLeft,
-    LipSuckCornerRight,
-    LipSuckCornerLeft,
-    LipFunnelUpperRight,
-    LipFunnelUpperLeft,
-    LipFunnelLowerRight,
-    LipFunnelLowerLeft,
-    LipPuckerUpperRight,
-    LipPuckerUpperLeft,
-    LipPuckerLowerRight,
-    LipPuckerLowerLeft,
-
-    // Upper lip raiser group
-    MouthUpperUpRight,
-    MouthUpperUpLeft,
-    MouthUpperDeepenRight,
-    MouthUpperDeepenLeft,
-    NoseSneerRight,
-    NoseSneerLeft,
-
-    // Lower lip depressor group
-    MouthLowerDownRight,
-    MouthLowerDownLeft,
-
-    // Mouth Direction group
-    MouthUpperRight,
-    MouthUpperLeft,
-    MouthLowerRight,
-    MouthLowerLeft,
-
-    // Smile group
-    MouthCornerPullRight,
-    MouthCornerPullLeft,
-    MouthCornerSlantRight,
-    MouthCornerSlantLeft,
-
-    // Sad group
-    MouthFrownRight,
-    MouthFrownLeft,
-    MouthStretchRight,
-    MouthStretchLeft,
-    MouthDimpleRight,
-    MouthDimpleLeft,
-    MouthRaiserUpper,
-    MouthRaiserLower,
-    MouthPressRight,
-    MouthPressLeft,
-    MouthTightenerRight,
-    MouthTightenerLeft,
-
-    // Tongue Expressions
-    TongueOut,
-    TongueUp,
-    TongueDown,
-    TongueRight,
-    TongueLeft,
-    TongueRoll,
-    TongueBendDown,
-    TongueCurlUp,
-    TongueSquish,
-    TongueFlat,
-    TongueTwistRight,
-    TongueTwistLeft,
-
-    // Throat/Neck Expressions
-    SoftPalateClose,
-    ThroatSwallow,
-    NeckFlexRight,
-    NeckFlexLeft,
-
-    Max,
-}
-
-impl TryFrom<usize> for UnifiedExpressions {
-    type Error = ();
-
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        if value >= Self::Max as usize {
-            return Err(());
+impl UnifiedTrackingData {
+    /// Confidence of shape `index`, combining the face status with the per-shape value.
+    pub fn shape_confidence(&self, index: usize) -> f32 {
+        if !self.face.valid {
+            return 0.0;
         }
-        // We've verified the value is in range [0, Max)
-        // and the enum is repr(usize) with contiguous discriminants starting from 0
-        Ok(unsafe { std::mem::transmute::<usize, UnifiedExpressions>(value) })
+        let shape = self.shape_confidences.get(index).copied().unwrap_or(1.0);
+        self.face.confidence.min(shape)
     }
 }
 
+/// Current wall-clock time in microseconds since the UNIX epoch.
+///
+/// This is the clock `UnifiedTrackingData::timestamp_us` is measured against, so modules
+/// and the host can compare their timestamps.
+pub fn timestamp_us_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
 /// log level for module logging
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -205,10 +196,26 @@ pub enum LogLevel {
     Trace = 5,
 }
 
-/// logger callback for modules
-pub type LogCallback = extern "C" fn(level: LogLevel, target: *const i8, message: *const i8);
+impl LogLevel {
+    /// The level a [`LogCallback`] was called with. Values outside `1..=5`, e.g. from a C
+    /// module, log as `Info`.
+    pub fn from_raw(level: u32) -> Self {
+        match level {
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            4 => LogLevel::Debug,
+            5 => LogLevel::Trace,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+/// logger callback for modules. The level is a [`LogLevel`], passed as a plain integer so
+/// that any value a module passes is defined.
+pub type LogCallback = extern "C" fn(level: u32, target: *const i8, message: *const i8);
 
 /// Logger interface for modules
+#[derive(Clone)]
 pub struct ModuleLogger {
     callback: LogCallback,
     module_name: String,
@@ -222,6 +229,16 @@ impl ModuleLogger {
         }
     }
 
+    /// The host callback messages are forwarded to.
+    pub fn callback(&self) -> LogCallback {
+        self.callback
+    }
+
+    /// The log target messages are tagged with.
+    pub fn target(&self) -> &str {
+        &self.module_name
+    }
+
     pub fn error(&self, message: &str) {
         self.log(LogLevel::Error, message);
     }
@@ -245,12 +262,73 @@ impl ModuleLogger {
     fn log(&self, level: LogLevel, message: &str) {
         let target = std::ffi::CString::new(self.module_name.as_str()).unwrap();
         let msg = std::ffi::CString::new(message).unwrap();
-        (self.callback)(level, target.as_ptr(), msg.as_ptr());
+        (self.callback)(level as u32, target.as_ptr(), msg.as_ptr());
+    }
+}
+
+/// Outcome of one [`TrackingModule::update`] call.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingState {
+    /// `data` holds a new frame.
+    NewFrame = 0,
+    /// Connected, but the device had nothing new since the last call.
+    NoNewFrame = 1,
+    /// The device is not available. The host polls less often until it comes back.
+    Disconnected = 2,
+    /// The connection dropped and the module is re-establishing it.
+    Reconnecting = 3,
+    /// The module cannot continue. The host stops polling it.
+    Fatal = 4,
+}
+
+impl TrackingState {
+    /// Parses the value a module returned through the C ABI.
+    pub fn from_raw(raw: i32) -> Option<Self> {
+        match raw {
+            0 => Some(Self::NewFrame),
+            1 => Some(Self::NoNewFrame),
+            2 => Some(Self::Disconnected),
+            3 => Some(Self::Reconnecting),
+            4 => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    /// The connection state this implies.
+    pub fn connection(self) -> ConnectionState {
+        match self {
+            Self::NewFrame | Self::NoNewFrame => ConnectionState::Connected,
+            Self::Reconnecting => ConnectionState::Connecting,
+            Self::Disconnected | Self::Fatal => ConnectionState::Disconnected,
+        }
     }
 }
 
 pub trait TrackingModule {
-    fn initialize(&mut self, logger: ModuleLogger) -> Result<()>;
-    fn update(&mut self, data: &mut UnifiedTrackingData) -> Result<()>;
+    /// Starts the module. `host` carries the logger and the other host services.
+    fn initialize(&mut self, host: HostServices) -> Result<()>;
+    /// Polls the device, writing a new frame into `data` if there is one.
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState;
     fn unload(&mut self);
+
+    /// Applies the module's settings. Called before `initialize` and again whenever the
+    /// settings change; an error keeps the previous settings in effect.
+    fn configure(&mut self, _settings: &ModuleSettings) -> Result<()> {
+        Ok(())
+    }
+
+    /// Push mode. Modules that produce frames on their own thread keep `sink`, start that
+    /// thread and return `true`; the host then stops calling `update`, and `unload` must stop
+    /// the thread. The default keeps the module in polling mode.
+    fn start_streaming(&mut self, _sink: FrameSink) -> bool {
+        false
+    }
+
+    /// Metadata and capabilities of the module. `None` means unknown, in which case the host
+    /// guesses what is tracked from the data itself.
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        None
+    }
 }
@@ -1,23 +1,46 @@
 //! Proxy module that communicates with a .NET runtime process via shared memory.
 //!
-//! Uses raw Windows API for compatibility with .NET's MemoryMappedFile.
+//! The shared memory backend is picked by platform: named file mappings on Windows, for
+//! compatibility with .NET's MemoryMappedFile, and `shm_open`/`mmap` elsewhere.
 
 use anyhow::{Context, Result};
+use glam::Vec2;
 use std::path::Path;
 use std::process::{Child, Command};
 
-use crate::{ModuleLogger, TrackingModule, UnifiedTrackingData};
+use crate::{
+    gaze, HostServices, TrackingModule, TrackingState, UnifiedExpressions, UnifiedTrackingData,
+};
 
-/// Shared memory name (must match the .NET side exactly).
-const SHMEM_NAME: &str = "Local\\VRCFT_TrackingData";
+#[cfg(unix)]
+mod posix;
+#[cfg(windows)]
+mod win32;
+
+#[cfg(unix)]
+pub use posix::{SharedMemory, DEFAULT_NAME as SHMEM_NAME};
+#[cfg(windows)]
+pub use win32::{SharedMemory, DEFAULT_NAME as SHMEM_NAME};
 
 /// Size of the marshaled data structure (must match .NET MarshaledTrackingData).
-const SHMEM_SIZE: usize = std::mem::size_of::<MarshaledTrackingData>();
+pub const SHMEM_SIZE: usize = std::mem::size_of::<MarshaledTrackingData>();
+
+/// First bytes of the shared memory, "VRFT" in little-endian.
+pub const SHMEM_MAGIC: u32 = u32::from_le_bytes(*b"VRFT");
+
+/// Bump together with the .NET side whenever `MarshaledTrackingData` changes.
+pub const SHMEM_LAYOUT_VERSION: u32 = 1;
+
+/// Number of expression weights in `MarshaledTrackingData::shapes`.
+pub const SHMEM_SHAPE_COUNT: usize = 200;
+
+// Every expression must fit, or the proxy would silently drop the last ones.
+const _: () = assert!(UnifiedExpressions::Max as usize <= SHMEM_SHAPE_COUNT);
 
 pub struct ProxyModule {
     child: Option<Child>,
-    shmem_handle: Option<windows::Win32::Foundation::HANDLE>,
-    shmem_ptr: Option<*mut std::ffi::c_void>,
+    shmem: Option<SharedMemory>,
+    shmem_name: String,
     proxy_exe: Option<std::path::PathBuf>,
     module_dll: Option<std::path::PathBuf>,
     last_runtime_heartbeat: u64,
@@ -27,41 +50,125 @@ pub struct ProxyModule {
 // SAFETY: The shared memory pointer is only accessed from a single thread.
 unsafe impl Send for ProxyModule {}
 
+/// Describes the layout the runtime host writes, so a mismatched host is refused instead
+/// of being read as garbage.
 #[repr(C, packed)]
-struct MarshaledTrackingData {
-    left_eye_gaze_x: f32,
-    left_eye_gaze_y: f32,
-    left_eye_pupil_diameter_mm: f32,
-    left_eye_openness: f32,
-
-    right_eye_gaze_x: f32,
-    right_eye_gaze_y: f32,
-    right_eye_pupil_diameter_mm: f32,
-    right_eye_openness: f32,
-
-    eye_max_dilation: f32,
-    eye_min_dilation: f32,
-    eye_left_diameter: f32,
-    eye_right_diameter: f32,
-
-    head_yaw: f32,
-    head_pitch: f32,
-    head_roll: f32,
-    head_pos_x: f32,
-    head_pos_y: f32,
-    head_pos_z: f32,
-
-    shapes: [f32; 200],
-    main_app_heartbeat: u64,
-    runtime_heartbeat: u64,
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShmemHeader {
+    pub magic: u32,
+    pub layout_version: u32,
+    /// `size_of::<MarshaledTrackingData>()` on the writer's side.
+    pub struct_size: u32,
+    pub shape_count: u32,
+    pub writer_pid: u32,
+}
+
+impl ShmemHeader {
+    /// The header of this build's layout, as written by process `writer_pid`.
+    pub fn current(writer_pid: u32) -> Self {
+        Self {
+            magic: SHMEM_MAGIC,
+            layout_version: SHMEM_LAYOUT_VERSION,
+            struct_size: SHMEM_SIZE as u32,
+            shape_count: SHMEM_SHAPE_COUNT as u32,
+            writer_pid,
+        }
+    }
+
+    /// Whether the writer has filled in the header yet; fresh shared memory is zeroed.
+    pub fn is_written(&self) -> bool {
+        let magic = self.magic;
+        magic != 0
+    }
+
+    /// Checks that the writer uses the same layout as this build.
+    pub fn check(&self) -> Result<()> {
+        let Self {
+            magic,
+            layout_version,
+            struct_size,
+            shape_count,
+            writer_pid,
+        } = *self;
+
+        if magic != SHMEM_MAGIC {
+            anyhow::bail!(
+                "Shared memory has magic {:#010x} instead of {:#010x}; it was not written by a \
+                 compatible VrcftRuntime",
+                magic,
+                SHMEM_MAGIC
+            );
+        }
+        if layout_version != SHMEM_LAYOUT_VERSION {
+            anyhow::bail!(
+                "VrcftRuntime (PID {}) uses shared memory layout version {}, expected {}; \
+                 update VrcftRuntime and vrft_d together",
+                writer_pid,
+                layout_version,
+                SHMEM_LAYOUT_VERSION
+            );
+        }
+        if struct_size as usize != SHMEM_SIZE || shape_count as usize != SHMEM_SHAPE_COUNT {
+            anyhow::bail!(
+                "VrcftRuntime (PID {}) writes {} bytes with {} shapes, expected {} bytes with {} \
+                 shapes",
+                writer_pid,
+                struct_size,
+                shape_count,
+                SHMEM_SIZE,
+                SHMEM_SHAPE_COUNT
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Layout of the shared memory, written by the runtime host and read by [`ProxyModule`].
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct MarshaledTrackingData {
+    pub header: ShmemHeader,
+
+    pub left_eye_gaze_x: f32,
+    pub left_eye_gaze_y: f32,
+    pub left_eye_pupil_diameter_mm: f32,
+    pub left_eye_openness: f32,
+
+    pub right_eye_gaze_x: f32,
+    pub right_eye_gaze_y: f32,
+    pub right_eye_pupil_diameter_mm: f32,
+    pub right_eye_openness: f32,
+
+    pub eye_max_dilation: f32,
+    pub eye_min_dilation: f32,
+    pub eye_left_diameter: f32,
+    pub eye_right_diameter: f32,
+
+    pub head_yaw: f32,
+    pub head_pitch: f32,
+    pub head_roll: f32,
+    pub head_pos_x: f32,
+    pub head_pos_y: f32,
+    pub head_pos_z: f32,
+
+    pub shapes: [f32; SHMEM_SHAPE_COUNT],
+    pub main_app_heartbeat: u64,
+    pub runtime_heartbeat: u64,
+}
+
+impl Default for MarshaledTrackingData {
+    fn default() -> Self {
+        // SAFETY: All fields are plain numbers, for which zero is valid.
+        unsafe { std::mem::zeroed() }
+    }
 }
 
 impl ProxyModule {
     pub fn new() -> Self {
         Self {
             child: None,
-            shmem_handle: None,
-            shmem_ptr: None,
+            shmem: None,
+            shmem_name: SHMEM_NAME.to_string(),
             proxy_exe: None,
             module_dll: None,
             last_runtime_heartbeat: 0,
@@ -69,14 +176,39 @@ impl ProxyModule {
         }
     }
 
+    /// Uses the shared memory named `name` instead of [`SHMEM_NAME`].
+    pub fn with_shmem_name(mut self, name: impl Into<String>) -> Self {
+        self.shmem_name = name.into();
+        self
+    }
+
     pub fn start(&mut self, proxy_exe: &Path, module_dll: &Path) -> Result<()> {
         self.proxy_exe = Some(proxy_exe.to_path_buf());
         self.module_dll = Some(module_dll.to_path_buf());
 
         self.spawn_child()?;
-        self.connect_shmem()?;
+        self.connect_shmem(self.child.as_ref().map(Child::id))?;
 
-        log::info!("Successfully connected to shared memory: {}", SHMEM_NAME);
+        log::info!(
+            "Successfully connected to shared memory: {}",
+            self.shmem_name
+        );
+        Ok(())
+    }
+
+    /// Connects to a runtime host started elsewhere, e.g. by hand or by a test. The proxy
+    /// does not restart such a host.
+    pub fn attach(&mut self) -> Result<()> {
+        self.connect_shmem(None)?;
+        log::info!("Attached to shared memory: {}", self.shmem_name);
+        Ok(())
+    }
+
+    /// Like [`attach`](Self::attach), but waits for the host with PID `pid` to write the
+    /// shared memory, ignoring what an earlier host left in it.
+    pub fn attach_to(&mut self, pid: u32) -> Result<()> {
+        self.connect_shmem(Some(pid))?;
+        log::info!("Attached to shared memory: {}", self.shmem_name);
         Ok(())
     }
 
@@ -104,63 +236,62 @@ impl ProxyModule {
         Ok(())
     }
 
-    fn connect_shmem(&mut self) -> Result<()> {
-        // Wait for the host to create shared memory, then open it
+    /// Waits for the host to create the shared memory and write the header, then maps it.
+    /// With `expected_pid`, the host with that PID must be the writer: bytes an earlier host
+    /// left behind are waited out, and only the expected host's header is checked.
+    fn connect_shmem(&mut self, expected_pid: Option<u32>) -> Result<()> {
         let mut retry = 0;
         let max_retries = 100; // 10 seconds total
 
-        let (handle, ptr) = loop {
-            match Self::open_shared_memory() {
-                Ok(result) => break result,
-                Err(e) => {
-                    if retry >= max_retries {
-                        return Err(e).context(format!(
-                            "Failed to open shared memory '{}' after {} retries",
-                            SHMEM_NAME, max_retries
-                        ));
-                    }
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                    retry += 1;
+        let header = loop {
+            let error = match Self::read_header(&self.shmem_name) {
+                Ok(header) if !header.is_written() => {
+                    anyhow::anyhow!("VrcftRuntime has not written the shared memory header")
                 }
+                Ok(header) if expected_pid.is_some_and(|pid| pid != header.writer_pid) => {
+                    let writer_pid = header.writer_pid;
+                    anyhow::anyhow!(
+                        "Shared memory was written by PID {}, not by VrcftRuntime (PID {})",
+                        writer_pid,
+                        expected_pid.unwrap_or_default()
+                    )
+                }
+                Ok(header) => {
+                    header
+                        .check()
+                        .with_context(|| format!("Refusing shared memory '{}'", self.shmem_name))?;
+                    break header;
+                }
+                Err(e) => e,
+            };
+            if retry >= max_retries {
+                return Err(error).context(format!(
+                    "Failed to open shared memory '{}' after {} retries",
+                    self.shmem_name, max_retries
+                ));
             }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            retry += 1;
         };
 
-        self.shmem_handle = Some(handle);
-        self.shmem_ptr = Some(ptr);
+        // Only map the full layout once the header says it is there.
+        let shmem = SharedMemory::open(&self.shmem_name, SHMEM_SIZE)?;
+        let (writer_pid, layout_version) = (header.writer_pid, header.layout_version);
+        log::debug!(
+            "Shared memory written by PID {} with layout version {}",
+            writer_pid,
+            layout_version
+        );
+
+        self.shmem = Some(shmem);
         self.last_runtime_update = std::time::Instant::now();
         Ok(())
     }
 
-    /// Opens the shared memory created by the .NET proxy host using Windows API.
-    fn open_shared_memory() -> Result<(windows::Win32::Foundation::HANDLE, *mut std::ffi::c_void)> {
-        use windows::core::PCSTR;
-        use windows::Win32::Foundation::CloseHandle;
-        use windows::Win32::System::Memory::{
-            MapViewOfFile, OpenFileMappingA, FILE_MAP_READ, FILE_MAP_WRITE,
-        };
-
-        // Convert the name to a null-terminated C string
-        let name_cstr = std::ffi::CString::new(SHMEM_NAME).context("Invalid shared memory name")?;
-
-        unsafe {
-            // Open existing file mapping
-            let handle = OpenFileMappingA(
-                (FILE_MAP_READ | FILE_MAP_WRITE).0,
-                false,
-                PCSTR::from_raw(name_cstr.as_ptr() as *const u8),
-            )
-            .context("OpenFileMappingA failed")?;
-
-            // Map the view
-            let ptr = MapViewOfFile(handle, FILE_MAP_READ | FILE_MAP_WRITE, 0, 0, SHMEM_SIZE);
-
-            if ptr.Value.is_null() {
-                let _ = CloseHandle(handle);
-                anyhow::bail!("MapViewOfFile returned null");
-            }
-
-            Ok((handle, ptr.Value))
-        }
+    fn read_header(name: &str) -> Result<ShmemHeader> {
+        let shmem = SharedMemory::open(name, std::mem::size_of::<ShmemHeader>())?;
+        // SAFETY: The mapping is at least as large as the header, which is packed.
+        Ok(unsafe { std::ptr::read_unaligned(shmem.as_ptr() as *const ShmemHeader) })
     }
 }
 
@@ -171,12 +302,13 @@ impl Default for ProxyModule {
 }
 
 impl TrackingModule for ProxyModule {
-    fn initialize(&mut self, _logger: ModuleLogger) -> Result<()> {
+    fn initialize(&mut self, _host: HostServices) -> Result<()> {
         Ok(())
     }
 
-    fn update(&mut self, data: &mut UnifiedTrackingData) -> Result<()> {
-        if let Some(ptr) = self.shmem_ptr {
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        let mut state = TrackingState::Disconnected;
+        if let Some(ptr) = self.shmem.as_ref().map(SharedMemory::as_ptr) {
             unsafe {
                 let m_data_mut = &mut *(ptr as *mut MarshaledTrackingData);
 
@@ -185,19 +317,27 @@ impl TrackingModule for ProxyModule {
 
                 let m_data = &*m_data_mut;
 
-                // Check runtime heartbeat
+                // Check runtime heartbeat; the runtime bumps it with every frame it writes
                 if m_data.runtime_heartbeat != self.last_runtime_heartbeat {
                     self.last_runtime_heartbeat = m_data.runtime_heartbeat;
                     self.last_runtime_update = std::time::Instant::now();
+                    state = TrackingState::NewFrame;
+                } else {
+                    state = TrackingState::NoNewFrame;
                 }
 
-                data.eye.left.gaze.x = m_data.left_eye_gaze_x;
-                data.eye.left.gaze.y = m_data.left_eye_gaze_y;
+                // VRCFT modules report gaze as the X/Y projection of the direction.
+                data.eye.left.gaze = gaze::from_projection(Vec2::new(
+                    m_data.left_eye_gaze_x,
+                    m_data.left_eye_gaze_y,
+                ));
                 data.eye.left.pupil_diameter_mm = m_data.left_eye_pupil_diameter_mm;
                 data.eye.left.openness = m_data.left_eye_openness;
 
-                data.eye.right.gaze.x = m_data.right_eye_gaze_x;
-                data.eye.right.gaze.y = m_data.right_eye_gaze_y;
+                data.eye.right.gaze = gaze::from_projection(Vec2::new(
+                    m_data.right_eye_gaze_x,
+                    m_data.right_eye_gaze_y,
+                ));
                 data.eye.right.pupil_diameter_mm = m_data.right_eye_pupil_diameter_mm;
                 data.eye.right.openness = m_data.right_eye_openness;
 
@@ -213,7 +353,7 @@ impl TrackingModule for ProxyModule {
                 data.head.head_pos_y = m_data.head_pos_y;
                 data.head.head_pos_z = m_data.head_pos_z;
 
-                for i in 0..data.shapes.len().min(200) {
+                for i in 0..data.shapes.len().min(SHMEM_SHAPE_COUNT) {
                     data.shapes[i].weight = m_data.shapes[i];
                 }
             }
@@ -242,38 +382,28 @@ impl TrackingModule for ProxyModule {
                 }
             }
         } else {
-            true
+            // Only restart hosts we started ourselves, not attached ones.
+            self.proxy_exe.is_some()
         };
 
         if should_restart {
             self.unload();
+            state = TrackingState::Disconnected;
             if let Err(e) = self.spawn_child() {
                 log::error!("Failed to restart VrcftRuntime: {}", e);
-            } else if let Err(e) = self.connect_shmem() {
+            } else if let Err(e) = self.connect_shmem(self.child.as_ref().map(Child::id)) {
                 log::error!("Failed to reconnect to shared memory: {}", e);
             } else {
                 log::info!("VrcftRuntime restarted successfully.");
+                state = TrackingState::Reconnecting;
             }
         }
 
-        Ok(())
+        state
     }
 
     fn unload(&mut self) {
-        use windows::Win32::Foundation::CloseHandle;
-        use windows::Win32::System::Memory::UnmapViewOfFile;
-
-        unsafe {
-            if let Some(ptr) = self.shmem_ptr.take() {
-                let _ =
-                    UnmapViewOfFile(windows::Win32::System::Memory::MEMORY_MAPPED_VIEW_ADDRESS {
-                        Value: ptr,
-                    });
-            }
-            if let Some(handle) = self.shmem_handle.take() {
-                let _ = CloseHandle(handle);
-            }
-        }
+        self.shmem = None;
 
         if let Some(mut child) = self.child.take() {
             let _ = child.kill();
@@ -0,0 +1,110 @@
+//! Shared memory through POSIX `shm_open`/`mmap`.
+//!
+//! A segment named `/VRCFT_TrackingData` lives at `/dev/shm/VRCFT_TrackingData` on Linux,
+//! which is where the .NET runtime host maps its file outside Windows.
+
+use anyhow::{Context, Result};
+use std::ffi::{c_void, CString};
+use std::io;
+
+/// Name of the segment the runtime host creates (must match the .NET side exactly).
+pub const DEFAULT_NAME: &str = "/VRCFT_TrackingData";
+
+/// A mapped POSIX shared memory segment. Unmapped on drop, and unlinked if this side
+/// created it.
+pub struct SharedMemory {
+    ptr: *mut c_void,
+    size: usize,
+    /// Set for segments created here, so they are removed again.
+    owned_name: Option<CString>,
+}
+
+impl SharedMemory {
+    /// Opens an existing segment created by the runtime host.
+    pub fn open(name: &str, size: usize) -> Result<Self> {
+        let name = CString::new(name).context("Invalid shared memory name")?;
+        let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error()).context("shm_open failed");
+        }
+        Self::map(fd, size, None)
+    }
+
+    /// Creates (or opens) a zeroed segment, like the runtime host does.
+    pub fn create(name: &str, size: usize) -> Result<Self> {
+        let name = CString::new(name).context("Invalid shared memory name")?;
+        let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR | libc::O_CREAT, 0o600) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error()).context("shm_open failed");
+        }
+        if unsafe { libc::ftruncate(fd, size as libc::off_t) } != 0 {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(fd);
+                libc::shm_unlink(name.as_ptr());
+            }
+            return Err(error).context("ftruncate failed");
+        }
+        Self::map(fd, size, Some(name))
+    }
+
+    fn map(fd: libc::c_int, size: usize, owned_name: Option<CString>) -> Result<Self> {
+        // The mapping keeps the segment alive, so the descriptor can be closed right away.
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        let result = if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+            Err(io::Error::last_os_error()).context("fstat failed")
+        } else if (stat.st_size as usize) < size {
+            Err(anyhow::anyhow!(
+                "Shared memory is {} bytes, expected at least {}",
+                stat.st_size,
+                size
+            ))
+        } else {
+            let ptr = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    size,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    fd,
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                Err(io::Error::last_os_error()).context("mmap failed")
+            } else {
+                Ok(ptr)
+            }
+        };
+        unsafe { libc::close(fd) };
+
+        match result {
+            Ok(ptr) => Ok(Self {
+                ptr,
+                size,
+                owned_name,
+            }),
+            Err(e) => {
+                if let Some(name) = owned_name {
+                    unsafe { libc::shm_unlink(name.as_ptr()) };
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr, self.size);
+            if let Some(name) = &self.owned_name {
+                libc::shm_unlink(name.as_ptr());
+            }
+        }
+    }
+}
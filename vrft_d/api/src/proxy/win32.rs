@@ -0,0 +1,78 @@
+//! Shared memory through named Windows file mappings, as created by .NET's
+//! `MemoryMappedFile.CreateOrOpen`.
+
+use anyhow::{Context, Result};
+use std::ffi::{c_void, CString};
+use windows::core::PCSTR;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+use windows::Win32::System::Memory::{
+    CreateFileMappingA, MapViewOfFile, OpenFileMappingA, UnmapViewOfFile, FILE_MAP_READ,
+    FILE_MAP_WRITE, MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READWRITE,
+};
+
+/// Name of the mapping the runtime host creates (must match the .NET side exactly).
+pub const DEFAULT_NAME: &str = "Local\\VRCFT_TrackingData";
+
+/// A mapped view of a named file mapping. Unmapped and closed on drop.
+pub struct SharedMemory {
+    handle: HANDLE,
+    ptr: *mut c_void,
+}
+
+impl SharedMemory {
+    /// Opens an existing mapping created by the runtime host.
+    pub fn open(name: &str, size: usize) -> Result<Self> {
+        let name = CString::new(name).context("Invalid shared memory name")?;
+        unsafe {
+            let handle = OpenFileMappingA(
+                (FILE_MAP_READ | FILE_MAP_WRITE).0,
+                false,
+                PCSTR::from_raw(name.as_ptr() as *const u8),
+            )
+            .context("OpenFileMappingA failed")?;
+            Self::map(handle, size)
+        }
+    }
+
+    /// Creates (or opens) a zeroed mapping, like the runtime host does.
+    pub fn create(name: &str, size: usize) -> Result<Self> {
+        let name = CString::new(name).context("Invalid shared memory name")?;
+        unsafe {
+            let handle = CreateFileMappingA(
+                INVALID_HANDLE_VALUE,
+                None,
+                PAGE_READWRITE,
+                0,
+                size as u32,
+                PCSTR::from_raw(name.as_ptr() as *const u8),
+            )
+            .context("CreateFileMappingA failed")?;
+            Self::map(handle, size)
+        }
+    }
+
+    unsafe fn map(handle: HANDLE, size: usize) -> Result<Self> {
+        let view = MapViewOfFile(handle, FILE_MAP_READ | FILE_MAP_WRITE, 0, 0, size);
+        if view.Value.is_null() {
+            let _ = CloseHandle(handle);
+            anyhow::bail!("MapViewOfFile returned null");
+        }
+        Ok(Self {
+            handle,
+            ptr: view.Value,
+        })
+    }
+
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS { Value: self.ptr });
+            let _ = CloseHandle(self.handle);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Per-module settings.
+//!
+//! Settings are arbitrary JSON kept under `module.settings.<module name>` in config.json.
+//! The host hands them to [`TrackingModule::configure`](crate::TrackingModule::configure)
+//! before `initialize` and again whenever they change. A module can publish a schema in its
+//! [`ModuleDescriptor`](crate::ModuleDescriptor); the host then refuses settings that do not
+//! match it.
+
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Settings of one module, usually a JSON object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModuleSettings(Value);
+
+impl Default for ModuleSettings {
+    fn default() -> Self {
+        Self(Value::Object(Map::new()))
+    }
+}
+
+impl ModuleSettings {
+    /// Wraps `value`; `null` becomes an empty object.
+    pub fn new(value: Value) -> Self {
+        match value {
+            Value::Null => Self::default(),
+            value => Self(value),
+        }
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Deserializes the value at `key`, or `None` if it is missing or has the wrong shape.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.0
+            .get(key)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn bool_or(&self, key: &str, default: bool) -> bool {
+        self.get(key).unwrap_or(default)
+    }
+
+    pub fn f32_or(&self, key: &str, default: f32) -> f32 {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Checks the settings against `schema`.
+    pub fn validate(&self, schema: &Value) -> Result<()> {
+        validate(schema, &self.0, "settings")
+    }
+}
+
+/// Validates `value` against the JSON Schema subset modules use: `type`, `properties`,
+/// `required`, `additionalProperties: false`, `items`, `enum`, `minimum` and `maximum`.
+///
+/// Unknown keywords are ignored.
+pub fn validate(schema: &Value, value: &Value, path: &str) -> Result<()> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !matches {
+            bail!("{} must be of type {}, got {}", path, expected, value);
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            bail!("{} must be one of {:?}, got {}", path, allowed, value);
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                bail!("{} must be at least {}, got {}", path, minimum, number);
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                bail!("{} must be at most {}, got {}", path, maximum, number);
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    bail!("{}.{} is required", path, key);
+                }
+            }
+        }
+
+        for (key, item) in object {
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate(property, item, &format!("{}.{}", path, key))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    bail!("{}.{} is not a known setting", path, key);
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            validate(items, item, &format!("{}[{}]", path, index))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Push-mode frame delivery.
+//!
+//! Instead of waiting for the host to call `update`, a streaming module keeps the
+//! [`FrameSink`] it receives in [`TrackingModule::start_streaming`](crate::TrackingModule::start_streaming)
+//! and submits frames from its own thread as soon as the device produces them.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::UnifiedTrackingData;
+
+/// Thread-safe handle through which a streaming module submits frames to the host.
+#[derive(Clone)]
+pub struct FrameSink {
+    submit: Arc<dyn Fn(&UnifiedTrackingData) + Send + Sync>,
+}
+
+impl FrameSink {
+    pub fn new(submit: impl Fn(&UnifiedTrackingData) + Send + Sync + 'static) -> Self {
+        Self {
+            submit: Arc::new(submit),
+        }
+    }
+
+    /// Hands one frame to the host. Leave `timestamp_us` at 0 to let the host stamp it.
+    pub fn submit(&self, data: &UnifiedTrackingData) {
+        (self.submit)(data)
+    }
+}
+
+impl fmt::Debug for FrameSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameSink").finish_non_exhaustive()
+    }
+}
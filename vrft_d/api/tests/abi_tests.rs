@@ -0,0 +1,579 @@
+//! C ABI round-trip tests
+//!
+//! Drives a Rust module through the exported function table the same way the host does.
+
+use anyhow::Result;
+use api::abi::{
+    export_module, AbiModule, VrftFrameSink, VrftModuleVTable, VrftTrackingFrame, VRFT_ABI_VERSION,
+    VRFT_CAPABILITY_EXPRESSIONS, VRFT_CAPABILITY_EYE_OPENNESS, VRFT_CAPABILITY_HEAD, VRFT_FAULT,
+    VRFT_OK,
+};
+use api::{
+    ConnectionState, FacialRegion, FrameSink, Host, HostServices, LogLevel, ModuleCapabilities,
+    ModuleDescriptor, ModuleLogger, ModuleSettings, TrackingModule, TrackingState,
+    UnifiedExpressions, UnifiedTrackingData,
+};
+use serde_json::json;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+extern "C" fn discard_log(_level: u32, _target: *const i8, _message: *const i8) {}
+
+fn host() -> HostServices {
+    HostServices::detached(ModuleLogger::new(discard_log, "test".to_string()))
+}
+
+struct JawModule {
+    updates: u32,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl TrackingModule for JawModule {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        host.logger().info("initialized");
+        Ok(())
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        self.updates += 1;
+        if self.updates > 2 {
+            return TrackingState::NoNewFrame;
+        }
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight = 0.75;
+        data.eye.left.openness = 0.25;
+        data.head.head_yaw = 1.5;
+        data.face.confidence = 0.5;
+        data.shape_confidences[UnifiedExpressions::JawOpen as usize] = 0.25;
+        TrackingState::NewFrame
+    }
+
+    fn unload(&mut self) {}
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        Some(
+            ModuleDescriptor::new("Jaw", "1.2.3", "Tests")
+                .with_capabilities(ModuleCapabilities {
+                    eye_openness: true,
+                    head: true,
+                    ..ModuleCapabilities::NONE
+                })
+                .with_expressions(&[UnifiedExpressions::JawOpen]),
+        )
+    }
+}
+
+impl Drop for JawModule {
+    fn drop(&mut self) {
+        self.dropped.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn exported_table(dropped: Arc<AtomicUsize>) -> VrftModuleVTable {
+    let mut vtable = VrftModuleVTable::empty();
+    let code = unsafe {
+        export_module(
+            &mut vtable,
+            JawModule {
+                updates: 0,
+                dropped,
+            },
+        )
+    };
+    assert_eq!(code, VRFT_OK);
+    vtable
+}
+
+#[test]
+fn exported_table_is_complete() {
+    let vtable = exported_table(Arc::default());
+    assert_eq!(vtable.abi_version, VRFT_ABI_VERSION);
+    assert_eq!(
+        vtable.struct_size as usize,
+        std::mem::size_of::<VrftModuleVTable>()
+    );
+    assert!(!vtable.instance.is_null());
+    assert!(vtable.describe.is_some());
+    drop(unsafe { AbiModule::from_vtable(vtable) }.unwrap());
+}
+
+#[test]
+fn update_round_trips_through_table() {
+    let mut module = unsafe { AbiModule::from_vtable(exported_table(Arc::default())) }.unwrap();
+    module.initialize(host()).unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    data.shapes[UnifiedExpressions::TongueOut as usize].weight = 0.5;
+
+    assert_eq!(module.update(&mut data), TrackingState::NewFrame);
+    assert_eq!(
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight,
+        0.75
+    );
+    assert_eq!(
+        data.shapes[UnifiedExpressions::TongueOut as usize].weight,
+        0.5
+    );
+    assert_eq!(data.eye.left.openness, 0.25);
+    assert_eq!(data.head.head_yaw, 1.5);
+    assert_eq!(data.face.confidence, 0.5);
+    assert_eq!(
+        data.shape_confidence(UnifiedExpressions::JawOpen as usize),
+        0.25
+    );
+
+    assert_eq!(module.update(&mut data), TrackingState::NewFrame);
+    assert_eq!(module.update(&mut data), TrackingState::NoNewFrame);
+    module.unload();
+}
+
+#[test]
+fn dropping_host_wrapper_destroys_instance() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let module = unsafe { AbiModule::from_vtable(exported_table(dropped.clone())) }.unwrap();
+    assert_eq!(dropped.load(Ordering::SeqCst), 0);
+    drop(module);
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn rejects_wrong_abi_version() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let mut vtable = exported_table(dropped.clone());
+    vtable.abi_version = VRFT_ABI_VERSION + 1;
+
+    let err = unsafe { AbiModule::from_vtable(vtable) }.err().unwrap();
+    assert!(err.to_string().contains("ABI version"));
+}
+
+#[test]
+fn rejects_missing_functions_and_destroys_instance() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let mut vtable = exported_table(dropped.clone());
+    vtable.update = None;
+
+    assert!(unsafe { AbiModule::from_vtable(vtable) }.is_err());
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn rejects_truncated_table() {
+    let mut vtable = exported_table(Arc::default());
+    vtable.struct_size = 8;
+
+    assert!(unsafe { AbiModule::from_vtable(vtable) }.is_err());
+}
+
+#[test]
+fn export_rejects_null_output() {
+    let code = unsafe {
+        export_module(
+            std::ptr::null_mut(),
+            JawModule {
+                updates: 0,
+                dropped: Arc::default(),
+            },
+        )
+    };
+    assert_ne!(code, VRFT_OK);
+}
+
+#[test]
+fn descriptor_round_trips_through_table() {
+    let module = unsafe { AbiModule::from_vtable(exported_table(Arc::default())) }.unwrap();
+    let descriptor = module.descriptor().expect("module describes itself");
+
+    assert_eq!(descriptor.name, "Jaw");
+    assert_eq!(descriptor.version, "1.2.3");
+    assert_eq!(descriptor.vendor, "Tests");
+    assert!(descriptor.capabilities.eye_openness && descriptor.capabilities.head);
+    assert!(!descriptor.capabilities.eye_gaze && !descriptor.capabilities.pupil);
+    assert!(descriptor.drives(UnifiedExpressions::JawOpen));
+    assert!(descriptor.drives_region(FacialRegion::Jaw));
+    assert!(!descriptor.drives_region(FacialRegion::Tongue));
+
+    let vtable = exported_table(Arc::default());
+    let raw = unsafe { &*vtable.describe.unwrap()(vtable.instance) };
+    assert_eq!(
+        raw.capabilities,
+        VRFT_CAPABILITY_EYE_OPENNESS | VRFT_CAPABILITY_EXPRESSIONS | VRFT_CAPABILITY_HEAD
+    );
+    drop(unsafe { AbiModule::from_vtable(vtable) });
+}
+
+#[test]
+fn module_without_descriptor_is_unknown() {
+    struct Silent;
+
+    impl TrackingModule for Silent {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            TrackingState::NewFrame
+        }
+
+        fn unload(&mut self) {}
+    }
+
+    let mut vtable = VrftModuleVTable::empty();
+    assert_eq!(unsafe { export_module(&mut vtable, Silent) }, VRFT_OK);
+    let module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    assert!(module.descriptor().is_none());
+}
+
+#[test]
+fn settings_and_schema_cross_the_table() {
+    struct Configurable {
+        received: Arc<Mutex<Option<ModuleSettings>>>,
+    }
+
+    impl TrackingModule for Configurable {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            TrackingState::NewFrame
+        }
+
+        fn unload(&mut self) {}
+
+        fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+            if settings.bool_or("reject", false) {
+                anyhow::bail!("rejected");
+            }
+            *self.received.lock().unwrap() = Some(settings.clone());
+            Ok(())
+        }
+
+        fn descriptor(&self) -> Option<ModuleDescriptor> {
+            Some(
+                ModuleDescriptor::new("Configurable", "1.0", "Tests")
+                    .with_settings_schema(json!({ "type": "object" })),
+            )
+        }
+    }
+
+    let received = Arc::new(Mutex::new(None));
+    let mut vtable = VrftModuleVTable::empty();
+    let module = Configurable {
+        received: received.clone(),
+    };
+    assert_eq!(unsafe { export_module(&mut vtable, module) }, VRFT_OK);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+
+    assert_eq!(
+        module.descriptor().unwrap().settings_schema,
+        Some(json!({ "type": "object" }))
+    );
+
+    let settings = ModuleSettings::new(json!({ "factor": 0.5, "name": "ünïcode" }));
+    module.configure(&settings).unwrap();
+    assert_eq!(received.lock().unwrap().as_ref(), Some(&settings));
+
+    assert!(module
+        .configure(&ModuleSettings::new(json!({ "reject": true })))
+        .is_err());
+    assert_eq!(received.lock().unwrap().as_ref(), Some(&settings));
+}
+
+#[test]
+fn tracking_states_cross_the_table() {
+    const STATES: [TrackingState; 5] = [
+        TrackingState::NewFrame,
+        TrackingState::NoNewFrame,
+        TrackingState::Disconnected,
+        TrackingState::Reconnecting,
+        TrackingState::Fatal,
+    ];
+
+    struct Sequence(usize);
+
+    impl TrackingModule for Sequence {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            self.0 += 1;
+            STATES[self.0 - 1]
+        }
+
+        fn unload(&mut self) {}
+    }
+
+    let mut vtable = VrftModuleVTable::empty();
+    assert_eq!(unsafe { export_module(&mut vtable, Sequence(0)) }, VRFT_OK);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    module.initialize(host()).unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    for state in STATES {
+        assert_eq!(module.update(&mut data), state);
+    }
+}
+
+#[test]
+fn unknown_update_code_is_fatal() {
+    unsafe extern "C" fn update(_instance: *mut c_void, _frame: *mut VrftTrackingFrame) -> i32 {
+        42
+    }
+
+    let mut vtable = exported_table(Arc::default());
+    vtable.update = Some(update);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    module.initialize(host()).unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    assert_eq!(module.update(&mut data), TrackingState::Fatal);
+}
+
+#[test]
+fn update_without_a_frame_is_fatal() {
+    let vtable = exported_table(Arc::default());
+    let update = vtable.update.unwrap();
+    let code = unsafe { update(vtable.instance, std::ptr::null_mut()) };
+    assert_eq!(code, TrackingState::Fatal as i32);
+    drop(unsafe { AbiModule::from_vtable(vtable) });
+}
+
+#[test]
+fn any_nonzero_validity_byte_is_valid() {
+    unsafe extern "C" fn update(_instance: *mut c_void, frame: *mut VrftTrackingFrame) -> i32 {
+        let frame = &mut *frame;
+        frame.face.valid = 2;
+        frame.eye.left.status.valid = 0;
+        frame.head.status.valid = 0xff;
+        VRFT_OK
+    }
+
+    let mut vtable = exported_table(Arc::default());
+    vtable.update = Some(update);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    module.initialize(host()).unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    data.face.valid = false;
+    assert_eq!(module.update(&mut data), TrackingState::NewFrame);
+    assert!(data.face.valid);
+    assert!(!data.eye.left.status.valid);
+    assert!(data.head.status.valid);
+}
+
+#[test]
+fn streamed_frames_reach_host_sink() {
+    struct Streaming {
+        thread: Option<std::thread::JoinHandle<()>>,
+    }
+
+    impl TrackingModule for Streaming {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            TrackingState::Disconnected
+        }
+
+        fn start_streaming(&mut self, sink: FrameSink) -> bool {
+            self.thread = Some(std::thread::spawn(move || {
+                let mut data = UnifiedTrackingData::default();
+                for frame in 1..=3 {
+                    data.shapes[UnifiedExpressions::JawOpen as usize].weight = frame as f32 / 4.0;
+                    data.timestamp_us = frame;
+                    sink.submit(&data);
+                }
+            }));
+            true
+        }
+
+        fn unload(&mut self) {
+            if let Some(thread) = self.thread.take() {
+                thread.join().unwrap();
+            }
+        }
+    }
+
+    let mut vtable = VrftModuleVTable::empty();
+    let module = Streaming { thread: None };
+    assert_eq!(unsafe { export_module(&mut vtable, module) }, VRFT_OK);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink_received = received.clone();
+    let sink = FrameSink::new(move |data| {
+        sink_received.lock().unwrap().push((
+            data.timestamp_us,
+            data.shapes[UnifiedExpressions::JawOpen as usize].weight,
+        ))
+    });
+    assert!(module.start_streaming(sink));
+    module.unload();
+
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![(1, 0.25), (2, 0.5), (3, 0.75)]
+    );
+}
+
+#[test]
+fn any_nonzero_streaming_byte_streams() {
+    unsafe extern "C" fn start_streaming(
+        _instance: *mut c_void,
+        _sink: *const VrftFrameSink,
+    ) -> u8 {
+        2
+    }
+
+    let mut vtable = exported_table(Arc::default());
+    vtable.start_streaming = Some(start_streaming);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    assert!(module.start_streaming(FrameSink::new(|_| {})));
+}
+
+#[test]
+fn polled_module_declines_streaming() {
+    let mut module = unsafe { AbiModule::from_vtable(exported_table(Arc::default())) }.unwrap();
+    assert!(!module.start_streaming(FrameSink::new(|_| {})));
+}
+
+#[test]
+fn host_services_cross_the_table() {
+    #[derive(Default)]
+    struct RecordingHost {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Host for RecordingHost {
+        fn now_us(&self) -> u64 {
+            42
+        }
+
+        fn connection_changed(&self, state: ConnectionState) {
+            self.calls.lock().unwrap().push(format!("{:?}", state));
+        }
+
+        fn request_recalibration(&self) {
+            self.calls.lock().unwrap().push("recalibrate".to_string());
+        }
+
+        fn set_gauge(&self, name: &str, value: f64) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}={}", name, value));
+        }
+
+        fn add_counter(&self, name: &str, delta: u64) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}+={}", name, delta));
+        }
+    }
+
+    struct UsesHost {
+        kept: Arc<Mutex<Option<HostServices>>>,
+    }
+
+    impl TrackingModule for UsesHost {
+        fn initialize(&mut self, host: HostServices) -> Result<()> {
+            host.set_connection_state(ConnectionState::Connected);
+            host.set_gauge("device_fps", 90.0);
+            host.increment_counter("reconnects");
+            host.request_recalibration();
+            *self.kept.lock().unwrap() = Some(host);
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            TrackingState::NewFrame
+        }
+
+        fn unload(&mut self) {}
+    }
+
+    let kept = Arc::new(Mutex::new(None));
+    let mut vtable = VrftModuleVTable::empty();
+    let module = UsesHost { kept: kept.clone() };
+    assert_eq!(unsafe { export_module(&mut vtable, module) }, VRFT_OK);
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+
+    let recording = Arc::new(RecordingHost::default());
+    let services = HostServices::new(
+        ModuleLogger::new(discard_log, "test".to_string()),
+        Some(std::path::PathBuf::from("module_data/uses_host")),
+        recording.clone(),
+    );
+    module.initialize(services).unwrap();
+
+    assert_eq!(
+        *recording.calls.lock().unwrap(),
+        vec!["Connected", "device_fps=90", "reconnects+=1", "recalibrate"]
+    );
+    // The module can keep using the services after initialize.
+    let kept = kept.lock().unwrap().clone().unwrap();
+    assert_eq!(kept.now_us(), 42);
+    assert_eq!(
+        kept.state_dir(),
+        Some(std::path::Path::new("module_data/uses_host"))
+    );
+    kept.set_connection_state(ConnectionState::Disconnected);
+    assert_eq!(recording.calls.lock().unwrap().len(), 5);
+}
+
+#[test]
+fn panics_are_caught_at_the_table() {
+    struct Panicking;
+
+    impl TrackingModule for Panicking {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            panic!("device exploded");
+        }
+
+        fn unload(&mut self) {}
+    }
+
+    let mut vtable = VrftModuleVTable::empty();
+    assert_eq!(unsafe { export_module(&mut vtable, Panicking) }, VRFT_OK);
+    let mut data = UnifiedTrackingData::default();
+    data.shape_confidences.resize(data.shapes.len(), 1.0);
+    let mut frame = VrftTrackingFrame {
+        eye: (&data.eye).into(),
+        head: data.head.into(),
+        face: data.face.into(),
+        shapes: data.shapes.as_mut_ptr(),
+        shape_confidences: data.shape_confidences.as_mut_ptr(),
+        shape_count: data.shapes.len(),
+        timestamp_us: 0,
+    };
+    let update = vtable.update.unwrap();
+    assert_eq!(unsafe { update(vtable.instance, &mut frame) }, VRFT_FAULT);
+
+    // The host wrapper turns the fault back into a panic on its own side.
+    let mut module = unsafe { AbiModule::from_vtable(vtable) }.unwrap();
+    module.initialize(host()).unwrap();
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        module.update(&mut data);
+    }))
+    .unwrap_err();
+    assert_eq!(
+        panic.downcast_ref::<String>().unwrap(),
+        "module faulted in update"
+    );
+}
+
+#[test]
+fn unknown_log_levels_log_as_info() {
+    assert_eq!(LogLevel::from_raw(1), LogLevel::Error);
+    assert_eq!(LogLevel::from_raw(5), LogLevel::Trace);
+    for level in [0, 6, u32::MAX] {
+        assert_eq!(LogLevel::from_raw(level), LogLevel::Info);
+    }
+}
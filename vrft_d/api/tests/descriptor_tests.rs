@@ -0,0 +1,42 @@
+//! Module descriptor tests
+
+use api::{FacialRegion, ModuleCapabilities, ModuleDescriptor, UnifiedExpressions};
+
+#[test]
+fn with_expressions_sets_capability() {
+    let descriptor = ModuleDescriptor::new("Face", "1.0", "Tests")
+        .with_expressions(&[UnifiedExpressions::TongueOut]);
+    assert!(descriptor.capabilities.expressions);
+    assert!(descriptor.drives(UnifiedExpressions::TongueOut));
+    assert!(descriptor.drives_region(FacialRegion::Tongue));
+    assert!(!descriptor.drives(UnifiedExpressions::JawOpen));
+
+    let empty = descriptor.with_expressions(&[]);
+    assert!(!empty.capabilities.expressions);
+    assert!(!empty.drives_region(FacialRegion::Tongue));
+}
+
+#[test]
+fn combine_unions_capabilities_and_expressions() {
+    let eyes =
+        ModuleDescriptor::new("Eyes", "1.0", "Tests").with_capabilities(ModuleCapabilities {
+            eye_gaze: true,
+            pupil: true,
+            ..ModuleCapabilities::NONE
+        });
+    let face = ModuleDescriptor::new("Face", "1.0", "Tests")
+        .with_expressions(&[UnifiedExpressions::JawOpen, UnifiedExpressions::TongueOut]);
+
+    let combined = ModuleDescriptor::combine(&[eyes, face.clone(), face]);
+    assert_eq!(combined.name, "Eyes + Face + Face");
+    assert!(combined.capabilities.eye_gaze && combined.capabilities.pupil);
+    assert!(combined.capabilities.expressions && !combined.capabilities.head);
+    assert_eq!(combined.expressions.len(), 2);
+}
+
+#[test]
+fn combine_of_nothing_tracks_nothing() {
+    let combined = ModuleDescriptor::combine(&[]);
+    assert_eq!(combined.capabilities, ModuleCapabilities::NONE);
+    assert!(!combined.capabilities.eye());
+}
@@ -0,0 +1,80 @@
+//! Expression metadata tests
+
+use api::{FacialRegion, Side, UnifiedExpressions};
+
+#[test]
+fn all_covers_every_index_in_order() {
+    assert_eq!(
+        UnifiedExpressions::ALL.len(),
+        UnifiedExpressions::Max as usize
+    );
+    for (index, expr) in UnifiedExpressions::iter().enumerate() {
+        assert_eq!(expr.index(), index);
+        assert_eq!(UnifiedExpressions::try_from(index), Ok(expr));
+    }
+    assert!(UnifiedExpressions::try_from(UnifiedExpressions::Max as usize).is_err());
+}
+
+#[test]
+fn names_round_trip() {
+    for expr in UnifiedExpressions::iter() {
+        assert_eq!(expr.to_string(), format!("{:?}", expr));
+        assert_eq!(expr.name().parse::<UnifiedExpressions>(), Ok(expr));
+    }
+    assert!("Max".parse::<UnifiedExpressions>().is_err());
+    assert!("jawopen".parse::<UnifiedExpressions>().is_err());
+}
+
+#[test]
+fn mirrors_are_symmetric() {
+    for expr in UnifiedExpressions::iter() {
+        match expr.side() {
+            Side::Center => assert_eq!(expr.mirror(), None, "{}", expr),
+            side => {
+                let mirror = expr
+                    .mirror()
+                    .unwrap_or_else(|| panic!("{} has no mirror", expr));
+                assert_ne!(mirror.side(), side);
+                assert_eq!(mirror.region(), expr.region());
+                assert_eq!(mirror.mirror(), Some(expr));
+            }
+        }
+    }
+    assert_eq!(
+        UnifiedExpressions::CheekPuffLeft.mirror(),
+        Some(UnifiedExpressions::CheekPuffRight)
+    );
+}
+
+#[test]
+fn antagonists_are_symmetric() {
+    for expr in UnifiedExpressions::iter() {
+        if let Some(antagonist) = expr.antagonist() {
+            assert_ne!(antagonist, expr);
+            assert_eq!(antagonist.antagonist(), Some(expr), "{}", expr);
+        }
+    }
+    assert_eq!(
+        UnifiedExpressions::CheekPuffLeft.antagonist(),
+        Some(UnifiedExpressions::CheekSuckLeft)
+    );
+    assert_eq!(UnifiedExpressions::JawOpen.antagonist(), None);
+}
+
+#[test]
+fn regions_partition_expressions() {
+    let total: usize = FacialRegion::ALL
+        .iter()
+        .map(|region| region.expressions().count())
+        .sum();
+    assert_eq!(total, UnifiedExpressions::Max as usize);
+
+    assert_eq!(UnifiedExpressions::EyeWideLeft.region(), FacialRegion::Eye);
+    assert_eq!(
+        UnifiedExpressions::NoseSneerRight.region(),
+        FacialRegion::Nose
+    );
+    assert_eq!(UnifiedExpressions::MouthClosed.region(), FacialRegion::Jaw);
+    assert_eq!(UnifiedExpressions::TongueOut.region(), FacialRegion::Tongue);
+    assert_eq!(FacialRegion::Brow.expressions().count(), 8);
+}
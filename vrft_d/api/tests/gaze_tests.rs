@@ -0,0 +1,63 @@
+//! Gaze direction conversion tests
+
+use api::gaze;
+use api::UnifiedSingleEyeData;
+use glam::{Vec2, Vec3};
+
+const EPSILON: f32 = 1e-5;
+
+fn assert_vec3_eq(a: Vec3, b: Vec3) {
+    assert!(a.abs_diff_eq(b, EPSILON), "{:?} != {:?}", a, b);
+}
+
+fn assert_vec2_eq(a: Vec2, b: Vec2) {
+    assert!(a.abs_diff_eq(b, EPSILON), "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn default_eye_looks_forward() {
+    let eye = UnifiedSingleEyeData::default();
+    assert_eq!(eye.gaze, Vec3::Z);
+    assert_vec2_eq(eye.yaw_pitch(), Vec2::ZERO);
+    assert_vec2_eq(eye.projection(), Vec2::ZERO);
+}
+
+#[test]
+fn yaw_pitch_signs_follow_axes() {
+    let right = gaze::from_yaw_pitch(0.3, 0.0);
+    assert!(right.x > 0.0 && right.y.abs() < EPSILON && right.z > 0.0);
+
+    let up = gaze::from_yaw_pitch(0.0, 0.3);
+    assert!(up.x.abs() < EPSILON && up.y > 0.0 && up.z > 0.0);
+}
+
+#[test]
+fn yaw_pitch_round_trips() {
+    for &(yaw, pitch) in &[(0.0, 0.0), (0.4, -0.2), (-1.2, 0.7), (2.5, 0.1)] {
+        let direction = gaze::from_yaw_pitch(yaw, pitch);
+        assert!((direction.length() - 1.0).abs() < EPSILON);
+        assert_vec2_eq(gaze::to_yaw_pitch(direction), Vec2::new(yaw, pitch));
+    }
+}
+
+#[test]
+fn projection_round_trips() {
+    let projection = Vec2::new(0.3, -0.4);
+    let direction = gaze::from_projection(projection);
+    assert!((direction.length() - 1.0).abs() < EPSILON);
+    assert!(direction.z > 0.0);
+    assert_vec2_eq(gaze::to_projection(direction), projection);
+}
+
+#[test]
+fn oversized_projection_is_clamped_to_unit_circle() {
+    let direction = gaze::from_projection(Vec2::new(3.0, 4.0));
+    assert_vec3_eq(direction, Vec3::new(0.6, 0.8, 0.0));
+}
+
+#[test]
+fn degenerate_direction_falls_back_to_forward() {
+    assert_eq!(gaze::normalize(Vec3::ZERO), gaze::FORWARD);
+    assert_vec2_eq(gaze::to_yaw_pitch(Vec3::ZERO), Vec2::ZERO);
+    assert_vec2_eq(gaze::to_projection(Vec3::ZERO), Vec2::ZERO);
+}
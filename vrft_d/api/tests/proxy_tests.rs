@@ -0,0 +1,164 @@
+//! Proxy tests
+//!
+//! A stand-in for the .NET runtime host writes the shared memory layout and the proxy
+//! reads it back, on whichever backend the platform uses.
+
+use api::proxy::{
+    MarshaledTrackingData, SharedMemory, ShmemHeader, SHMEM_LAYOUT_VERSION, SHMEM_SIZE,
+};
+use api::{ProxyModule, TrackingModule, TrackingState, UnifiedExpressions, UnifiedTrackingData};
+
+/// A name no other test run uses, valid for both backends.
+fn unique_name(test: &str) -> String {
+    let name = format!("VRFT_Test_{}_{}", test, std::process::id());
+    if cfg!(windows) {
+        format!("Local\\{}", name)
+    } else {
+        format!("/{}", name)
+    }
+}
+
+fn write(shmem: &SharedMemory, data: MarshaledTrackingData) {
+    unsafe { std::ptr::write_unaligned(shmem.as_ptr() as *mut MarshaledTrackingData, data) }
+}
+
+fn read(shmem: &SharedMemory) -> MarshaledTrackingData {
+    unsafe { std::ptr::read_unaligned(shmem.as_ptr() as *const MarshaledTrackingData) }
+}
+
+#[test]
+fn layout_matches_runtime_host() {
+    // A five field header, 18 floats, 200 shapes and two heartbeats, packed.
+    assert_eq!(SHMEM_SIZE, 5 * 4 + 18 * 4 + 200 * 4 + 2 * 8);
+}
+
+#[test]
+fn proxy_reads_stand_in_host() {
+    let name = unique_name("reads");
+    let host = SharedMemory::create(&name, SHMEM_SIZE).unwrap();
+
+    let mut frame = MarshaledTrackingData {
+        header: ShmemHeader::current(std::process::id()),
+        left_eye_openness: 0.25,
+        right_eye_pupil_diameter_mm: 4.0,
+        head_yaw: 1.5,
+        runtime_heartbeat: 1,
+        ..Default::default()
+    };
+    frame.shapes[UnifiedExpressions::JawOpen as usize] = 0.75;
+    write(&host, frame);
+
+    let mut proxy = ProxyModule::new().with_shmem_name(&name);
+    proxy.attach().unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    assert_eq!(proxy.update(&mut data), TrackingState::NewFrame);
+    assert_eq!(data.eye.left.openness, 0.25);
+    assert_eq!(data.eye.right.pupil_diameter_mm, 4.0);
+    assert_eq!(data.head.head_yaw, 1.5);
+    assert_eq!(
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight,
+        0.75
+    );
+
+    // The proxy bumps its own heartbeat so the host can tell it is alive.
+    let main_app_heartbeat = read(&host).main_app_heartbeat;
+    assert_eq!(main_app_heartbeat, 1);
+    // The runtime has not written since, so there is nothing new.
+    assert_eq!(proxy.update(&mut data), TrackingState::NoNewFrame);
+    let main_app_heartbeat = read(&host).main_app_heartbeat;
+    assert_eq!(main_app_heartbeat, 2);
+
+    proxy.unload();
+}
+
+#[test]
+fn opening_missing_shared_memory_fails() {
+    assert!(SharedMemory::open(&unique_name("missing"), SHMEM_SIZE).is_err());
+}
+
+#[test]
+fn proxy_refuses_mismatched_layout() {
+    let name = unique_name("mismatch");
+    let host = SharedMemory::create(&name, SHMEM_SIZE).unwrap();
+    write(
+        &host,
+        MarshaledTrackingData {
+            header: ShmemHeader {
+                layout_version: SHMEM_LAYOUT_VERSION + 1,
+                ..ShmemHeader::current(4242)
+            },
+            ..Default::default()
+        },
+    );
+
+    let error = ProxyModule::new()
+        .with_shmem_name(&name)
+        .attach()
+        .unwrap_err();
+    let message = format!("{:#}", error);
+    assert!(message.contains("layout version"), "{}", message);
+    assert!(message.contains("4242"), "{}", message);
+}
+
+#[test]
+fn proxy_waits_out_what_an_earlier_host_left() {
+    let name = unique_name("stale");
+    let host = SharedMemory::create(&name, SHMEM_SIZE).unwrap();
+    // An older runtime with another layout ran before and left its bytes behind.
+    write(
+        &host,
+        MarshaledTrackingData {
+            header: ShmemHeader {
+                layout_version: SHMEM_LAYOUT_VERSION + 1,
+                ..ShmemHeader::current(4242)
+            },
+            ..Default::default()
+        },
+    );
+
+    let runtime_pid = 4343;
+    let runtime_name = name.clone();
+    let runtime = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let runtime = SharedMemory::open(&runtime_name, SHMEM_SIZE).unwrap();
+        write(
+            &runtime,
+            MarshaledTrackingData {
+                header: ShmemHeader::current(runtime_pid),
+                head_yaw: 1.5,
+                runtime_heartbeat: 1,
+                ..Default::default()
+            },
+        );
+    });
+
+    let mut proxy = ProxyModule::new().with_shmem_name(&name);
+    proxy.attach_to(runtime_pid).unwrap();
+    runtime.join().unwrap();
+
+    let mut data = UnifiedTrackingData::default();
+    assert_eq!(proxy.update(&mut data), TrackingState::NewFrame);
+    assert_eq!(data.head.head_yaw, 1.5);
+    proxy.unload();
+}
+
+#[test]
+fn header_check_reports_each_mismatch() {
+    let current = ShmemHeader::current(1);
+    assert!(current.is_written());
+    assert!(current.check().is_ok());
+    assert!(!ShmemHeader::default().is_written());
+
+    let garbage = ShmemHeader {
+        magic: 0x3f80_0000,
+        ..current
+    };
+    assert!(garbage.check().is_err());
+
+    let fewer_shapes = ShmemHeader {
+        shape_count: 100,
+        ..current
+    };
+    assert!(format!("{:#}", fewer_shapes.check().unwrap_err()).contains("100 shapes"));
+}
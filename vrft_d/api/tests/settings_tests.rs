@@ -0,0 +1,88 @@
+//! Module settings and schema validation tests
+
+use api::settings::validate;
+use api::ModuleSettings;
+use serde_json::{json, Value};
+
+fn schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["mode"],
+        "properties": {
+            "mode": { "enum": ["fast", "smooth"] },
+            "factor": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+            "enabled": { "type": "boolean" },
+            "ports": { "type": "array", "items": { "type": "integer" } }
+        }
+    })
+}
+
+#[test]
+fn null_settings_become_empty_object() {
+    let settings = ModuleSettings::new(Value::Null);
+    assert_eq!(settings, ModuleSettings::default());
+    assert_eq!(settings.value(), &json!({}));
+}
+
+#[test]
+fn typed_getters_fall_back_to_defaults() {
+    let settings = ModuleSettings::new(json!({ "enabled": true, "factor": 0.25, "name": 3 }));
+    assert!(settings.bool_or("enabled", false));
+    assert_eq!(settings.f32_or("factor", 1.0), 0.25);
+    assert_eq!(settings.f32_or("missing", 1.0), 1.0);
+    assert_eq!(settings.get::<String>("name"), None);
+}
+
+#[test]
+fn valid_settings_pass() {
+    let settings = ModuleSettings::new(json!({
+        "mode": "smooth",
+        "factor": 0.5,
+        "enabled": false,
+        "ports": [9000, 9001]
+    }));
+    assert!(settings.validate(&schema()).is_ok());
+}
+
+#[test]
+fn invalid_settings_name_the_offending_path() {
+    let cases = [
+        (json!({ "factor": 0.5 }), "settings.mode is required"),
+        (json!({ "mode": "slow" }), "settings.mode must be one of"),
+        (
+            json!({ "mode": "fast", "factor": 2.0 }),
+            "settings.factor must be at most 1",
+        ),
+        (
+            json!({ "mode": "fast", "enabled": 1 }),
+            "settings.enabled must be of type boolean",
+        ),
+        (
+            json!({ "mode": "fast", "ports": [1, "x"] }),
+            "settings.ports[1] must be of type integer",
+        ),
+        (
+            json!({ "mode": "fast", "extra": 1 }),
+            "settings.extra is not a known setting",
+        ),
+        (json!([]), "settings must be of type object"),
+    ];
+    for (value, expected) in cases {
+        let err = ModuleSettings::new(value.clone())
+            .validate(&schema())
+            .expect_err(&value.to_string());
+        assert!(
+            err.to_string().starts_with(expected),
+            "{} -> {}",
+            value,
+            err
+        );
+    }
+}
+
+#[test]
+fn unknown_keywords_are_ignored() {
+    let schema = json!({ "type": "object", "title": "Example", "patternProperties": {} });
+    assert!(validate(&schema, &json!({ "anything": 1 }), "settings").is_ok());
+}
@@ -0,0 +1,214 @@
+//! Checks a native module against the host contract and writes a JSON report.
+//!
+//! Usage: `vrft_conformance <module library or plugin folder> [--settings <file>]
+//! [--updates <n>] [--budget-ms <ms>] [--stream-ms <ms>] [--cycles <n>] [--report <file>]`.
+//!
+//! The suite in `vrft_d::plugins::conformance` runs in a child copy of this process, so a
+//! module that crashes, or lets a panic escape, fails the check it happened in instead of
+//! taking the runner down. Exits with 0 if every check passed or only warned, 1 otherwise.
+
+use anyhow::{bail, Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use vrft_d::plugins::conformance::{
+    CheckResult, CheckStatus, ConformanceReport, Instance, Suite, SuiteOptions, CHECKS,
+};
+use vrft_d::plugins::manifest::{PluginManifest, MANIFEST_FILE};
+use vrft_d::plugins::native::load_native_module;
+
+/// Runs the suite in this process and prints each result as a JSON line.
+const RUN_SUITE_FLAG: &str = "--run-suite";
+
+struct Args {
+    module: PathBuf,
+    options: SuiteOptions,
+    report: Option<PathBuf>,
+    run_suite: bool,
+    /// Everything but the module and `--run-suite`, passed on to the child.
+    forwarded: Vec<String>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1);
+    let mut module = None;
+    let mut options = SuiteOptions::default();
+    let mut report = None;
+    let mut run_suite = false;
+    let mut forwarded = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if arg == RUN_SUITE_FLAG {
+            run_suite = true;
+            continue;
+        }
+        if !arg.starts_with("--") {
+            module = Some(PathBuf::from(arg));
+            continue;
+        }
+        let value = args
+            .next()
+            .with_context(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--settings" => {
+                let text = std::fs::read_to_string(&value)
+                    .with_context(|| format!("Failed to read {}", value))?;
+                options.settings = serde_json::from_str(&text)
+                    .with_context(|| format!("Invalid settings in {}", value))?;
+            }
+            "--updates" => options.updates = value.parse().context("Invalid --updates")?,
+            "--budget-ms" => {
+                options.budget =
+                    Duration::from_millis(value.parse().context("Invalid --budget-ms")?)
+            }
+            "--stream-ms" => {
+                options.stream_for =
+                    Duration::from_millis(value.parse().context("Invalid --stream-ms")?)
+            }
+            "--cycles" => options.cycles = value.parse().context("Invalid --cycles")?,
+            "--report" => report = Some(PathBuf::from(&value)),
+            _ => bail!("Unknown option {}", arg),
+        }
+        forwarded.push(arg);
+        forwarded.push(value);
+    }
+
+    Ok(Args {
+        module: module.context("Missing module path")?,
+        options,
+        report,
+        run_suite,
+        forwarded,
+    })
+}
+
+/// The module name and library behind `path`, a library or a plugin folder.
+fn resolve(path: &Path) -> Result<(String, PathBuf)> {
+    if path.is_dir() {
+        let manifest = PluginManifest::read(&path.join(MANIFEST_FILE))?;
+        if manifest.runtime != common::ModuleRuntime::Native {
+            bail!("Only native modules can be checked");
+        }
+        let library = manifest.check(path)?;
+        return Ok((manifest.id, library));
+    }
+    if !path.is_file() {
+        bail!("{:?} not found", path);
+    }
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("module")
+        .to_string();
+    Ok((name, path.to_path_buf()))
+}
+
+fn run_suite(name: &str, library: &Path, options: SuiteOptions) {
+    let create = || {
+        let (module, library) = load_native_module(library)?;
+        Ok(Instance {
+            module,
+            library: Some(library),
+        })
+    };
+    Suite::new(name, create, options).run(|result| {
+        let line = serde_json::to_string(result).expect("check results serialize");
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{}", line);
+        let _ = stdout.flush();
+    });
+}
+
+/// Runs the suite in a child process and fills in what a crash left unreported.
+fn run_isolated(args: &Args, name: &str) -> Result<ConformanceReport> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .arg(&args.module)
+        .args(&args.forwarded)
+        .arg(RUN_SUITE_FLAG)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to start the suite")?;
+
+    let mut checks: Vec<CheckResult> = Vec::new();
+    let stdout = child.stdout.take().context("No suite output")?;
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        // The module may print to stdout too.
+        match serde_json::from_str(&line) {
+            Ok(result) => checks.push(result),
+            Err(_) => eprintln!("{}", line),
+        }
+    }
+    let status = child.wait()?;
+
+    let missing: Vec<&str> = CHECKS
+        .iter()
+        .copied()
+        .filter(|name| !checks.iter().any(|c| c.name == *name))
+        .collect();
+    if let Some(crashed_in) = missing.first().copied() {
+        for check in &missing {
+            checks.push(if *check == crashed_in {
+                CheckResult::new(
+                    check,
+                    CheckStatus::Fail,
+                    format!("The process died during this check ({})", status),
+                )
+            } else if *check == "panic_containment" {
+                CheckResult::new(
+                    check,
+                    CheckStatus::Fail,
+                    format!(
+                        "The module took the process down during {}: a panic escaped it, or it crashed",
+                        crashed_in
+                    ),
+                )
+            } else {
+                CheckResult::new(check, CheckStatus::Skip, "Not run after the crash")
+            });
+        }
+        checks.sort_by_key(|c| CHECKS.iter().position(|name| *name == c.name));
+    }
+    Ok(ConformanceReport::new(name, checks))
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .target(env_logger::Target::Stderr)
+        .init();
+
+    let report = match resolve(&args.module) {
+        Ok((name, library)) if args.run_suite => {
+            run_suite(&name, &library, args.options);
+            return Ok(());
+        }
+        Ok((name, _)) => run_isolated(&args, &name)?,
+        Err(e) => {
+            let mut checks = vec![CheckResult::new(
+                "load",
+                CheckStatus::Fail,
+                format!("{:#}", e),
+            )];
+            checks.extend(
+                CHECKS[1..]
+                    .iter()
+                    .map(|name| CheckResult::new(name, CheckStatus::Skip, "Module did not load")),
+            );
+            ConformanceReport::new(&args.module.to_string_lossy(), checks)
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&report)?;
+    match &args.report {
+        Some(path) => {
+            std::fs::write(path, json).with_context(|| format!("Failed to write {:?}", path))?
+        }
+        None => println!("{}", json),
+    }
+    for check in &report.checks {
+        eprintln!("{:?}\t{}\t{}", check.status, check.name, check.detail);
+    }
+    std::process::exit(if report.passed { 0 } else { 1 });
+}
@@ -0,0 +1,184 @@
+//! Runs one native module on behalf of `vrft_d`, so a crash or hang in the module does not
+//! take the daemon down with it.
+//!
+//! Usage: `vrft_module_host <module path> <port> [log level]`, with the token to greet with
+//! in `VRFT_MODULE_HOST_TOKEN`. The host connects to `vrft_d` on the loopback port and speaks
+//! the protocol in `vrft_d::plugins::sandbox`.
+
+use anyhow::{Context, Result};
+use api::{ConnectionState, FrameSink, Host, HostServices, TrackingState, UnifiedTrackingData};
+use log::info;
+use std::net::{Ipv4Addr, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use vrft_d::plugins::host::module_logger;
+use vrft_d::plugins::native::load_native_module;
+use vrft_d::plugins::sandbox::{
+    spawn_reader, write_message, ChildMessage, HostMessage, HEARTBEAT_INTERVAL, TOKEN_ENV,
+};
+
+/// The connection to `vrft_d`, shared with the module's threads.
+#[derive(Clone)]
+struct Daemon {
+    writer: Arc<Mutex<TcpStream>>,
+}
+
+impl Daemon {
+    fn send(&self, message: &ChildMessage) {
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(e) = write_message(&mut *writer, message) {
+            // The daemon is gone; the command loop notices and exits.
+            log::debug!("Failed to send to vrft_d: {:#}", e);
+        }
+    }
+}
+
+impl Host for Daemon {
+    fn connection_changed(&self, state: ConnectionState) {
+        self.send(&ChildMessage::Connection { state });
+    }
+
+    fn request_recalibration(&self) {
+        self.send(&ChildMessage::Recalibrate);
+    }
+
+    fn set_gauge(&self, name: &str, value: f64) {
+        self.send(&ChildMessage::Gauge {
+            name: name.to_string(),
+            value,
+        });
+    }
+
+    fn add_counter(&self, name: &str, delta: u64) {
+        self.send(&ChildMessage::Counter {
+            name: name.to_string(),
+            delta,
+        });
+    }
+}
+
+fn reply(daemon: &Daemon, result: Result<()>) {
+    daemon.send(&ChildMessage::Reply {
+        error: result.err().map(|e| format!("{:#}", e)),
+    });
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let module_path = args.next().context("Missing module path")?;
+    let port: u16 = args
+        .next()
+        .context("Missing port")?
+        .parse()
+        .context("Invalid port")?;
+    let log_level = args.next().unwrap_or_else(|| "info".to_string());
+    env_logger::Builder::new().parse_filters(&log_level).init();
+    let token = std::env::var(TOKEN_ENV).with_context(|| format!("Missing {}", TOKEN_ENV))?;
+
+    let stream =
+        TcpStream::connect((Ipv4Addr::LOCALHOST, port)).context("Failed to connect to vrft_d")?;
+    stream.set_nodelay(true)?;
+    let commands = spawn_reader::<HostMessage>(stream.try_clone()?, "daemon-reader")?;
+    let daemon = Daemon {
+        writer: Arc::new(Mutex::new(stream)),
+    };
+
+    let module_path = Path::new(&module_path);
+    let name = module_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("module")
+        .to_string();
+    // `lib` must outlive the module, which is dropped explicitly at the end.
+    let (mut module, _lib) = match load_native_module(module_path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            daemon.send(&ChildMessage::Hello {
+                pid: std::process::id(),
+                token,
+                descriptor: None,
+                error: Some(format!("{:#}", e.context("Failed to load module"))),
+            });
+            return Ok(());
+        }
+    };
+    daemon.send(&ChildMessage::Hello {
+        pid: std::process::id(),
+        token,
+        descriptor: module.descriptor(),
+        error: None,
+    });
+
+    let mut data = UnifiedTrackingData::default();
+    let mut streaming = false;
+    let mut initialized = false;
+    let mut last_state = None;
+    let mut last_heartbeat = Instant::now();
+    let mut wait = Duration::ZERO;
+
+    loop {
+        match commands.recv_timeout(wait) {
+            Ok(HostMessage::Configure { settings }) => {
+                reply(&daemon, module.configure(&settings));
+                continue;
+            }
+            Ok(HostMessage::Initialize { state_dir }) => {
+                let host =
+                    HostServices::new(module_logger(&name), state_dir, Arc::new(daemon.clone()));
+                let result = module.initialize(host);
+                initialized = result.is_ok();
+                reply(&daemon, result);
+                if initialized {
+                    let sink_daemon = daemon.clone();
+                    streaming = module.start_streaming(FrameSink::new(move |data| {
+                        sink_daemon.send(&ChildMessage::Frame {
+                            data: Box::new(data.clone()),
+                        });
+                    }));
+                    if streaming {
+                        info!("Module {} is streaming frames", name);
+                    }
+                }
+                wait = Duration::ZERO;
+                continue;
+            }
+            Ok(HostMessage::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        wait = HEARTBEAT_INTERVAL;
+        if initialized && !streaming && last_state != Some(TrackingState::Fatal) {
+            data.timestamp_us = 0;
+            let state = module.update(&mut data);
+            if state == TrackingState::NewFrame {
+                daemon.send(&ChildMessage::Frame {
+                    data: Box::new(data.clone()),
+                });
+                last_heartbeat = Instant::now();
+            } else if last_state != Some(state) {
+                daemon.send(&ChildMessage::State { state });
+                last_heartbeat = Instant::now();
+            }
+            last_state = Some(state);
+            // Same pacing as the daemon's own polling loop.
+            wait = match state {
+                TrackingState::NewFrame => Duration::from_millis(1),
+                TrackingState::NoNewFrame | TrackingState::Reconnecting => Duration::from_millis(5),
+                TrackingState::Disconnected | TrackingState::Fatal => HEARTBEAT_INTERVAL,
+            };
+        }
+
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            daemon.send(&ChildMessage::Heartbeat);
+            last_heartbeat = Instant::now();
+        }
+    }
+
+    if initialized {
+        module.unload();
+    }
+    drop(module);
+    Ok(())
+}
@@ -1,21 +1,48 @@
 use crate::strategies::PlatformBackend;
 use anyhow::Result;
-use common::{IntegrationAdapter, UnifiedTrackingData};
+use common::{
+    timestamp_us_now, IntegrationAdapter, LatencyStage, PipelineLatency, UnifiedTrackingData,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
 pub struct Dispatcher {
     backend: PlatformBackend,
+    latency: Arc<PipelineLatency>,
+    last_sequence: AtomicU64,
 }
 
 impl Dispatcher {
-    pub fn new(backend: PlatformBackend) -> Self {
-        Self { backend }
+    pub fn new(backend: PlatformBackend, latency: Arc<PipelineLatency>) -> Self {
+        Self {
+            backend,
+            latency,
+            last_sequence: AtomicU64::new(0),
+        }
     }
 
     pub fn initialize(&mut self) -> Result<()> {
         self.backend.initialize()
     }
 
+    /// Sends a frame and records its send and capture-to-send latency.
+    ///
+    /// Frames resent with an already seen sequence number are not measured again.
     pub fn send(&self, data: &UnifiedTrackingData) -> Result<()> {
-        self.backend.send(data)
+        let start = Instant::now();
+        let result = self.backend.send(data);
+
+        if self.last_sequence.swap(data.sequence, Ordering::Relaxed) != data.sequence {
+            self.latency
+                .record(LatencyStage::Send, start.elapsed().as_micros() as u64);
+            self.latency.record_since(
+                LatencyStage::CaptureToSend,
+                data.timestamp_us,
+                timestamp_us_now(),
+            );
+        }
+
+        result
     }
 }
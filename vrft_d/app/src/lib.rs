@@ -1,4 +1,5 @@
 pub mod osc;
 
 pub mod dispatcher;
+pub mod plugins;
 pub mod strategies;
@@ -1,16 +1,22 @@
 use vrft_d::osc;
 
 use vrft_d::dispatcher;
+use vrft_d::plugins::emitter::FrameEmitter;
+use vrft_d::plugins::fusion::SourceMap;
+use vrft_d::plugins::host::SharedModuleStatus;
+use vrft_d::plugins::integrity::IntegrityPolicy;
+use vrft_d::plugins::manager::{
+    ModuleContext, ModuleControl, ModuleDirs, ModuleKind, ModuleManager,
+};
+use vrft_d::plugins::settings::ModuleSettingsStore;
 use vrft_d::strategies;
 
 use anyhow::Result;
-use api::{
-    LogLevel, ModuleLogger, ProxyModule, TrackingModule, UnifiedExpressions, UnifiedTrackingData,
-};
+use api::{ModuleDescriptor, UnifiedExpressions, UnifiedTrackingData};
 use common::{
-    CalibrationData, CalibrationState, ModuleRuntime, MutationConfig, UnifiedTrackingMutator,
+    gaze, timestamp_us_now, CalibrationData, CalibrationState, LatencyStage, ModuleRuntime,
+    MutationConfig, PipelineLatency, UnifiedTrackingMutator,
 };
-use libloading::{Library, Symbol};
 use log::{debug, error, info, trace, warn};
 use osc::query::host::{CalibrationStatus, OscQueryHost};
 use std::collections::HashMap;
@@ -24,6 +30,10 @@ use std::time::Duration;
 
 use dispatcher::Dispatcher;
 
+/// How often the producer loop applies commands and checks on the active module, which is
+/// polled on its own thread.
+const WATCH_INTERVAL: Duration = Duration::from_millis(10);
+
 fn load_config(path: &Path) -> Result<MutationConfig> {
     if path.exists() {
         info!("Loading config from {:?}", path);
@@ -41,23 +51,6 @@ fn load_config(path: &Path) -> Result<MutationConfig> {
     }
 }
 
-extern "C" fn module_log_callback(level: LogLevel, target: *const i8, message: *const i8) {
-    unsafe {
-        let target_str = std::ffi::CStr::from_ptr(target)
-            .to_str()
-            .unwrap_or("unknown");
-        let message_str = std::ffi::CStr::from_ptr(message).to_str().unwrap_or("");
-
-        match level {
-            LogLevel::Error => error!(target: target_str, "{}", message_str),
-            LogLevel::Warn => warn!(target: target_str, "{}", message_str),
-            LogLevel::Info => info!(target: target_str, "{}", message_str),
-            LogLevel::Debug => debug!(target: target_str, "{}", message_str),
-            LogLevel::Trace => trace!(target: target_str, "{}", message_str),
-        }
-    }
-}
-
 fn main() -> Result<()> {
     if std::env::var("RUST_LOG").is_err() {
         unsafe {
@@ -79,13 +72,6 @@ fn main() -> Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
-    struct LoadedModule {
-        name: String,
-        module: Box<dyn TrackingModule>,
-        #[allow(dead_code)]
-        _lib: Option<Library>, // Keep library loaded; dropped on shutdown
-    }
-
     let config_path = Path::new("config.json");
     let config = load_config(config_path).unwrap_or_else(|e| {
         error!("Failed to load config: {}. Using defaults.", e);
@@ -93,125 +79,27 @@ fn main() -> Result<()> {
     });
     info!("Loaded Config: {:?}", config);
 
-    let mut modules: Vec<LoadedModule> = Vec::new();
-
-    let mut native_plugins_dir = Path::new("plugins/native").to_path_buf();
-    if !native_plugins_dir.exists() {
-        let parent_native = Path::new("../plugins/native");
-        if parent_native.exists() {
-            native_plugins_dir = parent_native.to_path_buf();
-        }
-    }
-
-    if native_plugins_dir.exists() {
-        for entry in fs::read_dir(&native_plugins_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path
-                .extension()
-                .is_some_and(|ext| ext == "dll" || ext == "so" || ext == "dylib")
-            {
-                let filename = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                info!("Loading module: {:?}", path);
-
-                match (|| -> Result<(Box<dyn TrackingModule>, Library)> {
-                    unsafe {
-                        let lib = Library::new(&path)?;
-                        let func: Symbol<unsafe extern "C" fn() -> Box<dyn TrackingModule>> =
-                            lib.get(b"create_module")?;
-                        let module = func();
-                        Ok((module, lib))
-                    }
-                })() {
-                    Ok((module, lib)) => {
-                        info!("✓ Successfully loaded module: {}", filename);
-                        modules.push(LoadedModule {
-                            name: filename,
-                            module,
-                            _lib: Some(lib),
-                        });
-                    }
-                    Err(e) => {
-                        error!("✗ Failed to load module {:?}: {}", path, e);
-                    }
-                }
-            }
-        }
-    } else {
+    let module_dirs = ModuleDirs::locate();
+    if !module_dirs.native.exists() {
         warn!("'plugins/native' directory not found. Creating it.");
-        fs::create_dir_all(native_plugins_dir)?;
+        fs::create_dir_all(&module_dirs.native)?;
     }
+    let module_control = Arc::new(RwLock::new(
+        ModuleControl::new(module_dirs).with_instances(config.module.instances.clone()),
+    ));
+    let module_control_for_host = module_control.clone();
 
-    // Check if the active plugin is already satisfied by a native module
-    let native_active_found = modules.iter().any(|m| m.name == config.module.active);
-
-    // Attempt .NET module loading if configured and native module not found
-    if config.module.runtime == ModuleRuntime::Vrcft && !native_active_found {
-        let mut vrcft_dir = Path::new("plugins/dotnet/modules").to_path_buf();
-        let mut host_exe = Path::new("plugins/dotnet/host/VrcftRuntime.exe").to_path_buf();
+    // Set by the module manager whenever the active module changes.
+    let module_descriptor = Arc::new(RwLock::new(None::<ModuleDescriptor>));
 
-        if !vrcft_dir.exists() {
-            let parent_vrcft = Path::new("../plugins/dotnet/modules");
-            if parent_vrcft.exists() {
-                vrcft_dir = parent_vrcft.to_path_buf();
-            }
-        }
-        if !host_exe.exists() {
-            let parent_host = Path::new("../plugins/dotnet/host/VrcftRuntime.exe");
-            if parent_host.exists() {
-                host_exe = parent_host.to_path_buf();
-            }
-        }
+    let settings_store = Arc::new(RwLock::new(
+        ModuleSettingsStore::new(&config.module.settings).with_config_file(config_path),
+    ));
+    let settings_store_for_host = settings_store.clone();
 
-        if vrcft_dir.exists() {
-            let target_dll = vrcft_dir.join(&config.module.active);
-            if target_dll.exists() {
-                if host_exe.exists() {
-                    let mut proxy = ProxyModule::new();
-                    info!("Starting VrcftRuntime for module: {:?}", target_dll);
-                    match proxy.start(&host_exe, &target_dll) {
-                        Ok(_) => {
-                            info!("✓ VrcftRuntime started successfully.");
-                            modules.push(LoadedModule {
-                                name: config.module.active.clone(),
-                                module: Box::new(proxy),
-                                _lib: None,
-                            });
-                        }
-                        Err(e) => error!("✗ Failed to start VrcftRuntime: {}", e),
-                    }
-                } else {
-                    error!("✗ VrcftRuntime.exe not found at {:?}", host_exe);
-                }
-            } else {
-                debug!(
-                    "No VRCFT module found matching '{}' in '{:?}'",
-                    config.module.active, vrcft_dir
-                );
-            }
-        }
-    } else if config.module.runtime == ModuleRuntime::Native && !native_active_found {
-        debug!(
-            "module_runtime is Native but active plugin '{}' not found in native modules.",
-            config.module.active
-        );
-    } else if native_active_found {
-        info!(
-            "Active plugin '{}' is a native module. Skipping VRCFT search.",
-            config.module.active
-        );
-    }
-
-    if modules.is_empty() {
-        warn!("No modules loaded!");
-    } else {
-        info!("Loaded {} module(s) successfully", modules.len());
-    }
+    let module_status = SharedModuleStatus::default();
+    let module_status_for_host = module_status.clone();
+    let module_metrics_for_host = module_status.clone();
 
     let shared_data = Arc::new(RwLock::new(UnifiedTrackingData::default()));
     let shared_data_for_host = shared_data.clone();
@@ -236,14 +124,21 @@ fn main() -> Result<()> {
     let calibration_needs_save = Arc::new(AtomicBool::new(false));
     let calibration_needs_save_for_consumer = calibration_needs_save.clone();
 
-    let mut data = UnifiedTrackingData::default();
+    let latency = Arc::new(PipelineLatency::new());
+    let latency_for_host = latency.clone();
+    let latency_for_consumer = latency.clone();
+
+    let (tx, rx) = sync_channel::<UnifiedTrackingData>(1);
+    let emitter = Arc::new(FrameEmitter::new(tx, config.max_fps));
+    let emitter_for_host = emitter.clone();
 
     let osc_context = strategies::OscContext {
         tracking_data: shared_data_for_host.clone(),
+        module_descriptor: module_descriptor.clone(),
     };
     let (strategy, strategy_router, _avatar_change_rx) =
         strategies::create_strategy(&config, osc_context);
-    let mut transport_manager = Dispatcher::new(strategy);
+    let mut transport_manager = Dispatcher::new(strategy, latency);
 
     if let Err(e) = transport_manager.initialize() {
         error!("Failed to initialize transport manager: {}", e);
@@ -262,7 +157,17 @@ fn main() -> Result<()> {
                 calibration_status_for_host,
                 calibration_data_for_host,
                 calibration_request_for_host,
-            );
+            )
+            .merge(osc::query::metrics::get_router(
+                latency_for_host,
+                module_metrics_for_host,
+            ))
+            .merge(osc::query::modules::get_router(
+                settings_store_for_host,
+                module_status_for_host,
+                module_control_for_host,
+                emitter_for_host,
+            ));
 
             let app_router = if let Some(strategy_router) = strategy_router {
                 extensions_router.merge(strategy_router)
@@ -288,25 +193,76 @@ fn main() -> Result<()> {
         info!("No calibration found; using defaults.");
     }
 
-    info!("Initializing Modules...");
-    for module_wrapper in &mut modules {
-        let logger_name = format!("vrft_d::plugins::{}", module_wrapper.name);
-        let logger = ModuleLogger::new(module_log_callback, logger_name);
-
-        match module_wrapper.module.initialize(logger) {
-            Ok(_) => {
-                info!("✓ Initialized module: {}", module_wrapper.name);
-            }
-            Err(e) => {
-                error!(
-                    "✗ Failed to initialize module {}: {}",
-                    module_wrapper.name, e
-                );
-            }
+    info!("Loading Modules...");
+    let mut module_manager = ModuleManager::new(
+        ModuleContext {
+            status: module_status,
+            calibration_request: calibration_request.clone(),
+            settings: settings_store.clone(),
+            descriptor: module_descriptor,
+            emitter: emitter.clone(),
+        },
+        module_control,
+    )
+    .with_hot_reload(config.module.hot_reload)
+    .with_sandbox(config.module.sandbox)
+    .with_integrity(IntegrityPolicy::from_config(&config.module.integrity))
+    .with_update_deadline(
+        Duration::from_millis(config.module.update_deadline_ms),
+        config.module.on_stall,
+    )
+    .with_sources(SourceMap::from_config(&config.module.sources))
+    .with_failover(
+        config.module.active.clone(),
+        Duration::from_millis(config.module.failover_after_ms),
+        Duration::from_millis(config.module.crossfade_ms),
+    );
+    if config.module.hot_reload {
+        info!("Hot reload is on; native modules reload when their library file changes.");
+    }
+    if config.module.sandbox {
+        info!("Sandbox is on; native modules run in their own vrft_module_host process.");
+    }
+    if config.module.integrity.enabled {
+        info!("Integrity policy is on; native modules must be allowlisted or signed.");
+    }
+    // Only the modules that are selected get loaded; the rest are listed as available.
+    module_manager.discover();
+
+    // The first module of the failover chain that starts is activated; the rest stand by.
+    for active_plugin in &config.module.active {
+        let native = module_manager
+            .find(active_plugin)
+            .is_some_and(|file| file.kind == ModuleKind::Native);
+        if native {
+            info!(
+                "Active plugin '{}' is a native module. Skipping VRCFT search.",
+                active_plugin
+            );
+        } else if config.module.runtime == ModuleRuntime::Native {
+            debug!(
+                "module_runtime is Native but active plugin '{}' not found in native modules.",
+                active_plugin
+            );
+        }
+        // A VRCFT module is started when it is activated.
+        if (native || config.module.runtime == ModuleRuntime::Vrcft)
+            // Failures are logged; another module can still be activated through the HTTP API.
+            && module_manager.activate(active_plugin).is_ok()
+        {
+            break;
         }
     }
+    module_manager.start_standby();
 
-    let (tx, rx) = sync_channel::<UnifiedTrackingData>(1);
+    if module_manager.modules().is_empty() {
+        warn!("No modules loaded!");
+    } else {
+        info!(
+            "Loaded {} module(s) successfully",
+            module_manager.modules().len()
+        );
+    }
 
     let running_consumer = running.clone();
 
@@ -315,14 +271,17 @@ fn main() -> Result<()> {
 
         let transport_manager = transport_manager;
         let mut last_frame_time = std::time::Instant::now();
+        let mut last_capture_us: u64 = 0;
         let mut was_calibrating = false;
 
         // Hold last received data to prevent glitches on tracking loss
         let mut last_received_data: Option<UnifiedTrackingData> = None;
 
         while running_consumer.load(Ordering::SeqCst) {
+            let mut is_new_frame = false;
             let mut received_data = match rx.recv_timeout(Duration::from_millis(100)) {
                 Ok(data) => {
+                    is_new_frame = true;
                     last_received_data = Some(data.clone());
                     data
                 }
@@ -359,16 +318,13 @@ fn main() -> Result<()> {
                         }
                     }
 
-                    for i in 0..UnifiedExpressions::Max as usize {
-                        if let Ok(expr) = UnifiedExpressions::try_from(i) {
-                            let name = format!("v2/{:?}", expr);
-                            if let Some(&val) = debug.get(&name) {
-                                received_data.shapes[i].weight = val;
-                            } else if let Some(short_name) = name.strip_prefix("v2/") {
-                                if let Some(&val) = debug.get(short_name) {
-                                    received_data.shapes[i].weight = val;
-                                }
-                            }
+                    for expr in UnifiedExpressions::iter() {
+                        let name = expr.name();
+                        if let Some(&val) = debug
+                            .get(&format!("v2/{}", name))
+                            .or_else(|| debug.get(name))
+                        {
+                            received_data.shapes[expr.index()].weight = val;
                         }
                     }
 
@@ -386,17 +342,25 @@ fn main() -> Result<()> {
                         received_data.eye.right.pupil_diameter_mm = val;
                     }
 
-                    if let Some(&x) = debug.get("EyeLeftGazeX") {
-                        received_data.eye.left.gaze.x = x;
-                    }
-                    if let Some(&y) = debug.get("EyeLeftGazeY") {
-                        received_data.eye.left.gaze.y = y;
-                    }
-                    if let Some(&x) = debug.get("EyeRightGazeX") {
-                        received_data.eye.right.gaze.x = x;
-                    }
-                    if let Some(&y) = debug.get("EyeRightGazeY") {
-                        received_data.eye.right.gaze.y = y;
+                    // Gaze overrides are X/Y projections; the combined ones win.
+                    let combined_x = debug.get("EyeCombinedGazeX").copied();
+                    let combined_y = debug.get("EyeCombinedGazeY").copied();
+                    for (eye, x_key, y_key) in [
+                        (&mut received_data.eye.left, "EyeLeftGazeX", "EyeLeftGazeY"),
+                        (
+                            &mut received_data.eye.right,
+                            "EyeRightGazeX",
+                            "EyeRightGazeY",
+                        ),
+                    ] {
+                        let x = combined_x.or_else(|| debug.get(x_key).copied());
+                        let y = combined_y.or_else(|| debug.get(y_key).copied());
+                        if x.is_some() || y.is_some() {
+                            let mut projection = eye.projection();
+                            projection.x = x.unwrap_or(projection.x);
+                            projection.y = y.unwrap_or(projection.y);
+                            eye.gaze = gaze::from_projection(projection);
+                        }
                     }
 
                     if let Some(&val) = debug.get("EyeCombinedOpenness") {
@@ -407,20 +371,25 @@ fn main() -> Result<()> {
                         received_data.eye.left.pupil_diameter_mm = val;
                         received_data.eye.right.pupil_diameter_mm = val;
                     }
-                    if let Some(&x) = debug.get("EyeCombinedGazeX") {
-                        received_data.eye.left.gaze.x = x;
-                        received_data.eye.right.gaze.x = x;
-                    }
-                    if let Some(&y) = debug.get("EyeCombinedGazeY") {
-                        received_data.eye.left.gaze.y = y;
-                        received_data.eye.right.gaze.y = y;
-                    }
                 }
             }
 
+            // Prefer the spacing between capture timestamps so jitter between the producer
+            // and this thread does not leak into the filters.
             let now = std::time::Instant::now();
-            let dt = now.duration_since(last_frame_time).as_secs_f32();
+            let wall_dt = now.duration_since(last_frame_time).as_secs_f32();
             last_frame_time = now;
+            let dt = if is_new_frame
+                && last_capture_us != 0
+                && received_data.timestamp_us > last_capture_us
+            {
+                (received_data.timestamp_us - last_capture_us) as f32 / 1_000_000.0
+            } else {
+                wall_dt
+            };
+            if is_new_frame {
+                last_capture_us = received_data.timestamp_us;
+            }
 
             if let Ok(mut req) = calibration_request_for_consumer.write() {
                 if let Some(duration) = *req {
@@ -428,14 +397,28 @@ fn main() -> Result<()> {
                         mutator.get_calibration_state(),
                         CalibrationState::Uncalibrated | CalibrationState::Calibrated
                     ) {
-                        info!("Starting calibration from HTTP request: {}s", duration);
+                        info!("Starting requested calibration: {}s", duration);
                         mutator.start_calibration(duration);
                     }
                     *req = None;
                 }
             }
 
+            if is_new_frame {
+                latency_for_consumer.record_since(
+                    LatencyStage::CaptureToMutate,
+                    received_data.timestamp_us,
+                    timestamp_us_now(),
+                );
+            }
+            let mutate_start = std::time::Instant::now();
             mutator.mutate(&mut received_data, dt);
+            if is_new_frame {
+                latency_for_consumer.record(
+                    LatencyStage::Mutate,
+                    mutate_start.elapsed().as_micros() as u64,
+                );
+            }
 
             let is_calibrating_now = matches!(
                 mutator.get_calibration_state(),
@@ -503,28 +486,36 @@ fn main() -> Result<()> {
 
     info!("Entering Main Loop (Producer)...");
 
-    let mut frame_count: u64 = 0;
+    let mut logged_frames: u64 = 0;
     let mut log_interval: u64 = 1000;
     let mut last_log = std::time::Instant::now();
-    let mut last_frame_time = std::time::Instant::now();
-    let target_frame_duration = config.max_fps.map(|fps| Duration::from_secs_f32(1.0 / fps));
 
     while running.load(Ordering::SeqCst) {
-        let mut any_updated = false;
-
-        let active_plugin = &config.module.active;
-        let mut active_module_found = false;
-
-        for module_wrapper in &mut modules {
-            if module_wrapper.name == *active_plugin {
-                active_module_found = true;
-                if module_wrapper.module.update(&mut data).is_ok() {
-                    any_updated = true;
-                }
-            }
+        module_manager.apply_pending();
+        module_manager.check_for_changes();
+        module_manager.supervise();
+
+        let pending_settings = settings_store
+            .write()
+            .map(|mut store| store.take_pending())
+            .unwrap_or_default();
+        for (name, settings) in pending_settings {
+            module_manager.apply_settings(&name, &settings);
         }
 
-        if !active_module_found && !modules.is_empty() {
+        module_manager.watch();
+        module_manager.fail_over();
+
+        // Only the configured modules are expected; one deactivated on purpose stays quiet.
+        let active_plugin = config.module.active.join(", ");
+        if module_manager.active_name().is_none()
+            && !config
+                .module
+                .active
+                .iter()
+                .any(|m| module_manager.is_loaded(m))
+            && !module_manager.modules().is_empty()
+        {
             use std::cell::Cell;
             thread_local! {
                 static LAST_PLUGIN_WARN: Cell<Option<std::time::Instant>> = const { Cell::new(None) };
@@ -545,38 +536,28 @@ fn main() -> Result<()> {
             }
         }
 
-        if any_updated {
-            let _ = tx.try_send(data.clone());
-
-            frame_count += 1;
-            if frame_count.is_multiple_of(log_interval) {
-                let elapsed = last_log.elapsed().as_secs_f32();
-                let fps = log_interval as f32 / elapsed;
-                info!(
-                    "Tracking Active: Processed {} frames (approx {:.1} FPS)",
-                    frame_count, fps
-                );
-                last_log = std::time::Instant::now();
-
-                if frame_count >= 1_000_000 {
-                    log_interval = 1_000_000;
-                } else if frame_count >= 100_000 {
-                    log_interval = 100_000;
-                } else if frame_count >= 10_000 {
-                    log_interval = 10_000;
-                }
-            }
-
-            if let Some(target_duration) = target_frame_duration {
-                let elapsed = last_frame_time.elapsed();
-                if elapsed < target_duration {
-                    thread::sleep(target_duration - elapsed);
-                }
+        // Streamed frames are counted by the emitter as well.
+        let frame_count = emitter.frames();
+        if frame_count >= logged_frames + log_interval {
+            let elapsed = last_log.elapsed().as_secs_f32();
+            let fps = (frame_count - logged_frames) as f32 / elapsed;
+            info!(
+                "Tracking Active: Processed {} frames (approx {:.1} FPS)",
+                frame_count, fps
+            );
+            last_log = std::time::Instant::now();
+            logged_frames = frame_count;
+
+            if frame_count >= 1_000_000 {
+                log_interval = 1_000_000;
+            } else if frame_count >= 100_000 {
+                log_interval = 100_000;
+            } else if frame_count >= 10_000 {
+                log_interval = 10_000;
             }
-            last_frame_time = std::time::Instant::now();
-        } else {
-            thread::sleep(Duration::from_millis(5));
         }
+
+        thread::sleep(WATCH_INTERVAL);
     }
 
     info!("Shutting down...");
@@ -602,8 +583,6 @@ fn main() -> Result<()> {
         }
     }
 
-    for module_wrapper in &mut modules {
-        module_wrapper.module.unload();
-    }
+    module_manager.unload_all();
     Ok(())
 }
@@ -27,18 +27,22 @@ pub fn create_legacy_eye_parameters() -> Vec<Box<dyn Parameter>> {
 
     // XY Eye Params (split into X/Y since OSC doesn't support Vector2)
     params.push(Box::new(FloatParam::new("EyesX", |d| {
-        (d.eye.left.gaze.x + d.eye.right.gaze.x) / 2.0
+        (d.eye.left.projection().x + d.eye.right.projection().x) / 2.0
     })));
     params.push(Box::new(FloatParam::new("EyesY", |d| {
-        (d.eye.left.gaze.y + d.eye.right.gaze.y) / 2.0
+        (d.eye.left.projection().y + d.eye.right.projection().y) / 2.0
+    })));
+    params.push(Box::new(FloatParam::new("LeftEyeX", |d| {
+        d.eye.left.projection().x
+    })));
+    params.push(Box::new(FloatParam::new("LeftEyeY", |d| {
+        d.eye.left.projection().y
     })));
-    params.push(Box::new(FloatParam::new("LeftEyeX", |d| d.eye.left.gaze.x)));
-    params.push(Box::new(FloatParam::new("LeftEyeY", |d| d.eye.left.gaze.y)));
     params.push(Box::new(FloatParam::new("RightEyeX", |d| {
-        d.eye.right.gaze.x
+        d.eye.right.projection().x
     })));
     params.push(Box::new(FloatParam::new("RightEyeY", |d| {
-        d.eye.right.gaze.y
+        d.eye.right.projection().y
     })));
 
     // Eye Widen
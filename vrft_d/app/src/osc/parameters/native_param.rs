@@ -134,13 +134,15 @@ pub fn create_native_parameters() -> Vec<Box<dyn Parameter>> {
         Box::new(NativeParameter::new_vector4(
             "/tracking/eye/LeftRightPitchYaw",
             |d| {
-                // Convert gaze X/Y to pitch/yaw
-                // Gaze convention: X = yaw (left-right), Y = pitch (up-down)
+                // VRChat expects degrees in Unity's convention, where positive pitch
+                // looks down.
+                let left = d.eye.left.yaw_pitch();
+                let right = d.eye.right.yaw_pitch();
                 [
-                    d.eye.left.gaze.y,  // left pitch (up/down)
-                    d.eye.left.gaze.x,  // left yaw (left/right)
-                    d.eye.right.gaze.y, // right pitch
-                    d.eye.right.gaze.x, // right yaw
+                    -left.y.to_degrees(),  // left pitch
+                    left.x.to_degrees(),   // left yaw
+                    -right.y.to_degrees(), // right pitch
+                    right.x.to_degrees(),  // right yaw
                 ]
             },
             |params| !has_eye_xy_params(params),
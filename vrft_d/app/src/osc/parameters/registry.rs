@@ -5,7 +5,11 @@ use super::legacy_lip::create_legacy_lip_parameters;
 use super::native_param::create_native_parameters;
 use super::unified_expressions::create_unified_expression_params;
 use super::{ParamType, Parameter};
-use common::{UnifiedExpressions, UnifiedTrackingData};
+use crate::strategies::SharedDescriptor;
+use common::{
+    gaze, FacialRegion, ModuleDescriptor, UnifiedExpressions, UnifiedSingleEyeData,
+    UnifiedTrackingData,
+};
 use rosc::OscMessage;
 use std::collections::{HashMap, HashSet};
 
@@ -13,8 +17,20 @@ pub struct ParameterRegistry {
     parameters: Vec<Box<dyn Parameter>>,
 }
 
+/// Runs `known` against the module descriptor, or `guess` while the modules are undescribed.
+fn with_descriptor<T>(
+    descriptor: &SharedDescriptor,
+    known: impl FnOnce(&ModuleDescriptor) -> T,
+    guess: impl FnOnce() -> T,
+) -> T {
+    match descriptor.read().ok().as_deref() {
+        Some(Some(descriptor)) => known(descriptor),
+        _ => guess(),
+    }
+}
+
 impl ParameterRegistry {
-    pub fn new() -> Self {
+    pub fn new(module_descriptor: SharedDescriptor) -> Self {
         let mut parameters: Vec<Box<dyn Parameter>> = Vec::new();
 
         // Helper to get shape weight
@@ -44,22 +60,22 @@ impl ParameterRegistry {
 
         // Eye Gaze
         parameters.push(Box::new(EParam::simple("v2/EyeLeftX", |d| {
-            d.eye.left.gaze.x
+            d.eye.left.projection().x
         })));
         parameters.push(Box::new(EParam::simple("v2/EyeLeftY", |d| {
-            d.eye.left.gaze.y
+            d.eye.left.projection().y
         })));
         parameters.push(Box::new(EParam::simple("v2/EyeRightX", |d| {
-            d.eye.right.gaze.x
+            d.eye.right.projection().x
         })));
         parameters.push(Box::new(EParam::simple("v2/EyeRightY", |d| {
-            d.eye.right.gaze.y
+            d.eye.right.projection().y
         })));
         parameters.push(Box::new(EParam::simple("v2/EyeX", |d| {
-            (d.eye.left.gaze.x + d.eye.right.gaze.x) / 2.0
+            (d.eye.left.projection().x + d.eye.right.projection().x) / 2.0
         })));
         parameters.push(Box::new(EParam::simple("v2/EyeY", |d| {
-            (d.eye.left.gaze.y + d.eye.right.gaze.y) / 2.0
+            (d.eye.left.projection().y + d.eye.right.projection().y) / 2.0
         })));
 
         // Eye Pupils
@@ -467,17 +483,12 @@ impl ParameterRegistry {
 
         // All Base Expressions (v2/{ExpressionName})
         // Generate EParam for each UnifiedExpression
-        for i in 0..UnifiedExpressions::Max as usize {
-            if let Ok(expr) = UnifiedExpressions::try_from(i) {
-                if expr != UnifiedExpressions::Max {
-                    let name = format!("v2/{:?}", expr);
-                    // Capture i for the closure
-                    let idx = i;
-                    parameters.push(Box::new(EParam::expression(&name, move |d| {
-                        d.shapes[idx].weight
-                    })));
-                }
-            }
+        for expr in UnifiedExpressions::iter() {
+            let name = format!("v2/{}", expr);
+            let idx = expr.index();
+            parameters.push(Box::new(EParam::expression(&name, move |d| {
+                d.shapes[idx].weight
+            })));
         }
 
         // v2/ Simple Expressions
@@ -529,35 +540,55 @@ impl ParameterRegistry {
         // These are sent on avatar load to communicate tracking state
         use super::base_param::BoolParam;
 
-        // Eye tracking active: true if we have valid gaze data
-        // Check if gaze values are non-zero or pupil has valid diameter
+        // Eye tracking active: true if a module tracks the eyes and reports a valid eye.
+        // Undescribed modules are judged by whether the gaze has left the default forward
+        // direction or the pupil has a valid diameter.
+        let descriptor = module_descriptor.clone();
         parameters.push(Box::new(BoolParam::new_with_send_on_load(
             "FT/EyeTrackingActive",
-            |d| {
-                // Consider eye tracking active if we have any non-default gaze or pupil data
-                d.eye.left.gaze.x != 0.0
-                    || d.eye.left.gaze.y != 0.0
-                    || d.eye.right.gaze.x != 0.0
-                    || d.eye.right.gaze.y != 0.0
-                    || d.eye.left.pupil_diameter_mm > 0.1
-                    || d.eye.right.pupil_diameter_mm > 0.1
+            move |d| {
+                let valid = d.eye.left.status.valid || d.eye.right.status.valid;
+                with_descriptor(
+                    &descriptor,
+                    |m| m.capabilities.eye() && valid,
+                    || {
+                        let eye_active = |eye: &UnifiedSingleEyeData| {
+                            eye.status.valid
+                                && (eye.gaze != gaze::FORWARD || eye.pupil_diameter_mm > 0.1)
+                        };
+                        eye_active(&d.eye.left) || eye_active(&d.eye.right)
+                    },
+                )
             },
         )));
 
-        // Expression tracking active: true if any expression weights are active
+        // Expression tracking active: true if a module drives expressions and the face is
+        // valid. Undescribed modules are judged by whether any weight is active.
+        let descriptor = module_descriptor.clone();
         parameters.push(Box::new(BoolParam::new_with_send_on_load(
             "FT/ExpressionTrackingActive",
-            |d| {
-                // Check if any expression weight is above threshold
-                d.shapes.iter().any(|s| s.weight > 0.01)
+            move |d| {
+                d.face.valid
+                    && with_descriptor(
+                        &descriptor,
+                        |m| m.capabilities.expressions,
+                        || d.shapes.iter().any(|s| s.weight > 0.01),
+                    )
             },
         )));
 
-        // Lip tracking active: based on mouth-related expression activity
+        // Lip tracking active: true if a module drives the lower face and the face is valid.
+        // Undescribed modules are judged by mouth/jaw expression activity.
+        let descriptor = module_descriptor;
         parameters.push(Box::new(BoolParam::new_with_send_on_load(
             "FT/LipTrackingActive",
-            |d| {
-                // Check mouth/jaw expressions specifically
+            move |d| {
+                let lower_face = [
+                    FacialRegion::Jaw,
+                    FacialRegion::Lip,
+                    FacialRegion::Mouth,
+                    FacialRegion::Tongue,
+                ];
                 let mouth_exprs = [
                     UnifiedExpressions::JawOpen,
                     UnifiedExpressions::MouthClosed,
@@ -567,7 +598,12 @@ impl ParameterRegistry {
                     UnifiedExpressions::MouthFrownRight,
                     UnifiedExpressions::TongueOut,
                 ];
-                mouth_exprs.iter().any(|e| w(d, *e) > 0.01)
+                d.face.valid
+                    && with_descriptor(
+                        &descriptor,
+                        |m| lower_face.iter().any(|&region| m.drives_region(region)),
+                        || mouth_exprs.iter().any(|e| w(d, *e) > 0.01),
+                    )
             },
         )));
 
@@ -667,6 +703,6 @@ impl ParameterRegistry {
 
 impl Default for ParameterRegistry {
     fn default() -> Self {
-        Self::new()
+        Self::new(SharedDescriptor::default())
     }
 }
@@ -4,6 +4,9 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+/// Calibration length when none is requested explicitly.
+pub const DEFAULT_CALIBRATION_SECONDS: f32 = 30.0;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct CalibrationStatus {
     pub is_calibrating: bool,
@@ -97,7 +100,7 @@ async fn start_calibration_handler(
     let duration = payload
         .as_ref()
         .and_then(|p| p.duration)
-        .unwrap_or(30.0)
+        .unwrap_or(DEFAULT_CALIBRATION_SECONDS)
         .max(1.0);
 
     // Signal the consumer thread to start calibration
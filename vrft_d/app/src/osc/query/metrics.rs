@@ -0,0 +1,96 @@
+use crate::plugins::host::SharedModuleStatus;
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use common::PipelineLatency;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+#[derive(Clone)]
+struct MetricsState {
+    latency: Arc<PipelineLatency>,
+    module_status: SharedModuleStatus,
+}
+
+pub fn get_router(latency: Arc<PipelineLatency>, module_status: SharedModuleStatus) -> Router {
+    let state = MetricsState {
+        latency,
+        module_status,
+    };
+
+    Router::new()
+        .route("/metrics/latency", get(latency_handler))
+        .route(
+            "/metrics/latency/reset",
+            axum::routing::post(reset_latency_handler),
+        )
+        .route("/metrics/modules", get(module_metrics_handler))
+        .route(
+            "/metrics/modules/reset",
+            axum::routing::post(reset_module_metrics_handler),
+        )
+        .route(
+            "/metrics/modules/{name}",
+            get(single_module_metrics_handler),
+        )
+        .with_state(state)
+}
+
+async fn latency_handler(State(state): State<MetricsState>) -> Json<Value> {
+    let mut stages = Map::new();
+    for (stage, snapshot) in state.latency.snapshot() {
+        stages.insert(stage.name().to_string(), json!(snapshot));
+    }
+    Json(json!({
+        "status": "ok",
+        "unit": "us",
+        "latency": stages
+    }))
+}
+
+async fn reset_latency_handler(State(state): State<MetricsState>) -> Json<Value> {
+    state.latency.reset();
+    Json(json!({ "status": "ok" }))
+}
+
+async fn module_metrics_handler(State(state): State<MetricsState>) -> Json<Value> {
+    let status = state.module_status.read().unwrap();
+    let modules: Map<String, Value> = status
+        .iter()
+        .map(|(name, status)| (name.clone(), json!(status.metrics)))
+        .collect();
+    Json(json!({
+        "status": "ok",
+        "modules": modules
+    }))
+}
+
+async fn single_module_metrics_handler(
+    State(state): State<MetricsState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    let status = state.module_status.read().unwrap();
+    match status.get(&name) {
+        Some(status) => Json(json!({
+            "status": "ok",
+            "module": name,
+            "connection": status.connection,
+            "tracking": status.tracking,
+            "metrics": status.metrics
+        })),
+        None => Json(json!({
+            "status": "error",
+            "module": name,
+            "message": "Unknown module"
+        })),
+    }
+}
+
+async fn reset_module_metrics_handler(State(state): State<MetricsState>) -> Json<Value> {
+    for status in state.module_status.write().unwrap().values_mut() {
+        status.metrics.reset();
+    }
+    Json(json!({ "status": "ok" }))
+}
@@ -1,4 +1,6 @@
 pub mod extensions;
 pub mod host;
+pub mod metrics;
+pub mod modules;
 pub mod service;
 pub mod vrchat;
@@ -0,0 +1,179 @@
+use crate::plugins::emitter::FrameEmitter;
+use crate::plugins::host::SharedModuleStatus;
+use crate::plugins::manager::{ModuleCommand, SharedModuleControl};
+use crate::plugins::settings::ModuleSettingsStore;
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use common::ModuleSettings;
+use serde_json::{json, Value};
+use std::sync::{Arc, RwLock};
+
+#[derive(Clone)]
+struct ModulesState {
+    settings: Arc<RwLock<ModuleSettingsStore>>,
+    status: SharedModuleStatus,
+    control: SharedModuleControl,
+    emitter: Arc<FrameEmitter>,
+}
+
+pub fn get_router(
+    settings: Arc<RwLock<ModuleSettingsStore>>,
+    status: SharedModuleStatus,
+    control: SharedModuleControl,
+    emitter: Arc<FrameEmitter>,
+) -> Router {
+    let state = ModulesState {
+        settings,
+        status,
+        control,
+        emitter,
+    };
+
+    Router::new()
+        .route("/modules", get(list_handler))
+        .route("/modules/{name}/activate", post(activate_handler))
+        .route("/modules/{name}/deactivate", post(deactivate_handler))
+        .route("/modules/{name}/unload", post(unload_handler))
+        .route("/modules/{name}/reload", post(reload_handler))
+        .route("/modules/status", get(status_handler))
+        .route("/modules/faults", get(faults_handler))
+        .route("/modules/sources", get(sources_handler))
+        .route("/modules/settings", get(all_settings_handler))
+        .route(
+            "/modules/settings/{name}",
+            get(module_settings_handler).post(update_settings_handler),
+        )
+        .with_state(state)
+}
+
+async fn list_handler(State(state): State<ModulesState>) -> Json<Value> {
+    let control = state.control.read().unwrap();
+    Json(json!({
+        "status": "ok",
+        "active": control.active(),
+        "modules": control.listing()
+    }))
+}
+
+async fn activate_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    request(&state, ModuleCommand::Activate(name))
+}
+
+async fn deactivate_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    request(&state, ModuleCommand::Deactivate(name))
+}
+
+async fn unload_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    request(&state, ModuleCommand::Unload(name))
+}
+
+async fn reload_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    request(&state, ModuleCommand::Reload(name))
+}
+
+/// Queues `command` for the producer loop, which applies it before the next frame.
+fn request(state: &ModulesState, command: ModuleCommand) -> Json<Value> {
+    let name = command.module().to_string();
+    let mut control = state.control.write().unwrap();
+    match control.request(command) {
+        Ok(()) => Json(json!({
+            "status": "queued",
+            "module": name
+        })),
+        Err(e) => Json(json!({
+            "status": "error",
+            "module": name,
+            "message": format!("{:#}", e)
+        })),
+    }
+}
+
+async fn status_handler(State(state): State<ModulesState>) -> Json<Value> {
+    let status = state.status.read().unwrap();
+    Json(json!({
+        "status": "ok",
+        "modules": *status
+    }))
+}
+
+async fn faults_handler(State(state): State<ModulesState>) -> Json<Value> {
+    let status = state.status.read().unwrap();
+    let faults: serde_json::Map<String, Value> = status
+        .iter()
+        .filter(|(_, status)| status.faults.total > 0)
+        .map(|(name, status)| (name.clone(), json!(status.faults)))
+        .collect();
+    Json(json!({
+        "status": "ok",
+        "modules": faults
+    }))
+}
+
+async fn sources_handler(State(state): State<ModulesState>) -> Json<Value> {
+    Json(json!({
+        "status": "ok",
+        "active": state.control.read().unwrap().active(),
+        "channels": state.emitter.sources()
+    }))
+}
+
+async fn all_settings_handler(State(state): State<ModulesState>) -> Json<Value> {
+    let store = state.settings.read().unwrap();
+    Json(json!({
+        "status": "ok",
+        "settings": store.all(),
+        "schemas": store.schemas()
+    }))
+}
+
+async fn module_settings_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+) -> Json<Value> {
+    let store = state.settings.read().unwrap();
+    Json(json!({
+        "status": "ok",
+        "module": name,
+        "settings": store.get(&name),
+        "schema": store.schema(&name)
+    }))
+}
+
+async fn update_settings_handler(
+    State(state): State<ModulesState>,
+    Path(name): Path<String>,
+    Json(payload): Json<Value>,
+) -> Json<Value> {
+    let mut store = state.settings.write().unwrap();
+    match store.update(&name, ModuleSettings::new(payload)) {
+        Ok(saved) => {
+            log::info!("Updated settings for module {}", name);
+            Json(json!({
+                "status": "ok",
+                "module": name,
+                "settings": store.get(&name),
+                "saved": saved
+            }))
+        }
+        Err(e) => Json(json!({
+            "status": "error",
+            "module": name,
+            "message": format!("{:#}", e)
+        })),
+    }
+}
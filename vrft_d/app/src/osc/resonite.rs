@@ -39,22 +39,32 @@ impl ResoniteOsc {
             };
         }
 
-        add_msg!("/avatar/parameters/LeftEyeX", data.eye.left.gaze.x);
-        add_msg!("/avatar/parameters/LeftEyeY", data.eye.left.gaze.y);
-        add_msg!("/avatar/parameters/RightEyeX", data.eye.right.gaze.x);
-        add_msg!("/avatar/parameters/RightEyeY", data.eye.right.gaze.y);
-
-        add_msg!(
-            "/avatar/parameters/LeftEyeLid",
-            1.0 - data.eye.left.openness
-        );
-        add_msg!(
-            "/avatar/parameters/RightEyeLid",
-            1.0 - data.eye.right.openness
-        );
+        // Lost eyes are left out so Resonite keeps the last good pose.
+        if data.eye.left.status.valid {
+            let left_gaze = data.eye.left.projection();
+            add_msg!("/avatar/parameters/LeftEyeX", left_gaze.x);
+            add_msg!("/avatar/parameters/LeftEyeY", left_gaze.y);
+            add_msg!(
+                "/avatar/parameters/LeftEyeLid",
+                1.0 - data.eye.left.openness
+            );
+            add_msg!("/sl/xrfb/facew/EyesClosedL", 1.0 - data.eye.left.openness);
+        }
+        if data.eye.right.status.valid {
+            let right_gaze = data.eye.right.projection();
+            add_msg!("/avatar/parameters/RightEyeX", right_gaze.x);
+            add_msg!("/avatar/parameters/RightEyeY", right_gaze.y);
+            add_msg!(
+                "/avatar/parameters/RightEyeLid",
+                1.0 - data.eye.right.openness
+            );
+            add_msg!("/sl/xrfb/facew/EyesClosedR", 1.0 - data.eye.right.openness);
+        }
 
-        add_msg!("/sl/xrfb/facew/EyesClosedL", 1.0 - data.eye.left.openness);
-        add_msg!("/sl/xrfb/facew/EyesClosedR", 1.0 - data.eye.right.openness);
+        // Same for the face: skip the shapes entirely while it is lost.
+        if !data.face.valid {
+            return self.send_messages(socket, messages);
+        }
 
         let w = |expr: UnifiedExpressions| data.shapes[expr as usize].weight;
 
@@ -289,6 +299,10 @@ impl ResoniteOsc {
             w(UnifiedExpressions::TongueDown)
         );
 
+        self.send_messages(socket, messages)
+    }
+
+    fn send_messages(&self, socket: &UdpSocket, messages: Vec<OscMessage>) -> Result<()> {
         if messages.is_empty() {
             return Ok(());
         }
@@ -1,6 +1,7 @@
 use crate::osc::parameters::registry::ParameterRegistry;
 use crate::osc::parameters::ParamType;
 use crate::osc::query::service::{OscParamType, OscParameterInfo, OscQueryService};
+use crate::strategies::SharedDescriptor;
 use anyhow::Result;
 use common::UnifiedTrackingData;
 use log::{error, info};
@@ -27,7 +28,7 @@ pub struct VRChatOsc {
 }
 
 impl VRChatOsc {
-    pub fn new(target_addr: &str, receive_port: u16) -> Self {
+    pub fn new(target_addr: &str, receive_port: u16, module_descriptor: SharedDescriptor) -> Self {
         let (query_tx, query_rx) = channel();
         let (change_tx_calibration, change_rx_calibration) = channel();
         let (change_tx_query, change_rx_query) = channel();
@@ -43,7 +44,7 @@ impl VRChatOsc {
             change_tx_calibration,
             change_tx_query,
             change_rx: Mutex::new(Some(change_rx_calibration)),
-            param_registry: Mutex::new(ParameterRegistry::new()),
+            param_registry: Mutex::new(ParameterRegistry::new(module_descriptor)),
             shutdown_flag: Arc::new(AtomicBool::new(false)),
         }
     }
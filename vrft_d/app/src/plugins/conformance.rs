@@ -0,0 +1,764 @@
+//! Conformance suite: checks a module against the host contract, for `vrft_conformance`.
+//!
+//! The suite runs the checks in [`CHECKS`] in order and reports each as it finishes, so a
+//! runner that watches from another process can tell which check a crash happened in.
+
+use anyhow::Result;
+use api::{
+    ConnectionState, FrameSink, Host, HostServices, ModuleDescriptor, ModuleSettings,
+    TrackingModule, TrackingState, UnifiedTrackingData,
+};
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::plugins::faults::contain;
+use crate::plugins::host::module_logger;
+
+/// The checks, in the order they run.
+pub const CHECKS: [&str; 8] = [
+    "load",
+    "descriptor",
+    "configure",
+    "initialize",
+    "update_latency",
+    "output",
+    "panic_containment",
+    "unload_reload",
+];
+
+/// Problems listed per check; the rest are only counted.
+const MAX_LISTED: usize = 10;
+
+/// Resident memory a load cycle may leave behind before it counts as a leak.
+const LEAK_PER_CYCLE_KB: u64 = 1024;
+
+/// How far a frame timestamp may be from the host clock.
+const CLOCK_TOLERANCE: Duration = Duration::from_secs(5);
+
+/// A loaded module instance. The library is dropped after the module.
+pub struct Instance {
+    pub module: Box<dyn TrackingModule + Send>,
+    pub library: Option<Library>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SuiteOptions {
+    /// Settings handed to `configure`, checked against the module's schema first.
+    pub settings: Value,
+    /// Polls for the latency and output checks.
+    pub updates: u32,
+    /// The longest an `update` call may take.
+    pub budget: Duration,
+    /// How long to collect frames from a streaming module.
+    pub stream_for: Duration,
+    /// Load cycles for the leak check; 0 skips it.
+    pub cycles: u32,
+}
+
+impl Default for SuiteOptions {
+    fn default() -> Self {
+        Self {
+            settings: json!({}),
+            updates: 500,
+            // The default `module.update_deadline_ms`.
+            budget: Duration::from_millis(250),
+            stream_for: Duration::from_secs(2),
+            cycles: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// Allowed by the contract, but worth a look.
+    Warn,
+    Fail,
+    /// Not run, e.g. because an earlier check failed.
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Measurements backing the result, e.g. latency percentiles.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+}
+
+impl CheckResult {
+    pub fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            data: Value::Null,
+        }
+    }
+
+    fn with_data(mut self, data: Value) -> Self {
+        self.data = data;
+        self
+    }
+}
+
+/// The outcome of a suite run, as `vrft_conformance` writes it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    pub module: String,
+    pub abi_version: u32,
+    pub platform: String,
+    /// Set when no check failed.
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+impl ConformanceReport {
+    pub fn new(module: &str, checks: Vec<CheckResult>) -> Self {
+        Self {
+            module: module.to_string(),
+            abi_version: api::abi::VRFT_ABI_VERSION,
+            platform: std::env::consts::OS.to_string(),
+            passed: checks.iter().all(|c| c.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+
+    pub fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+}
+
+/// Host services that only log; the suite checks what the module does, not what it reports.
+struct SilentHost;
+
+impl Host for SilentHost {
+    fn connection_changed(&self, _state: ConnectionState) {}
+
+    fn request_recalibration(&self) {}
+
+    fn set_gauge(&self, _name: &str, _value: f64) {}
+
+    fn add_counter(&self, _name: &str, _delta: u64) {}
+}
+
+/// A module instance being driven by the suite.
+struct Running {
+    instance: Instance,
+    frames: Option<Receiver<UnifiedTrackingData>>,
+    initialized: bool,
+}
+
+impl Running {
+    fn unload(mut self) -> Result<(), String> {
+        let result = if self.initialized {
+            contain(|| self.instance.module.unload())
+        } else {
+            Ok(())
+        };
+        contain(move || drop(self.instance)).and(result)
+    }
+}
+
+/// Runs the checks against modules created by `create`, calling `report` after each one.
+pub struct Suite<F> {
+    name: String,
+    create: F,
+    options: SuiteOptions,
+    state_dir: PathBuf,
+    /// Contained panics so far, as `"<call>: <message>"`.
+    panics: Vec<String>,
+}
+
+impl<F: FnMut() -> Result<Instance>> Suite<F> {
+    pub fn new(name: &str, create: F, options: SuiteOptions) -> Self {
+        let state_dir =
+            std::env::temp_dir().join(format!("vrft_conformance_{}_{}", std::process::id(), name));
+        Self {
+            name: name.to_string(),
+            create,
+            options,
+            state_dir,
+            panics: Vec::new(),
+        }
+    }
+
+    pub fn run(mut self, mut report: impl FnMut(&CheckResult)) -> ConformanceReport {
+        let mut results = Vec::new();
+        let mut finish = |result: CheckResult| {
+            report(&result);
+            results.push(result);
+        };
+
+        let mut running = match self.load() {
+            Ok(running) => {
+                finish(CheckResult::new("load", CheckStatus::Pass, "Module loaded"));
+                running
+            }
+            Err(e) => {
+                finish(CheckResult::new("load", CheckStatus::Fail, e));
+                for name in &CHECKS[1..] {
+                    finish(CheckResult::new(
+                        name,
+                        CheckStatus::Skip,
+                        "Module did not load",
+                    ));
+                }
+                return ConformanceReport::new(&self.name, results);
+            }
+        };
+
+        let descriptor = running.instance.module.descriptor();
+        finish(check_descriptor(descriptor.as_ref()));
+        let configured = self.check_configure(&mut running, descriptor.as_ref());
+        let configure_failed = configured.status == CheckStatus::Fail;
+        finish(configured);
+
+        let initialized = if configure_failed {
+            CheckResult::new("initialize", CheckStatus::Skip, "Module was not configured")
+        } else {
+            self.check_initialize(&mut running)
+        };
+        let initialize_failed = initialized.status != CheckStatus::Pass;
+        finish(initialized);
+
+        if initialize_failed {
+            finish(CheckResult::new(
+                "update_latency",
+                CheckStatus::Skip,
+                "Module was not initialized",
+            ));
+            finish(CheckResult::new(
+                "output",
+                CheckStatus::Skip,
+                "Module was not initialized",
+            ));
+        } else {
+            let (latency, frames) = self.check_latency(&mut running);
+            finish(latency);
+            finish(check_output(&frames));
+        }
+
+        finish(self.check_panics(running, descriptor.as_ref()));
+        finish(if configure_failed || initialize_failed {
+            CheckResult::new("unload_reload", CheckStatus::Skip, "Module did not start")
+        } else {
+            self.check_reload()
+        });
+
+        let _ = std::fs::remove_dir_all(&self.state_dir);
+        ConformanceReport::new(&self.name, results)
+    }
+
+    fn load(&mut self) -> Result<Running, String> {
+        match contain(&mut self.create) {
+            Ok(Ok(instance)) => Ok(Running {
+                instance,
+                frames: None,
+                initialized: false,
+            }),
+            Ok(Err(e)) => Err(format!("{:#}", e)),
+            Err(message) => Err(self.panicked("create", message)),
+        }
+    }
+
+    fn panicked(&mut self, call: &str, message: String) -> String {
+        let panic = format!("{}: {}", call, message);
+        self.panics.push(panic.clone());
+        format!("Panicked in {}", panic)
+    }
+
+    fn configure(&mut self, running: &mut Running, settings: &Value) -> Result<(), String> {
+        let settings = ModuleSettings::new(settings.clone());
+        match contain(|| running.instance.module.configure(&settings)) {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(format!("{:#}", e)),
+            Err(message) => Err(self.panicked("configure", message)),
+        }
+    }
+
+    fn initialize(&mut self, running: &mut Running) -> Result<(), String> {
+        let _ = std::fs::create_dir_all(&self.state_dir);
+        let host = HostServices::new(
+            module_logger(&self.name),
+            Some(self.state_dir.clone()),
+            Arc::new(SilentHost),
+        );
+        match contain(|| running.instance.module.initialize(host)) {
+            Ok(Ok(())) => running.initialized = true,
+            Ok(Err(e)) => return Err(format!("{:#}", e)),
+            Err(message) => return Err(self.panicked("initialize", message)),
+        }
+
+        let (tx, rx) = channel();
+        let tx = Mutex::new(tx);
+        let sink = FrameSink::new(move |data| {
+            let _ = tx.lock().unwrap().send(data.clone());
+        });
+        match contain(|| running.instance.module.start_streaming(sink)) {
+            Ok(streaming) => {
+                running.frames = streaming.then_some(rx);
+                Ok(())
+            }
+            Err(message) => Err(self.panicked("start_streaming", message)),
+        }
+    }
+
+    /// Creates, configures and initializes a fresh instance.
+    fn start(&mut self) -> Result<Running, String> {
+        let mut running = self.load()?;
+        let settings = self.options.settings.clone();
+        self.configure(&mut running, &settings)?;
+        self.initialize(&mut running)?;
+        Ok(running)
+    }
+
+    fn check_configure(
+        &mut self,
+        running: &mut Running,
+        descriptor: Option<&ModuleDescriptor>,
+    ) -> CheckResult {
+        let settings = self.options.settings.clone();
+        if let Some(schema) = descriptor.and_then(|d| d.settings_schema.as_ref()) {
+            if let Err(e) = ModuleSettings::new(settings.clone()).validate(schema) {
+                return CheckResult::new(
+                    "configure",
+                    CheckStatus::Fail,
+                    format!("The settings do not match the module's schema: {:#}", e),
+                );
+            }
+        }
+        match self.configure(running, &settings) {
+            Ok(()) => CheckResult::new("configure", CheckStatus::Pass, "Settings accepted"),
+            Err(e) => CheckResult::new("configure", CheckStatus::Fail, e),
+        }
+    }
+
+    fn check_initialize(&mut self, running: &mut Running) -> CheckResult {
+        let started = Instant::now();
+        match self.initialize(running) {
+            Ok(()) => CheckResult::new(
+                "initialize",
+                CheckStatus::Pass,
+                if running.frames.is_some() {
+                    "Initialized; the module streams frames"
+                } else {
+                    "Initialized; the module is polled"
+                },
+            )
+            .with_data(json!({ "ms": millis(started.elapsed()) })),
+            Err(e) => CheckResult::new("initialize", CheckStatus::Fail, e),
+        }
+    }
+
+    /// Polls the module, or collects what it streams, and returns the frames it produced.
+    fn check_latency(&mut self, running: &mut Running) -> (CheckResult, Vec<UnifiedTrackingData>) {
+        if let Some(frames) = &running.frames {
+            let deadline = Instant::now() + self.options.stream_for;
+            let mut collected = Vec::new();
+            while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+                match frames.recv_timeout(left) {
+                    Ok(frame) => collected.push(frame),
+                    Err(_) => break,
+                }
+            }
+            let result = CheckResult::new(
+                "update_latency",
+                CheckStatus::Skip,
+                "The module streams frames, so it is not polled",
+            )
+            .with_data(json!({ "frames": collected.len() }));
+            return (result, collected);
+        }
+
+        let mut data = UnifiedTrackingData::default();
+        let mut frames = Vec::new();
+        let mut times = Vec::new();
+        let mut states = serde_json::Map::new();
+        let mut problem = None;
+        for _ in 0..self.options.updates {
+            data.timestamp_us = 0;
+            let started = Instant::now();
+            let state = match contain(|| running.instance.module.update(&mut data)) {
+                Ok(state) => state,
+                Err(message) => {
+                    problem = Some(self.panicked("update", message));
+                    break;
+                }
+            };
+            times.push(started.elapsed());
+            let count = states.entry(state_name(state)).or_insert(json!(0));
+            *count = json!(count.as_u64().unwrap_or(0) + 1);
+            if state == TrackingState::NewFrame {
+                frames.push(data.clone());
+            }
+            if state == TrackingState::Fatal {
+                problem = Some("update returned fatal".to_string());
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        times.sort();
+        let percentile = |p: usize| {
+            times
+                .get((times.len() * p / 100).min(times.len().saturating_sub(1)))
+                .map_or(0.0, |t| millis(*t))
+        };
+        let over_budget = times.iter().filter(|t| **t > self.options.budget).count();
+        let data = json!({
+            "calls": times.len(),
+            "p50_ms": percentile(50),
+            "p99_ms": percentile(99),
+            "max_ms": times.last().map_or(0.0, |t| millis(*t)),
+            "budget_ms": millis(self.options.budget),
+            "over_budget": over_budget,
+            "states": states,
+        });
+
+        let result = if let Some(problem) = problem {
+            CheckResult::new("update_latency", CheckStatus::Fail, problem)
+        } else if over_budget > 0 {
+            CheckResult::new(
+                "update_latency",
+                CheckStatus::Fail,
+                format!(
+                    "{} of {} calls took longer than {}ms",
+                    over_budget,
+                    times.len(),
+                    millis(self.options.budget)
+                ),
+            )
+        } else {
+            CheckResult::new(
+                "update_latency",
+                CheckStatus::Pass,
+                format!(
+                    "{} calls, none longer than {}ms",
+                    times.len(),
+                    millis(self.options.budget)
+                ),
+            )
+        };
+        (result.with_data(data), frames)
+    }
+
+    /// Feeds settings the host may pass a module without a schema, then unloads the module.
+    fn check_panics(
+        &mut self,
+        mut running: Running,
+        descriptor: Option<&ModuleDescriptor>,
+    ) -> CheckResult {
+        let mut probed = Vec::new();
+        if descriptor.is_some_and(|d| d.settings_schema.is_some()) {
+            probed.push("none; the host validates settings against the schema".to_string());
+        } else {
+            for settings in [Value::Null, json!([]), json!("text"), json!({ "": -1 })] {
+                // Errors are fine here; only panics count.
+                let _ = self.configure(&mut running, &settings);
+                probed.push(settings.to_string());
+            }
+            let settings = self.options.settings.clone();
+            let _ = self.configure(&mut running, &settings);
+        }
+        if let Err(message) = running.unload() {
+            self.panicked("unload", message);
+        }
+
+        let data = json!({ "probed_settings": probed, "panics": self.panics });
+        if self.panics.is_empty() {
+            CheckResult::new("panic_containment", CheckStatus::Pass, "No panics")
+        } else {
+            CheckResult::new(
+                "panic_containment",
+                CheckStatus::Warn,
+                format!(
+                    "{} panic(s) were contained; the host restarts a module after each one",
+                    self.panics.len()
+                ),
+            )
+        }
+        .with_data(data)
+    }
+
+    fn check_reload(&mut self) -> CheckResult {
+        if self.options.cycles == 0 {
+            return CheckResult::new("unload_reload", CheckStatus::Skip, "Disabled");
+        }
+
+        let mut baseline = None;
+        for cycle in 0..self.options.cycles {
+            let mut running = match self.start() {
+                Ok(running) => running,
+                Err(e) => {
+                    return CheckResult::new(
+                        "unload_reload",
+                        CheckStatus::Fail,
+                        format!("Cycle {}: {}", cycle + 1, e),
+                    )
+                }
+            };
+            let mut data = UnifiedTrackingData::default();
+            for _ in 0..10 {
+                if running.frames.is_some() {
+                    break;
+                }
+                if let Err(message) = contain(|| running.instance.module.update(&mut data)) {
+                    let e = self.panicked("update", message);
+                    return CheckResult::new(
+                        "unload_reload",
+                        CheckStatus::Fail,
+                        format!("Cycle {}: {}", cycle + 1, e),
+                    );
+                }
+            }
+            if let Err(message) = running.unload() {
+                let e = self.panicked("unload", message);
+                return CheckResult::new(
+                    "unload_reload",
+                    CheckStatus::Fail,
+                    format!("Cycle {}: {}", cycle + 1, e),
+                );
+            }
+            // Give threads the module stopped in unload time to exit.
+            std::thread::sleep(Duration::from_millis(50));
+            // The first cycle warms up caches and allocator pools.
+            if cycle == 0 {
+                baseline = Usage::measure();
+            }
+        }
+
+        let cycles = self.options.cycles;
+        let (Some(before), Some(after)) = (baseline, Usage::measure()) else {
+            return CheckResult::new(
+                "unload_reload",
+                CheckStatus::Warn,
+                format!(
+                    "{} cycles ran cleanly; leaks are not measured on {}",
+                    cycles,
+                    std::env::consts::OS
+                ),
+            );
+        };
+        let data = json!({ "cycles": cycles, "before": before, "after": after });
+        let mut leaks = Vec::new();
+        if after.threads > before.threads {
+            leaks.push(format!("{} thread(s)", after.threads - before.threads));
+        }
+        if after.open_files > before.open_files {
+            leaks.push(format!(
+                "{} open file(s)",
+                after.open_files - before.open_files
+            ));
+        }
+        let measured = u64::from(cycles.saturating_sub(1).max(1));
+        let grown_kb = after.resident_kb.saturating_sub(before.resident_kb);
+        if grown_kb / measured > LEAK_PER_CYCLE_KB {
+            leaks.push(format!("{}KB of memory", grown_kb));
+        }
+
+        if leaks.is_empty() {
+            CheckResult::new(
+                "unload_reload",
+                CheckStatus::Pass,
+                format!("{} cycles left nothing behind", cycles),
+            )
+        } else {
+            CheckResult::new(
+                "unload_reload",
+                CheckStatus::Fail,
+                format!("{} cycles left {} behind", cycles, leaks.join(", ")),
+            )
+        }
+        .with_data(data)
+    }
+}
+
+fn check_descriptor(descriptor: Option<&ModuleDescriptor>) -> CheckResult {
+    match descriptor {
+        Some(d) => CheckResult::new(
+            "descriptor",
+            CheckStatus::Pass,
+            format!("{} {} by {}", d.name, d.version, d.vendor),
+        )
+        .with_data(serde_json::to_value(d).unwrap_or_default()),
+        None => CheckResult::new(
+            "descriptor",
+            CheckStatus::Warn,
+            "No descriptor; the host guesses the tracked channels from the data",
+        ),
+    }
+}
+
+fn check_output(frames: &[UnifiedTrackingData]) -> CheckResult {
+    if frames.is_empty() {
+        return CheckResult::new(
+            "output",
+            CheckStatus::Warn,
+            "No frames to check; connect the device and run again",
+        );
+    }
+    let mut problems = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        for problem in frame_problems(frame) {
+            problems.push(format!("Frame {}: {}", index + 1, problem));
+        }
+    }
+    let data = json!({
+        "frames": frames.len(),
+        "problems": problems.len(),
+        "listed": problems.iter().take(MAX_LISTED).collect::<Vec<_>>(),
+    });
+    if problems.is_empty() {
+        CheckResult::new(
+            "output",
+            CheckStatus::Pass,
+            format!("{} frames in range", frames.len()),
+        )
+    } else {
+        CheckResult::new(
+            "output",
+            CheckStatus::Fail,
+            format!("{} problem(s), first: {}", problems.len(), problems[0]),
+        )
+    }
+    .with_data(data)
+}
+
+/// Values in `data` that break the frame contract: non-finite numbers, weights and
+/// confidences outside `[0, 1]`, and the like.
+pub fn frame_problems(data: &UnifiedTrackingData) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut unit = |name: &str, value: f32| {
+        if !value.is_finite() {
+            problems.push(format!("{} is {}", name, value));
+        } else if !(0.0..=1.0).contains(&value) {
+            problems.push(format!("{} is {}, outside [0, 1]", name, value));
+        }
+    };
+
+    for (side, eye) in [("left", &data.eye.left), ("right", &data.eye.right)] {
+        unit(&format!("eye.{}.openness", side), eye.openness);
+        unit(
+            &format!("eye.{}.status.confidence", side),
+            eye.status.confidence,
+        );
+    }
+    unit("face.confidence", data.face.confidence);
+    unit("head.status.confidence", data.head.status.confidence);
+    for (index, shape) in data.shapes.iter().enumerate() {
+        unit(&format!("shapes[{}]", index), shape.weight);
+    }
+    for (index, confidence) in data.shape_confidences.iter().enumerate() {
+        unit(&format!("shape_confidences[{}]", index), *confidence);
+    }
+
+    let mut finite = |name: &str, value: f32| {
+        if !value.is_finite() {
+            problems.push(format!("{} is {}", name, value));
+        }
+    };
+    for (side, eye) in [("left", &data.eye.left), ("right", &data.eye.right)] {
+        for (axis, value) in ["x", "y", "z"].iter().zip(eye.gaze.to_array()) {
+            finite(&format!("eye.{}.gaze.{}", side, axis), value);
+        }
+        finite(
+            &format!("eye.{}.pupil_diameter_mm", side),
+            eye.pupil_diameter_mm,
+        );
+    }
+    for (name, value) in [
+        ("eye.max_dilation", data.eye.max_dilation),
+        ("eye.min_dilation", data.eye.min_dilation),
+        ("eye.left_diameter", data.eye.left_diameter),
+        ("eye.right_diameter", data.eye.right_diameter),
+        ("head.head_yaw", data.head.head_yaw),
+        ("head.head_pitch", data.head.head_pitch),
+        ("head.head_roll", data.head.head_roll),
+        ("head.head_pos_x", data.head.head_pos_x),
+        ("head.head_pos_y", data.head.head_pos_y),
+        ("head.head_pos_z", data.head.head_pos_z),
+    ] {
+        finite(name, value);
+    }
+
+    for (side, eye) in [("left", &data.eye.left), ("right", &data.eye.right)] {
+        let length = eye.gaze.length();
+        if eye.status.valid && length.is_finite() && (length - 1.0).abs() > 0.01 {
+            problems.push(format!(
+                "eye.{}.gaze has length {}, not normalized",
+                side, length
+            ));
+        }
+    }
+    if data.shapes.len() != data.shape_confidences.len() {
+        problems.push(format!(
+            "{} shapes but {} shape confidences",
+            data.shapes.len(),
+            data.shape_confidences.len()
+        ));
+    }
+    if data.timestamp_us != 0 {
+        let offset = data.timestamp_us.abs_diff(api::timestamp_us_now());
+        if offset > CLOCK_TOLERANCE.as_micros() as u64 {
+            problems.push(format!(
+                "timestamp_us is {:.1}s off the host clock",
+                offset as f64 / 1e6
+            ));
+        }
+    }
+    problems
+}
+
+fn state_name(state: TrackingState) -> String {
+    serde_json::to_value(state)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Resources held by this process, to spot what load cycles leave behind.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct Usage {
+    threads: u64,
+    open_files: u64,
+    resident_kb: u64,
+}
+
+impl Usage {
+    #[cfg(target_os = "linux")]
+    fn measure() -> Option<Self> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let field = |name: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        };
+        Some(Self {
+            threads: field("Threads:")?,
+            open_files: std::fs::read_dir("/proc/self/fd").ok()?.count() as u64,
+            resident_kb: field("VmRSS:")?,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn measure() -> Option<Self> {
+        None
+    }
+}
@@ -0,0 +1,129 @@
+//! Hands frames from modules to the consumer thread, for polled and streaming modules alike.
+//! With `module.sources`, the frames of several modules are merged first, see [`Fusion`].
+
+use crate::plugins::fusion::{Freshness, Fusion, SourceMap, SourceStatus};
+use api::FrameSink;
+use common::{timestamp_us_now, UnifiedTrackingData};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Stamps frames with a capture time and sequence number and sends them to the consumer.
+pub struct FrameEmitter {
+    tx: SyncSender<UnifiedTrackingData>,
+    sequence: AtomicU64,
+    /// Streamed frames arriving faster than this are dropped (`max_fps`).
+    min_interval: Option<Duration>,
+    next_due: Mutex<Option<Instant>>,
+    last: Mutex<Option<UnifiedTrackingData>>,
+    fusion: Mutex<Fusion>,
+}
+
+impl FrameEmitter {
+    pub fn new(tx: SyncSender<UnifiedTrackingData>, max_fps: Option<f32>) -> Self {
+        Self {
+            tx,
+            sequence: AtomicU64::new(0),
+            min_interval: max_fps
+                .filter(|fps| *fps > 0.0)
+                .map(|fps| Duration::from_secs_f32(1.0 / fps)),
+            next_due: Mutex::new(None),
+            last: Mutex::new(None),
+            fusion: Mutex::new(Fusion::default()),
+        }
+    }
+
+    /// Merges the frames of the modules in `sources` with those of the active module.
+    pub fn set_sources(&self, sources: SourceMap) {
+        self.fusion.lock().unwrap().set_sources(sources);
+    }
+
+    /// Sets the module owning the channels no source is assigned to.
+    pub fn set_active(&self, active: Option<&str>) {
+        self.fusion.lock().unwrap().set_active(active);
+    }
+
+    /// Blends the output towards the next frames over `duration`, e.g. on a failover.
+    pub fn crossfade(&self, duration: Duration) {
+        self.fusion.lock().unwrap().crossfade(duration);
+    }
+
+    /// How fresh the frames of `module` are, whether or not they go out.
+    pub fn freshness(&self, module: &str) -> Option<Freshness> {
+        self.fusion.lock().unwrap().freshness(module)
+    }
+
+    /// Owner and freshness of every channel; empty without sources.
+    pub fn sources(&self) -> Vec<SourceStatus> {
+        self.fusion.lock().unwrap().status()
+    }
+
+    /// Number of frames emitted so far.
+    pub fn frames(&self) -> u64 {
+        self.sequence.load(Ordering::Relaxed)
+    }
+
+    /// The shortest spacing between frames (`max_fps`), if limited.
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.min_interval
+    }
+
+    /// The last frame emitted, e.g. to keep feeding it while the active module is stuck.
+    pub fn last_frame(&self) -> Option<UnifiedTrackingData> {
+        self.last.lock().unwrap().clone()
+    }
+
+    /// Stamps `data` from module `source` and sends it, or the output it merges into, to the
+    /// consumer. Frames are dropped, not queued, while the consumer is busy.
+    pub fn emit(&self, source: &str, data: &mut UnifiedTrackingData) {
+        self.submit(source, data, false);
+    }
+
+    /// A sink for streaming module `source`. Unlike polled modules, which their poll thread
+    /// paces, streamed frames are rate limited here.
+    pub fn sink(self: &Arc<Self>, source: &str) -> FrameSink {
+        let emitter = self.clone();
+        let source = source.to_string();
+        FrameSink::new(move |data| {
+            emitter.submit(&source, &mut data.clone(), true);
+        })
+    }
+
+    fn submit(&self, source: &str, data: &mut UnifiedTrackingData, streamed: bool) {
+        if data.timestamp_us == 0 {
+            data.timestamp_us = timestamp_us_now();
+        }
+        let (merged, fused) = {
+            let mut fusion = self.fusion.lock().unwrap();
+            (fusion.merge(source, data), fusion.is_fused())
+        };
+        let Some(mut output) = merged else {
+            return;
+        };
+        // Merged modules add up, so their output is rate limited like a stream.
+        if (streamed || fused) && !self.is_due() {
+            return;
+        }
+        output.sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        *self.last.lock().unwrap() = Some(output.clone());
+        let _ = self.tx.try_send(output);
+    }
+
+    /// Whether a frame may go out now under `max_fps`.
+    fn is_due(&self) -> bool {
+        let Some(min_interval) = self.min_interval else {
+            return true;
+        };
+        let mut next_due = self.next_due.lock().unwrap();
+        let now = Instant::now();
+        let due = next_due.unwrap_or(now);
+        if now < due {
+            return false;
+        }
+        // Advance from the due time rather than from now, so a device running slightly
+        // faster than `max_fps` is not halved; resync after falling behind.
+        *next_due = Some((due + min_interval).max(now));
+        true
+    }
+}
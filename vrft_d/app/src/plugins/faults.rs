@@ -0,0 +1,94 @@
+//! Panic containment for module calls.
+//!
+//! Every call into a module goes through [`contain`]. A panic marks the module faulted: the
+//! manager records it in the module's [`FaultHistory`], unloads the module and recreates it
+//! after a [`RestartPolicy`] delay, until the retry budget is spent.
+
+use serde::Serialize;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+/// Faults kept per module; older ones are dropped.
+pub const MAX_RECENT_FAULTS: usize = 20;
+
+/// A panic caught in a module call.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModuleFault {
+    /// When it happened, in microseconds since the UNIX epoch.
+    pub timestamp_us: u64,
+    /// The module call that panicked, e.g. `"update"`.
+    pub call: String,
+    pub message: String,
+}
+
+/// Faults and restarts of one module since the daemon started.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FaultHistory {
+    /// The most recent faults, oldest first.
+    pub recent: VecDeque<ModuleFault>,
+    pub total: u64,
+    /// Restarts after a fault, successful or not.
+    pub restarts: u64,
+    /// Set when the retry budget ran out; the module stays unloaded until it is activated or
+    /// reloaded through the HTTP API.
+    pub gave_up: bool,
+}
+
+impl FaultHistory {
+    pub fn record(&mut self, fault: ModuleFault) {
+        if self.recent.len() == MAX_RECENT_FAULTS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(fault);
+        self.total += 1;
+    }
+}
+
+/// Runs a module call, turning a panic into an error message.
+pub fn contain<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(panic_message)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// When to recreate a faulted module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Delay before the first restart; doubled for each further attempt.
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Restarts in a row before giving up.
+    pub max_attempts: u32,
+    /// A module that runs this long after a restart gets its full budget back.
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 5,
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restart number `attempt`, counting from 0.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_delay)
+    }
+}
@@ -0,0 +1,432 @@
+//! Merges the frames of several modules into one, so e.g. an eye tracker and a lip tracker
+//! can run side by side.
+//!
+//! Each [`Channel`] of the output is owned by one module (`module.sources`); the active
+//! module owns every channel not assigned elsewhere. A frame from a module only updates the
+//! channels it owns, and the merged frame keeps the latest values of the others. Channels
+//! whose owner has not delivered a frame for [`STALE_AFTER`] are marked lost in the output.
+//!
+//! Without sources only the active module's frames go out; modules standing by in a failover
+//! chain are only tracked for their [`Freshness`].
+
+use api::{gaze, ChannelStatus, FacialRegion, ModuleDescriptor, UnifiedTrackingData};
+use common::ModuleSources;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+/// How long a module may go without a frame before its channels count as lost.
+pub const STALE_AFTER: Duration = Duration::from_millis(500);
+
+/// A part of the output that one module owns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    EyeGaze,
+    EyeOpenness,
+    /// Pupil diameters and the dilation range.
+    Pupil,
+    Head,
+    /// The expression shapes of one region, with their confidences.
+    Region(FacialRegion),
+}
+
+impl Channel {
+    /// Every channel, eye channels first.
+    pub fn all() -> impl Iterator<Item = Channel> {
+        [
+            Channel::EyeGaze,
+            Channel::EyeOpenness,
+            Channel::Pupil,
+            Channel::Head,
+        ]
+        .into_iter()
+        .chain(FacialRegion::ALL.into_iter().map(Channel::Region))
+    }
+
+    /// The name used by `GET /modules/sources`, e.g. `"eye_gaze"` or `"region.lip"`.
+    pub fn name(self) -> String {
+        match self {
+            Channel::EyeGaze => "eye_gaze".to_string(),
+            Channel::EyeOpenness => "eye_openness".to_string(),
+            Channel::Pupil => "pupil".to_string(),
+            Channel::Head => "head".to_string(),
+            Channel::Region(region) => format!("region.{:?}", region).to_lowercase(),
+        }
+    }
+
+    /// Copies this channel from `from` into `to`.
+    fn copy(self, from: &UnifiedTrackingData, to: &mut UnifiedTrackingData) {
+        let eyes = [
+            (&from.eye.left, &mut to.eye.left),
+            (&from.eye.right, &mut to.eye.right),
+        ];
+        match self {
+            Channel::EyeGaze => {
+                for (from, to) in eyes {
+                    to.gaze = from.gaze;
+                    // The eye status travels with the gaze.
+                    to.status = from.status;
+                }
+            }
+            Channel::EyeOpenness => {
+                for (from, to) in eyes {
+                    to.openness = from.openness;
+                }
+            }
+            Channel::Pupil => {
+                for (from, to) in eyes {
+                    to.pupil_diameter_mm = from.pupil_diameter_mm;
+                }
+                to.eye.max_dilation = from.eye.max_dilation;
+                to.eye.min_dilation = from.eye.min_dilation;
+                to.eye.left_diameter = from.eye.left_diameter;
+                to.eye.right_diameter = from.eye.right_diameter;
+            }
+            Channel::Head => to.head = from.head,
+            Channel::Region(region) => {
+                for expr in region.expressions() {
+                    let i = expr.index();
+                    if let Some(shape) = from.shapes.get(i) {
+                        to.shapes[i] = *shape;
+                    }
+                    // Baked with the sender's face status, which does not survive the merge.
+                    to.shape_confidences[i] = from.shape_confidence(i);
+                }
+            }
+        }
+    }
+
+    /// Marks this channel as lost in `data`. The eyes have one status, so a lost eye channel
+    /// takes the whole eye with it.
+    fn mark_lost(self, data: &mut UnifiedTrackingData) {
+        match self {
+            Channel::EyeGaze | Channel::EyeOpenness | Channel::Pupil => {
+                data.eye.left.status = ChannelStatus::LOST;
+                data.eye.right.status = ChannelStatus::LOST;
+            }
+            Channel::Head => data.head.status = ChannelStatus::LOST,
+            Channel::Region(region) => {
+                for expr in region.expressions() {
+                    data.shape_confidences[expr.index()] = 0.0;
+                }
+            }
+        }
+    }
+}
+
+/// Which module owns each channel, from `module.sources`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    owners: HashMap<Channel, String>,
+}
+
+impl SourceMap {
+    pub fn from_config(sources: &ModuleSources) -> Self {
+        let mut owners = HashMap::new();
+        for (channel, module) in [
+            (Channel::EyeGaze, &sources.eye_gaze),
+            (Channel::EyeOpenness, &sources.eye_openness),
+            (Channel::Pupil, &sources.pupil),
+            (Channel::Head, &sources.head),
+        ] {
+            if let Some(module) = module {
+                owners.insert(channel, module.clone());
+            }
+        }
+        for (region, module) in &sources.regions {
+            owners.insert(Channel::Region(*region), module.clone());
+        }
+        Self { owners }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    /// The module owning `channel`: the one assigned to it, or else the active one.
+    pub fn owner<'a>(&'a self, channel: Channel, active: Option<&'a str>) -> Option<&'a str> {
+        self.owners.get(&channel).map(String::as_str).or(active)
+    }
+
+    /// Modules that own a channel, besides whichever module is active.
+    pub fn modules(&self) -> BTreeSet<&str> {
+        self.owners.values().map(String::as_str).collect()
+    }
+
+    pub fn is_source(&self, name: &str) -> bool {
+        self.owners.values().any(|owner| owner == name)
+    }
+
+    /// What the merged output tracks: each module's descriptor, limited to the channels it
+    /// owns. `descriptors` lists the modules feeding the pipeline.
+    pub fn descriptor(
+        &self,
+        active: Option<&str>,
+        descriptors: &[(&str, Option<ModuleDescriptor>)],
+    ) -> Option<ModuleDescriptor> {
+        if self.is_empty() {
+            return descriptors
+                .iter()
+                .find(|(name, _)| Some(*name) == active)
+                .and_then(|(_, descriptor)| descriptor.clone());
+        }
+
+        let owned: Vec<ModuleDescriptor> = descriptors
+            .iter()
+            .filter_map(|(name, descriptor)| {
+                let mut descriptor = descriptor.clone()?;
+                let owns = |channel| self.owner(channel, active) == Some(*name);
+                let capabilities = &mut descriptor.capabilities;
+                capabilities.eye_gaze &= owns(Channel::EyeGaze);
+                capabilities.eye_openness &= owns(Channel::EyeOpenness);
+                capabilities.pupil &= owns(Channel::Pupil);
+                capabilities.head &= owns(Channel::Head);
+                descriptor
+                    .expressions
+                    .retain(|expr| owns(Channel::Region(expr.region())));
+                descriptor.capabilities.expressions &= !descriptor.expressions.is_empty();
+                Some(descriptor)
+            })
+            .collect();
+        if owned.is_empty() {
+            None
+        } else {
+            Some(ModuleDescriptor::combine(&owned))
+        }
+    }
+}
+
+/// Who feeds one channel and how fresh its data is, as served by `GET /modules/sources`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceStatus {
+    pub channel: String,
+    pub module: Option<String>,
+    /// Time since the module's last frame; `None` if it has not delivered one yet.
+    pub age_ms: Option<u64>,
+    pub stale: bool,
+}
+
+/// How recently, and how steadily, a module has been delivering frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Freshness {
+    /// Time since its last frame.
+    pub age: Duration,
+    /// How long it has delivered frames without a gap longer than [`STALE_AFTER`].
+    pub streak: Duration,
+}
+
+/// A cross-fade from an earlier output to the current one.
+#[derive(Debug)]
+struct Fade {
+    from: UnifiedTrackingData,
+    started: Instant,
+    duration: Duration,
+}
+
+/// The merged output and when each module last delivered a frame.
+#[derive(Debug)]
+pub struct Fusion {
+    sources: SourceMap,
+    active: Option<String>,
+    merged: UnifiedTrackingData,
+    last_frame: HashMap<String, Instant>,
+    /// Face status of the last frame of each module owning a region.
+    faces: HashMap<String, ChannelStatus>,
+    /// Start of each module's current streak of frames.
+    fresh_since: HashMap<String, Instant>,
+    last_output: Option<UnifiedTrackingData>,
+    fade: Option<Fade>,
+}
+
+impl Default for Fusion {
+    fn default() -> Self {
+        Self::new(SourceMap::default())
+    }
+}
+
+impl Fusion {
+    pub fn new(sources: SourceMap) -> Self {
+        Self {
+            sources,
+            active: None,
+            merged: UnifiedTrackingData::default(),
+            last_frame: HashMap::new(),
+            faces: HashMap::new(),
+            fresh_since: HashMap::new(),
+            last_output: None,
+            fade: None,
+        }
+    }
+
+    pub fn set_sources(&mut self, sources: SourceMap) {
+        self.sources = sources;
+    }
+
+    /// Whether frames are merged at all, i.e. any source is configured.
+    pub fn is_fused(&self) -> bool {
+        !self.sources.is_empty()
+    }
+
+    /// Sets the module that owns the channels not assigned elsewhere.
+    pub fn set_active(&mut self, active: Option<&str>) {
+        self.active = active.map(str::to_string);
+    }
+
+    /// Blends the output from the last one sent towards the new frames over `duration`,
+    /// e.g. when another module takes over.
+    pub fn crossfade(&mut self, duration: Duration) {
+        self.fade = self.last_output.clone().map(|from| Fade {
+            from,
+            started: Instant::now(),
+            duration,
+        });
+    }
+
+    /// Takes the channels `source` owns from `frame` and returns the merged output, or `None`
+    /// if `source` owns no channel. Without sources, the active module's frames pass through
+    /// unchanged.
+    pub fn merge(
+        &mut self,
+        source: &str,
+        frame: &UnifiedTrackingData,
+    ) -> Option<UnifiedTrackingData> {
+        let now = Instant::now();
+        let previous = self.last_frame.insert(source.to_string(), now);
+        if previous.is_none_or(|t| now.duration_since(t) > STALE_AFTER) {
+            self.fresh_since.insert(source.to_string(), now);
+        }
+
+        let mut output = if self.sources.is_empty() {
+            if self.active.as_deref() != Some(source) {
+                return None;
+            }
+            frame.clone()
+        } else {
+            let mut owns_any = false;
+            for channel in Channel::all() {
+                if self.sources.owner(channel, self.active.as_deref()) == Some(source) {
+                    channel.copy(frame, &mut self.merged);
+                    if let Channel::Region(_) = channel {
+                        self.faces.insert(source.to_string(), frame.face);
+                    }
+                    owns_any = true;
+                }
+            }
+            if !owns_any {
+                return None;
+            }
+
+            let mut output = self.merged.clone();
+            output.face = self.face_status(now);
+            output.timestamp_us = frame.timestamp_us;
+            for channel in Channel::all() {
+                if self.is_stale(channel, now) {
+                    channel.mark_lost(&mut output);
+                }
+            }
+            output
+        };
+
+        if let Some(fade) = &self.fade {
+            let t = now.duration_since(fade.started).as_secs_f32() / fade.duration.as_secs_f32();
+            if t < 1.0 {
+                blend(&fade.from, &mut output, t);
+            } else {
+                self.fade = None;
+            }
+        }
+        self.last_output = Some(output.clone());
+        Some(output)
+    }
+
+    /// How fresh the frames of `module` are; `None` if it has not delivered one yet.
+    pub fn freshness(&self, module: &str) -> Option<Freshness> {
+        let now = Instant::now();
+        let last = *self.last_frame.get(module)?;
+        let since = self.fresh_since.get(module).copied().unwrap_or(last);
+        let age = now.duration_since(last);
+        Some(Freshness {
+            age,
+            streak: if age > STALE_AFTER {
+                Duration::ZERO
+            } else {
+                now.duration_since(since)
+            },
+        })
+    }
+
+    /// Owner and freshness of every channel. Empty without sources.
+    pub fn status(&self) -> Vec<SourceStatus> {
+        if self.sources.is_empty() {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        Channel::all()
+            .map(|channel| {
+                let module = self.sources.owner(channel, self.active.as_deref());
+                SourceStatus {
+                    channel: channel.name(),
+                    module: module.map(str::to_string),
+                    age_ms: module
+                        .and_then(|m| self.last_frame.get(m))
+                        .map(|t| now.duration_since(*t).as_millis() as u64),
+                    stale: self.is_stale(channel, now),
+                }
+            })
+            .collect()
+    }
+
+    /// The face is valid if a module owning a region tracks it, with the best confidence of
+    /// those modules. The shape confidences keep which regions are lost.
+    fn face_status(&self, now: Instant) -> ChannelStatus {
+        FacialRegion::ALL
+            .into_iter()
+            .map(Channel::Region)
+            .filter(|channel| !self.is_stale(*channel, now))
+            .filter_map(|channel| self.sources.owner(channel, self.active.as_deref()))
+            .filter_map(|module| self.faces.get(module))
+            .filter(|face| face.valid)
+            .map(|face| face.confidence)
+            .reduce(f32::max)
+            .map_or(ChannelStatus::LOST, |confidence| {
+                ChannelStatus::new(true, confidence)
+            })
+    }
+
+    fn is_stale(&self, channel: Channel, now: Instant) -> bool {
+        self.sources
+            .owner(channel, self.active.as_deref())
+            .and_then(|module| self.last_frame.get(module))
+            .is_none_or(|t| now.duration_since(*t) > STALE_AFTER)
+    }
+}
+
+/// Moves `to` from `from` by `t` in `[0, 1]`: `0` gives `from`, `1` leaves `to` as it is.
+/// Statuses and confidences are taken from `to`.
+pub fn blend(from: &UnifiedTrackingData, to: &mut UnifiedTrackingData, t: f32) {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |from: f32, to: &mut f32| *to = from + (*to - from) * t;
+
+    for (from, to) in from.shapes.iter().zip(&mut to.shapes) {
+        lerp(from.weight, &mut to.weight);
+    }
+    for (from, to) in [
+        (&from.eye.left, &mut to.eye.left),
+        (&from.eye.right, &mut to.eye.right),
+    ] {
+        lerp(from.openness, &mut to.openness);
+        lerp(from.pupil_diameter_mm, &mut to.pupil_diameter_mm);
+        to.gaze = gaze::normalize(from.gaze.lerp(to.gaze, t));
+    }
+    let (from, to) = (&from.head, &mut to.head);
+    for (from, to) in [
+        (from.head_yaw, &mut to.head_yaw),
+        (from.head_pitch, &mut to.head_pitch),
+        (from.head_roll, &mut to.head_roll),
+        (from.head_pos_x, &mut to.head_pos_x),
+        (from.head_pos_y, &mut to.head_pos_y),
+        (from.head_pos_z, &mut to.head_pos_z),
+    ] {
+        lerp(from, to);
+    }
+}
@@ -0,0 +1,183 @@
+//! The host's side of `api::HostServices`: what modules report ends up in a shared
+//! status table that the HTTP API serves.
+
+use crate::osc::query::extensions::DEFAULT_CALIBRATION_SECONDS;
+use crate::plugins::faults::FaultHistory;
+use crate::plugins::metrics::ModuleMetrics;
+use api::{ConnectionState, Host, HostServices, LogLevel, ModuleLogger, TrackingState};
+use log::{debug, error, info, trace, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Directory holding one state directory per module.
+pub const MODULE_DATA_DIR: &str = "module_data";
+
+/// What a module last reported through its host services.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModuleStatus {
+    pub connection: ConnectionState,
+    /// What the last `update` returned; `None` until the module is polled.
+    pub tracking: Option<TrackingState>,
+    pub metrics: ModuleMetrics,
+    /// Panics caught in the module's calls, and the restarts that followed.
+    pub faults: FaultHistory,
+    /// The module has been stuck in `update` for longer than `module.update_deadline_ms`.
+    pub stalled: bool,
+}
+
+/// Status of every module, keyed by module name.
+pub type SharedModuleStatus = Arc<RwLock<HashMap<String, ModuleStatus>>>;
+
+/// Host services of one module.
+pub struct ModuleHost {
+    module: String,
+    status: SharedModuleStatus,
+    calibration_request: Arc<RwLock<Option<f32>>>,
+}
+
+impl ModuleHost {
+    pub fn new(
+        module: &str,
+        status: SharedModuleStatus,
+        calibration_request: Arc<RwLock<Option<f32>>>,
+    ) -> Self {
+        status
+            .write()
+            .unwrap()
+            .entry(module.to_string())
+            .or_default();
+        Self {
+            module: module.to_string(),
+            status,
+            calibration_request,
+        }
+    }
+}
+
+impl ModuleHost {
+    /// Runs `f` on this module's metrics. The host records its own per-module metrics here.
+    pub fn with_metrics(&self, f: impl FnOnce(&mut ModuleMetrics)) {
+        let mut status = self.status.write().unwrap();
+        f(&mut status.entry(self.module.clone()).or_default().metrics);
+    }
+
+    /// Records whether the module is stuck in `update`.
+    pub fn set_stalled(&self, stalled: bool) {
+        self.status
+            .write()
+            .unwrap()
+            .entry(self.module.clone())
+            .or_default()
+            .stalled = stalled;
+    }
+
+    /// Records what the module's `update` returned. The connection state follows it.
+    pub fn tracking_changed(&self, state: TrackingState) {
+        self.status
+            .write()
+            .unwrap()
+            .entry(self.module.clone())
+            .or_default()
+            .tracking = Some(state);
+        self.connection_changed(state.connection());
+    }
+}
+
+impl Host for ModuleHost {
+    fn connection_changed(&self, state: ConnectionState) {
+        let mut status = self.status.write().unwrap();
+        let entry = status.entry(self.module.clone()).or_default();
+        if entry.connection != state {
+            info!("Module {} is now {:?}", self.module, state);
+            entry.connection = state;
+        }
+    }
+
+    fn request_recalibration(&self) {
+        let mut request = self.calibration_request.write().unwrap();
+        if request.is_none() {
+            info!("Module {} requested recalibration", self.module);
+            *request = Some(DEFAULT_CALIBRATION_SECONDS);
+        }
+    }
+
+    fn set_gauge(&self, name: &str, value: f64) {
+        self.with_metrics(|metrics| metrics.set(name, value));
+    }
+
+    fn add_counter(&self, name: &str, delta: u64) {
+        self.with_metrics(|metrics| metrics.add(name, delta));
+    }
+}
+
+/// Forwards module log lines to the host's logger, under the target the module was given.
+/// A null target logs as `unknown`, a null message as an empty line.
+pub(crate) extern "C" fn module_log_callback(level: u32, target: *const i8, message: *const i8) {
+    unsafe {
+        let target_str = if target.is_null() {
+            "unknown"
+        } else {
+            std::ffi::CStr::from_ptr(target)
+                .to_str()
+                .unwrap_or("unknown")
+        };
+        let message_str = if message.is_null() {
+            ""
+        } else {
+            std::ffi::CStr::from_ptr(message).to_str().unwrap_or("")
+        };
+
+        match LogLevel::from_raw(level) {
+            LogLevel::Error => error!(target: target_str, "{}", message_str),
+            LogLevel::Warn => warn!(target: target_str, "{}", message_str),
+            LogLevel::Info => info!(target: target_str, "{}", message_str),
+            LogLevel::Debug => debug!(target: target_str, "{}", message_str),
+            LogLevel::Trace => trace!(target: target_str, "{}", message_str),
+        }
+    }
+}
+
+/// Logger for `module`, forwarding to the host's logger under `vrft_d::plugins::<module>`.
+pub fn module_logger(module: &str) -> ModuleLogger {
+    ModuleLogger::new(module_log_callback, format!("vrft_d::plugins::{}", module))
+}
+
+/// State directory of `module` under `root`, named after the full module or instance name
+/// with path separators and characters reserved on Windows replaced. Created on demand;
+/// `None` if that fails.
+pub fn module_state_dir(root: &Path, module: &str) -> Option<PathBuf> {
+    let mut name: String = module
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, and `.` or `..` would leave `root`.
+    if name.ends_with(['.', ' ']) {
+        name.push('_');
+    }
+    if name.is_empty() {
+        return None;
+    }
+    let dir = root.join(name);
+    match std::fs::create_dir_all(&dir) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            log::warn!("Could not create state directory {:?}: {}", dir, e);
+            None
+        }
+    }
+}
+
+/// Host services for `module`, reporting through `host`.
+pub fn host_services(module: &str, logger: ModuleLogger, host: Arc<ModuleHost>) -> HostServices {
+    HostServices::new(
+        logger,
+        module_state_dir(Path::new(MODULE_DATA_DIR), module),
+        host,
+    )
+}
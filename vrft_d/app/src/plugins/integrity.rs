@@ -0,0 +1,145 @@
+//! Integrity policy for native module libraries (`module.integrity`).
+//!
+//! A library is loaded only if its SHA-256 digest is on the allowlist, or if a detached
+//! Ed25519 signature next to it (`<library>.sig`) verifies against one of the configured
+//! keys. The check runs on the exact file handed to the loader, right before `Library::new`.
+
+use anyhow::{Context, Result};
+use common::IntegrityConfig;
+use log::warn;
+use ring::digest::{digest, SHA256};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Extension of a detached signature, appended to the library's file name.
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// A library the policy refused to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refused(pub String);
+
+impl fmt::Display for Refused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Refused by the integrity policy: {}", self.0)
+    }
+}
+
+impl std::error::Error for Refused {}
+
+/// The parsed `module.integrity` section. Disabled by default, letting every library load.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityPolicy {
+    enabled: bool,
+    sha256: HashSet<String>,
+    keys: Vec<Vec<u8>>,
+}
+
+impl IntegrityPolicy {
+    /// Entries that do not parse are logged and left out, so they allow nothing.
+    pub fn from_config(config: &IntegrityConfig) -> Self {
+        let sha256 = config
+            .sha256
+            .iter()
+            .filter_map(|entry| match decode_hex(entry) {
+                Some(bytes) if bytes.len() == 32 => Some(entry.to_ascii_lowercase()),
+                _ => {
+                    warn!(
+                        "Ignoring invalid SHA-256 digest in module.integrity: {}",
+                        entry
+                    );
+                    None
+                }
+            })
+            .collect();
+        let keys = config
+            .signing_keys
+            .iter()
+            .filter_map(|entry| match decode_hex(entry) {
+                Some(bytes) if bytes.len() == 32 => Some(bytes),
+                _ => {
+                    warn!(
+                        "Ignoring invalid Ed25519 key in module.integrity: {}",
+                        entry
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self {
+            enabled: config.enabled,
+            sha256,
+            keys,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Checks the library at `loaded`, a copy of `original` or the same file. The signature
+    /// is looked up next to `original`. Returns how it passed, or a [`Refused`] error.
+    pub fn verify(&self, original: &Path, loaded: &Path) -> Result<Option<String>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let bytes =
+            std::fs::read(loaded).with_context(|| format!("Failed to read {:?}", loaded))?;
+        let sha256 = encode_hex(digest(&SHA256, &bytes).as_ref());
+        if self.sha256.contains(&sha256) {
+            return Ok(Some("its SHA-256 digest is allowed".to_string()));
+        }
+
+        let signature_path = signature_path(original);
+        let Ok(signature) = std::fs::read(&signature_path) else {
+            return Err(Refused(format!(
+                "SHA-256 {} is not allowed and {:?} has no signature",
+                sha256, original
+            ))
+            .into());
+        };
+        // Raw bytes or hex text.
+        let signature = match std::str::from_utf8(&signature).ok().and_then(decode_hex) {
+            Some(decoded) => decoded,
+            None => signature,
+        };
+        for (index, key) in self.keys.iter().enumerate() {
+            if UnparsedPublicKey::new(&ED25519, key)
+                .verify(&bytes, &signature)
+                .is_ok()
+            {
+                return Ok(Some(format!("it is signed by signing key {}", index + 1)));
+            }
+        }
+        Err(Refused(format!(
+            "SHA-256 {} is not allowed and {:?} does not verify against any signing key",
+            sha256, signature_path
+        ))
+        .into())
+    }
+}
+
+/// Where the detached signature of `library` is expected, e.g. `module.dll.sig`.
+pub fn signature_path(library: &Path) -> PathBuf {
+    let mut name = library.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    library.with_file_name(name)
+}
+
+/// Lowercase hex of `bytes`, as digests are written in the config.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
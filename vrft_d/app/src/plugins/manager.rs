@@ -0,0 +1,1459 @@
+//! Discovery and lifecycle of tracking modules, so they can be loaded, swapped and unloaded
+//! while the daemon runs.
+//!
+//! The HTTP API only sees a [`ModuleControl`]: it lists what is on disk and queues
+//! [`ModuleCommand`]s. The producer loop owns the [`ModuleManager`] and applies them between
+//! frames, so the output and the calibration keep running through a swap.
+//!
+//! Every call into a module is wrapped in [`contain`]. A module that panics is unloaded and
+//! recreated with backoff, see [`RestartPolicy`].
+//!
+//! The active module is polled on its own thread by a [`PollWorker`]. The producer loop only
+//! watches it, and keeps the output fed while an `update` overruns its deadline.
+//!
+//! Modules named in `module.sources` feed the pipeline next to the active module, each with
+//! the channels it owns; the emitter merges their frames, see [`SourceMap`].
+//!
+//! When `module.active` lists several modules, they all run and the first one delivering
+//! frames is active; [`ModuleManager::fail_over`] switches between them.
+
+use crate::plugins::emitter::FrameEmitter;
+use crate::plugins::faults::{contain, FaultHistory, ModuleFault, RestartPolicy};
+use crate::plugins::fusion::{SourceMap, STALE_AFTER};
+use crate::plugins::host::{host_services, module_logger, ModuleHost, SharedModuleStatus};
+use crate::plugins::integrity::{IntegrityPolicy, Refused};
+use crate::plugins::manifest::{PluginManifest, MANIFEST_FILE};
+use crate::plugins::metrics::HOST_FRAMES;
+use crate::plugins::native::{is_native_library, load_native_module, shadow_dir, ShadowCopy};
+use crate::plugins::sandbox::{host_exe, SandboxModule};
+use crate::plugins::settings::{configure_module, ModuleSettingsStore};
+use crate::plugins::worker::{
+    after_workers, decay_toward_neutral, lock_module, try_lock_module, PollWorker, SharedModule,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use api::{
+    timestamp_us_now, ConnectionState, FrameSink, Host, ModuleDescriptor, ModuleSettings,
+    ProxyModule, TrackingModule, UnifiedTrackingData,
+};
+use common::{ModuleInstance, ModuleRuntime, StallBehavior};
+use libloading::Library;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often library files are checked for changes when hot reload is on.
+const HOT_RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How quickly a held frame relaxes towards a neutral face with `StallBehavior::Decay`.
+const STALL_DECAY_TIME: Duration = Duration::from_secs(1);
+
+/// How a module is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleKind {
+    /// A library implementing the C ABI, loaded into the daemon.
+    Native,
+    /// A VRCFaceTracking module, run by the .NET `VrcftRuntime` host.
+    Vrcft,
+}
+
+/// A module found on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleFile {
+    pub name: String,
+    pub kind: ModuleKind,
+    pub path: PathBuf,
+    /// The `plugin.json` of a plugin in a folder of its own.
+    pub manifest: Option<PluginManifest>,
+    /// Why the module cannot be loaded here, e.g. a plugin for another platform.
+    pub unavailable: Option<String>,
+    /// The module this is a named instance of (`module.instances`).
+    pub instance_of: Option<String>,
+}
+
+/// Where modules are looked for.
+#[derive(Debug, Clone)]
+pub struct ModuleDirs {
+    pub native: PathBuf,
+    pub vrcft: PathBuf,
+    /// The `VrcftRuntime` executable.
+    pub vrcft_host: PathBuf,
+    /// Where native modules are copied before loading when hot reload or an integrity
+    /// policy is on. Only the daemon's user may write to it.
+    pub shadow: PathBuf,
+}
+
+impl ModuleDirs {
+    /// The `plugins` directories under the working directory, or under its parent when
+    /// started from a build tree.
+    pub fn locate() -> Self {
+        let host_exe = format!("VrcftRuntime{}", std::env::consts::EXE_SUFFIX);
+        Self {
+            native: locate("plugins/native"),
+            vrcft: locate("plugins/dotnet/modules"),
+            vrcft_host: locate("plugins/dotnet/host").join(host_exe),
+            shadow: shadow_dir(),
+        }
+    }
+
+    /// Modules in the plugin directories, sorted by name: plugins in folders of their own,
+    /// named by their manifest, and loose libraries, named by their file. A native module
+    /// hides a VRCFT module of the same name.
+    pub fn discover(&self) -> Vec<ModuleFile> {
+        let mut files = list_plugins(&self.native, ModuleKind::Native);
+        files.extend(list_dir(
+            &self.native,
+            ModuleKind::Native,
+            is_native_library,
+        ));
+        let mut vrcft = list_plugins(&self.vrcft, ModuleKind::Vrcft);
+        vrcft.extend(list_dir(&self.vrcft, ModuleKind::Vrcft, |path| {
+            path.extension().is_some_and(|ext| ext == "dll")
+        }));
+        for file in vrcft {
+            if !files.iter().any(|f| f.name == file.name) {
+                files.push(file);
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        files
+    }
+
+    pub fn find(&self, name: &str) -> Option<ModuleFile> {
+        self.discover().into_iter().find(|f| f.name == name)
+    }
+}
+
+fn locate(dir: &str) -> PathBuf {
+    let path = Path::new(dir);
+    let parent = Path::new("..").join(dir);
+    if !path.exists() && parent.exists() {
+        parent
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn list_dir(dir: &Path, kind: ModuleKind, filter: impl Fn(&Path) -> bool) -> Vec<ModuleFile> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && filter(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            Some(ModuleFile {
+                name,
+                kind,
+                path,
+                manifest: None,
+                unavailable: None,
+                instance_of: None,
+            })
+        })
+        .collect()
+}
+
+/// Plugins in the folders of `dir` that hold a manifest. The manifest's runtime decides the
+/// kind; a plugin whose manifest cannot be read is listed under its folder name as
+/// unavailable.
+fn list_plugins(dir: &Path, kind: ModuleKind) -> Vec<ModuleFile> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .filter_map(|folder| {
+            let manifest_path = folder.join(MANIFEST_FILE);
+            let file = match PluginManifest::read(&manifest_path) {
+                Ok(manifest) => {
+                    let checked = manifest.check(&folder);
+                    ModuleFile {
+                        name: manifest.id.clone(),
+                        kind: match manifest.runtime {
+                            ModuleRuntime::Native => ModuleKind::Native,
+                            ModuleRuntime::Vrcft => ModuleKind::Vrcft,
+                        },
+                        path: checked.as_ref().map_or(manifest_path, PathBuf::clone),
+                        unavailable: checked.err().map(|e| format!("{:#}", e)),
+                        manifest: Some(manifest),
+                        instance_of: None,
+                    }
+                }
+                Err(e) => ModuleFile {
+                    name: folder.file_name()?.to_str()?.to_string(),
+                    kind,
+                    path: manifest_path,
+                    manifest: None,
+                    unavailable: Some(format!("{:#}", e)),
+                    instance_of: None,
+                },
+            };
+            Some(file)
+        })
+        .collect()
+}
+
+/// A change requested through the HTTP API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleCommand {
+    /// Loads the module if needed and feeds the pipeline from it instead of the active one.
+    Activate(String),
+    /// Stops feeding the pipeline from the module if it is the active one. It stays loaded.
+    Deactivate(String),
+    Unload(String),
+    /// Unloads the module if loaded and loads it again from disk, keeping it active if it was.
+    Reload(String),
+}
+
+impl ModuleCommand {
+    pub fn module(&self) -> &str {
+        match self {
+            Self::Activate(name)
+            | Self::Deactivate(name)
+            | Self::Unload(name)
+            | Self::Reload(name) => name,
+        }
+    }
+}
+
+/// One module as the HTTP API lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleListing {
+    pub name: String,
+    pub kind: ModuleKind,
+    pub path: PathBuf,
+    /// Can be loaded here; see `error` otherwise.
+    pub available: bool,
+    pub loaded: bool,
+    pub active: bool,
+    /// Feeding the pipeline, as the active module or as a source.
+    pub feeding: bool,
+    pub streaming: bool,
+    /// Why the module cannot be loaded, or why the last load failed.
+    pub error: Option<String>,
+    /// The last load was refused by the integrity policy.
+    pub refused: bool,
+    pub manifest: Option<PluginManifest>,
+    /// The module this is a named instance of.
+    pub instance_of: Option<String>,
+}
+
+/// What the HTTP API knows about the modules, plus the commands the producer loop still has
+/// to apply.
+#[derive(Debug)]
+pub struct ModuleControl {
+    dirs: ModuleDirs,
+    instances: BTreeMap<String, ModuleInstance>,
+    active: Option<String>,
+    /// Loaded modules and whether each one streams.
+    loaded: Vec<(ModuleFile, bool)>,
+    feeding: Vec<String>,
+    errors: HashMap<String, String>,
+    refused: HashSet<String>,
+    pending: Vec<ModuleCommand>,
+}
+
+pub type SharedModuleControl = Arc<RwLock<ModuleControl>>;
+
+impl ModuleControl {
+    pub fn new(dirs: ModuleDirs) -> Self {
+        Self {
+            dirs,
+            instances: BTreeMap::new(),
+            active: None,
+            loaded: Vec::new(),
+            feeding: Vec::new(),
+            errors: HashMap::new(),
+            refused: HashSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Adds the named instances of `module.instances` to the modules on disk.
+    pub fn with_instances(mut self, instances: BTreeMap<String, ModuleInstance>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    pub fn dirs(&self) -> &ModuleDirs {
+        &self.dirs
+    }
+
+    /// Modules on disk plus the configured instances, sorted by name. An instance takes the
+    /// place of a module of the same name.
+    pub fn discover(&self) -> Vec<ModuleFile> {
+        let mut files = self.dirs.discover();
+        let instances: Vec<ModuleFile> = self
+            .instances
+            .iter()
+            .map(|(name, instance)| {
+                let base = files.iter().find(|f| f.name == instance.module);
+                ModuleFile {
+                    name: name.clone(),
+                    instance_of: Some(instance.module.clone()),
+                    ..base.cloned().unwrap_or_else(|| ModuleFile {
+                        name: String::new(),
+                        kind: ModuleKind::Native,
+                        path: PathBuf::new(),
+                        manifest: None,
+                        unavailable: Some(format!(
+                            "No module named {} in the plugin directories",
+                            instance.module
+                        )),
+                        instance_of: None,
+                    })
+                }
+            })
+            .collect();
+        files.retain(|f| !self.instances.contains_key(&f.name));
+        files.extend(instances);
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        files
+    }
+
+    pub fn find(&self, name: &str) -> Option<ModuleFile> {
+        self.discover().into_iter().find(|f| f.name == name)
+    }
+
+    /// The module feeding the pipeline.
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Modules on disk and loaded modules whose file has since been removed.
+    pub fn listing(&self) -> Vec<ModuleListing> {
+        let mut files = self.discover();
+        for (file, _) in &self.loaded {
+            if !files.iter().any(|f| f.name == file.name) {
+                files.push(file.clone());
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        files
+            .into_iter()
+            .map(|file| {
+                let loaded = self.loaded.iter().find(|(f, _)| f.name == file.name);
+                ModuleListing {
+                    available: file.unavailable.is_none(),
+                    loaded: loaded.is_some(),
+                    active: self.active.as_deref() == Some(file.name.as_str()),
+                    feeding: self.feeding.contains(&file.name),
+                    streaming: loaded.is_some_and(|(_, streaming)| *streaming),
+                    error: self.errors.get(&file.name).cloned().or(file.unavailable),
+                    refused: self.refused.contains(&file.name),
+                    name: file.name,
+                    kind: file.kind,
+                    path: file.path,
+                    manifest: file.manifest,
+                    instance_of: file.instance_of,
+                }
+            })
+            .collect()
+    }
+
+    /// Queues `command` for the producer loop. Fails for modules that are neither on disk
+    /// nor loaded.
+    pub fn request(&mut self, command: ModuleCommand) -> Result<()> {
+        let name = command.module();
+        let known = self.loaded.iter().any(|(f, _)| f.name == name) || self.find(name).is_some();
+        if !known {
+            bail!("Unknown module {}", name);
+        }
+        self.pending.push(command);
+        Ok(())
+    }
+
+    /// Commands requested since the last call, in order.
+    pub fn take_pending(&mut self) -> Vec<ModuleCommand> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// A module that is loaded and initialized.
+pub struct LoadedModule {
+    /// Polls the module while it is active. Declared first, so the thread has stopped before
+    /// the module and its library are dropped.
+    worker: Option<PollWorker>,
+    /// Workers of earlier activations that were asked to stop, possibly still stuck in
+    /// `update`.
+    retired: Vec<PollWorker>,
+    pub name: String,
+    pub kind: ModuleKind,
+    pub module: SharedModule,
+    /// Pushes frames from its own thread instead of being polled.
+    pub streaming: bool,
+    /// The host records this module's metrics here.
+    pub host: Arc<ModuleHost>,
+    /// Returned `TrackingState::Fatal`; no longer polled.
+    pub fatal: bool,
+    /// Whether the module feeds the pipeline, as the active module or as a source.
+    feeding: Arc<AtomicBool>,
+    /// What it reported when it was loaded.
+    descriptor: Option<ModuleDescriptor>,
+    file: ModuleFile,
+    #[allow(dead_code)]
+    _lib: Option<Library>, // Keep library loaded; dropped after the module
+    #[allow(dead_code)]
+    _shadow: Option<ShadowCopy>, // Deleted after the library is unloaded
+}
+
+/// Size and modification time of a library file, to notice it being replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// A native module watched for hot reload.
+struct WatchedFile {
+    /// The file as it was last loaded.
+    loaded: FileStamp,
+    /// A change seen on the previous check. Reloading waits until the file stops changing,
+    /// so a library still being written is not loaded.
+    changed: Option<FileStamp>,
+    /// Activate the module once it loads again; set when a reload of the active module fails.
+    reactivate: bool,
+}
+
+/// Restart bookkeeping of a module that faulted.
+#[derive(Debug, Default)]
+struct Supervision {
+    /// Restarts since the module last ran stably.
+    attempts: u32,
+    /// When it was last restarted.
+    restarted: Option<Instant>,
+    /// When to restart it next; `None` while it runs.
+    due: Option<Instant>,
+    /// Activate it again once it is back.
+    reactivate: bool,
+}
+
+/// A module feeding the pipeline is stuck in `update`.
+struct Stall {
+    since: Instant,
+    /// The last frame before it got stuck.
+    held: Option<UnifiedTrackingData>,
+}
+
+/// Shared state a module is wired to when it is loaded.
+pub struct ModuleContext {
+    pub status: SharedModuleStatus,
+    pub calibration_request: Arc<RwLock<Option<f32>>>,
+    pub settings: Arc<RwLock<ModuleSettingsStore>>,
+    /// What the active module tracks; read by the output strategies.
+    pub descriptor: Arc<RwLock<Option<ModuleDescriptor>>>,
+    pub emitter: Arc<FrameEmitter>,
+}
+
+/// Owns the loaded modules. Lives on the producer thread.
+pub struct ModuleManager {
+    context: ModuleContext,
+    control: SharedModuleControl,
+    modules: Vec<LoadedModule>,
+    active: Option<String>,
+    hot_reload: bool,
+    watched: HashMap<String, WatchedFile>,
+    last_check: Instant,
+    sandbox: bool,
+    integrity: IntegrityPolicy,
+    restart_policy: RestartPolicy,
+    supervision: HashMap<String, Supervision>,
+    update_deadline: Duration,
+    on_stall: StallBehavior,
+    stalls: HashMap<String, Stall>,
+    /// Settings for modules that were in `update` when they arrived.
+    pending_settings: HashMap<String, ModuleSettings>,
+    sources: SourceMap,
+    /// Failover chain, in order of preference.
+    chain: Vec<String>,
+    failover_after: Duration,
+    crossfade: Duration,
+    activated_at: Instant,
+}
+
+impl ModuleManager {
+    pub fn new(context: ModuleContext, control: SharedModuleControl) -> Self {
+        Self {
+            context,
+            control,
+            modules: Vec::new(),
+            active: None,
+            hot_reload: false,
+            watched: HashMap::new(),
+            last_check: Instant::now(),
+            sandbox: false,
+            integrity: IntegrityPolicy::default(),
+            restart_policy: RestartPolicy::default(),
+            supervision: HashMap::new(),
+            update_deadline: Duration::from_millis(250),
+            on_stall: StallBehavior::default(),
+            stalls: HashMap::new(),
+            pending_settings: HashMap::new(),
+            sources: SourceMap::default(),
+            chain: Vec::new(),
+            failover_after: Duration::from_secs(1),
+            crossfade: Duration::from_millis(300),
+            activated_at: Instant::now(),
+        }
+    }
+
+    /// Loads native modules from shadow copies and reloads them when their file changes.
+    pub fn with_hot_reload(mut self, enabled: bool) -> Self {
+        self.hot_reload = enabled;
+        if enabled {
+            // Copies left behind by an earlier run.
+            let shadow = self.control.read().unwrap().dirs.shadow.clone();
+            let _ = std::fs::remove_dir_all(shadow);
+        }
+        self
+    }
+
+    /// Checks native libraries against `policy` before loading them.
+    pub fn with_integrity(mut self, policy: IntegrityPolicy) -> Self {
+        if policy.is_enabled() {
+            let shadow = self.control.read().unwrap().dirs.shadow.clone();
+            let _ = std::fs::remove_dir_all(shadow);
+        }
+        self.integrity = policy;
+        self
+    }
+
+    /// Runs each native module in its own `vrft_module_host` process instead of loading it
+    /// into the daemon.
+    pub fn with_sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = enabled;
+        self
+    }
+
+    /// Replaces the default [`RestartPolicy`] for faulted modules.
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// How long an `update` may take before the module counts as stuck, and what the output
+    /// shows meanwhile.
+    pub fn with_update_deadline(mut self, deadline: Duration, on_stall: StallBehavior) -> Self {
+        self.update_deadline = deadline;
+        self.on_stall = on_stall;
+        self
+    }
+
+    /// Feeds the channels in `sources` from their own modules instead of the active one.
+    pub fn with_sources(mut self, sources: SourceMap) -> Self {
+        self.context.emitter.set_sources(sources.clone());
+        self.sources = sources;
+        self
+    }
+
+    /// Falls over between the modules of `chain`, in order of preference, when the active one
+    /// delivers no frames for `after`, cross-fading the output over `crossfade`.
+    pub fn with_failover(
+        mut self,
+        chain: Vec<String>,
+        after: Duration,
+        crossfade: Duration,
+    ) -> Self {
+        self.chain = chain;
+        self.failover_after = after;
+        self.crossfade = crossfade;
+        self
+    }
+
+    pub fn modules(&self) -> &[LoadedModule] {
+        &self.modules
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.modules.iter().any(|m| m.name == name)
+    }
+
+    /// Name of the module feeding the pipeline, loaded or not.
+    pub fn active_name(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// The module feeding the pipeline.
+    pub fn active_module(&mut self) -> Option<&mut LoadedModule> {
+        let active = self.active.as_deref()?;
+        self.modules.iter_mut().find(|m| m.name == active)
+    }
+
+    /// Finds the modules on disk without loading any, so they can be listed and configured
+    /// before one is activated. Settings schemas from plugin manifests apply right away.
+    pub fn discover(&mut self) {
+        let files = self.control.read().unwrap().discover();
+        let mut settings = self.context.settings.write().unwrap();
+        for file in &files {
+            if let Some(schema) = file
+                .manifest
+                .as_ref()
+                .and_then(|m| m.settings_schema.clone())
+            {
+                settings.set_schema(&file.name, Some(schema));
+            }
+        }
+        drop(settings);
+        info!("Found {} module(s)", files.len());
+        self.publish();
+    }
+
+    /// The module named `name` in the plugin directories.
+    pub fn find(&self, name: &str) -> Option<ModuleFile> {
+        self.control.read().unwrap().find(name)
+    }
+
+    /// Loads every native module found, e.g. to check that they all load.
+    pub fn load_native_modules(&mut self) {
+        let files = self.control.read().unwrap().discover();
+        for file in files {
+            if file.kind == ModuleKind::Native {
+                let _ = self.load(&file.name);
+            }
+        }
+    }
+
+    /// Loads and initializes `name` unless it is loaded already. Failures are logged and
+    /// listed by the HTTP API.
+    pub fn load(&mut self, name: &str) -> Result<()> {
+        if self.is_loaded(name) {
+            return Ok(());
+        }
+        let result = match contain(|| self.try_load(name)) {
+            Ok(result) => result,
+            Err(message) => {
+                self.record_fault(name, "load", &message);
+                Err(anyhow!("Module panicked while loading: {}", message))
+            }
+        };
+        let refused = result
+            .as_ref()
+            .is_err_and(|e| e.downcast_ref::<Refused>().is_some());
+        match &result {
+            Err(e) if refused => error!("✗ Refused to load module {}: {:#}", name, e),
+            Err(e) => error!("✗ Failed to load module {}: {:#}", name, e),
+            Ok(()) => {}
+        }
+        let mut control = self.control.write().unwrap();
+        match &result {
+            Ok(()) => control.errors.remove(name),
+            Err(e) => control.errors.insert(name.to_string(), format!("{:#}", e)),
+        };
+        if refused {
+            control.refused.insert(name.to_string());
+        } else {
+            control.refused.remove(name);
+        }
+        drop(control);
+        self.publish();
+        result
+    }
+
+    fn try_load(&mut self, name: &str) -> Result<()> {
+        let (dirs, file) = {
+            let control = self.control.read().unwrap();
+            (control.dirs.clone(), control.find(name))
+        };
+        let file =
+            file.with_context(|| format!("No module named {} in the plugin directories", name))?;
+        if let Some(reason) = &file.unavailable {
+            bail!("{}", reason);
+        }
+
+        info!("Loading module: {:?}", file.path);
+        let stamp = FileStamp::of(&file.path);
+        let (module, lib, shadow): (Box<dyn TrackingModule + Send>, _, _) = match file.kind {
+            ModuleKind::Native => {
+                // With an integrity policy, the copy is checked and loaded, so a change to the
+                // original after the check is not picked up.
+                let shadow = if self.hot_reload || self.integrity.is_enabled() {
+                    Some(ShadowCopy::create(&file.path, &dirs.shadow)?)
+                } else {
+                    None
+                };
+                let path = shadow
+                    .as_ref()
+                    .map_or(file.path.as_path(), ShadowCopy::path);
+                if let Some(passed) = self.integrity.verify(&file.path, path)? {
+                    info!("Module {} passed the integrity check: {}", name, passed);
+                }
+                if self.sandbox {
+                    let mut sandbox = SandboxModule::new(&host_exe()?, path);
+                    sandbox.start().context("Failed to start the module host")?;
+                    (Box::new(sandbox), None, shadow)
+                } else {
+                    let (module, lib) = load_native_module(path)?;
+                    (module, Some(lib), shadow)
+                }
+            }
+            ModuleKind::Vrcft => {
+                if !dirs.vrcft_host.exists() {
+                    bail!("VrcftRuntime not found at {:?}", dirs.vrcft_host);
+                }
+                // Runtimes share one shared memory block, so only one can run at a time.
+                let running: Vec<String> = self
+                    .modules
+                    .iter()
+                    .filter(|m| m.kind == ModuleKind::Vrcft)
+                    .map(|m| m.name.clone())
+                    .collect();
+                for other in running {
+                    info!("Unloading {} to start the VrcftRuntime for {}", other, name);
+                    self.unload(&other);
+                }
+
+                let mut proxy = ProxyModule::new();
+                info!("Starting VrcftRuntime for module: {:?}", file.path);
+                proxy
+                    .start(&dirs.vrcft_host, &file.path)
+                    .context("Failed to start VrcftRuntime")?;
+                (Box::new(proxy), None, None)
+            }
+        };
+
+        let host = Arc::new(ModuleHost::new(
+            name,
+            self.context.status.clone(),
+            self.context.calibration_request.clone(),
+        ));
+        // Built before anything can fail, so the module is dropped before its library.
+        let mut loaded = LoadedModule {
+            worker: None,
+            retired: Vec::new(),
+            name: name.to_string(),
+            kind: file.kind,
+            module: Arc::new(Mutex::new(module)),
+            streaming: false,
+            host: host.clone(),
+            fatal: false,
+            feeding: Arc::new(AtomicBool::new(false)),
+            descriptor: None,
+            file,
+            _lib: lib,
+            _shadow: shadow,
+        };
+
+        let module = loaded.module.clone();
+        let mut module = lock_module(&module);
+        loaded.descriptor = module.descriptor();
+        match &loaded.descriptor {
+            Some(d) => info!(
+                "Module {}: {} {} by {} ({:?}, {} expressions)",
+                name,
+                d.name,
+                d.version,
+                d.vendor,
+                d.capabilities,
+                d.expressions.len()
+            ),
+            None => debug!("Module {} does not describe itself", name),
+        }
+
+        let schema = loaded
+            .descriptor
+            .as_ref()
+            .and_then(|d| d.settings_schema.clone())
+            .or_else(|| {
+                let manifest = loaded.file.manifest.as_ref()?;
+                manifest.settings_schema.clone()
+            });
+        self.context
+            .settings
+            .write()
+            .unwrap()
+            .set_schema(name, schema);
+        let settings = self.context.settings.read().unwrap().get(name);
+        if let Err(e) = configure_module(module.as_mut(), &settings) {
+            error!(
+                "✗ Failed to configure module {}: {:#}. Using its defaults.",
+                name, e
+            );
+        }
+
+        if let Err(e) = module.initialize(host_services(name, module_logger(name), host)) {
+            module.unload();
+            return Err(e.context("Failed to initialize module"));
+        }
+        drop(module);
+        info!("✓ Initialized module: {}", name);
+
+        if self.hot_reload && loaded.kind == ModuleKind::Native {
+            if let Some(stamp) = stamp {
+                self.watched.insert(
+                    name.to_string(),
+                    WatchedFile {
+                        loaded: stamp,
+                        changed: None,
+                        reactivate: false,
+                    },
+                );
+            }
+        }
+        self.modules.push(loaded);
+        Ok(())
+    }
+
+    /// Unloads `name`, deactivating it first if it is active. Returns whether it was loaded.
+    pub fn unload(&mut self, name: &str) -> bool {
+        let Some(index) = self.modules.iter().position(|m| m.name == name) else {
+            return false;
+        };
+        if self.active.as_deref() == Some(name) {
+            self.deactivate();
+        }
+        self.stop_feeding(name);
+
+        self.pending_settings.remove(name);
+
+        let mut loaded = self.modules.remove(index);
+        loaded
+            .host
+            .connection_changed(ConnectionState::Disconnected);
+        let workers: Vec<PollWorker> = loaded
+            .worker
+            .take()
+            .into_iter()
+            .chain(loaded.retired.drain(..))
+            .collect();
+        let status = self.context.status.clone();
+        if workers.iter().any(|w| w.busy_for().is_some()) {
+            warn!(
+                "Module {} is stuck in update; unloading it once it returns",
+                name
+            );
+            after_workers(workers, move || tear_down(loaded, &status));
+        } else {
+            drop(workers);
+            tear_down(loaded, &status);
+        }
+        self.update_descriptor();
+        self.publish();
+        true
+    }
+
+    /// Unloads `name` if loaded and loads it again, e.g. after its file was replaced.
+    pub fn reload(&mut self, name: &str) -> Result<()> {
+        let was_active = self.active.as_deref() == Some(name);
+        self.unload(name);
+        self.load(name)?;
+        self.restore(name, was_active)
+    }
+
+    /// Makes `name` feed the pipeline instead of the active module, loading it if needed.
+    pub fn activate(&mut self, name: &str) -> Result<()> {
+        self.load(name)?;
+        if self.active.as_deref() == Some(name) {
+            return Ok(());
+        }
+        self.deactivate();
+        self.feed(name)?;
+
+        self.active = Some(name.to_string());
+        self.activated_at = Instant::now();
+        self.context.emitter.set_active(Some(name));
+        self.update_descriptor();
+        info!("✓ Activated module: {}", name);
+        self.publish();
+        Ok(())
+    }
+
+    /// Stops feeding the pipeline from the active module, unless it stands by. It stays
+    /// loaded.
+    pub fn deactivate(&mut self) {
+        let Some(name) = self.active.take() else {
+            return;
+        };
+        if !self.stands_by(&name) {
+            self.stop_feeding(&name);
+        }
+        self.context.emitter.set_active(None);
+        self.update_descriptor();
+        info!("Deactivated module: {}", name);
+        self.publish();
+    }
+
+    /// Loads the modules named in `module.sources` and the failover chain, and feeds the
+    /// pipeline from them next to the active module. Failures are logged and listed by the
+    /// HTTP API.
+    ///
+    /// Only one VRCFT module can run, since starting another runtime stops the one running.
+    /// VRCFT modules after the active one, or after the first one named, are skipped.
+    pub fn start_standby(&mut self) {
+        let mut names: Vec<String> = self
+            .sources
+            .modules()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        for name in &self.chain {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        let mut vrcft = self.active.clone().filter(|name| self.is_vrcft(name));
+        for name in names {
+            if self.active.as_deref() == Some(name.as_str()) {
+                continue;
+            }
+            if self.is_vrcft(&name) {
+                if let Some(running) = &vrcft {
+                    let message = format!(
+                        "Only one VRCFT module can run at a time, and {} does",
+                        running
+                    );
+                    error!("✗ Skipped module {}: {}", name, message);
+                    let mut control = self.control.write().unwrap();
+                    control.errors.insert(name.clone(), message);
+                    continue;
+                }
+                vrcft = Some(name.clone());
+            }
+            if self.load(&name).is_err() {
+                continue;
+            }
+            match self.feed(&name) {
+                Ok(()) if self.sources.is_source(&name) => {
+                    info!("✓ Module {} feeds the pipeline as a source", name)
+                }
+                Ok(()) => info!("✓ Module {} stands by in the failover chain", name),
+                Err(e) => error!("✗ Failed to start module {}: {:#}", name, e),
+            }
+        }
+        self.update_descriptor();
+        self.publish();
+    }
+
+    fn is_vrcft(&self, name: &str) -> bool {
+        self.find(name)
+            .is_some_and(|file| file.kind == ModuleKind::Vrcft)
+    }
+
+    /// Whether `name` keeps running while it is not active: a source, or a module of the
+    /// failover chain.
+    fn stands_by(&self, name: &str) -> bool {
+        self.sources.is_source(name) || self.chain.iter().any(|m| m == name)
+    }
+
+    /// Switches to another module of the failover chain when the active one has delivered no
+    /// frames for `module.failover_after_ms`, and back to a preferred one once it has
+    /// delivered frames for as long. Does nothing while the active module is not part of the
+    /// chain, e.g. after one was activated or deactivated through the HTTP API.
+    pub fn fail_over(&mut self) {
+        if self.chain.len() < 2 {
+            return;
+        }
+        let Some(active) = self.active.clone() else {
+            return;
+        };
+        let Some(position) = self.chain.iter().position(|m| *m == active) else {
+            return;
+        };
+        let emitter = &self.context.emitter;
+        let silent_for = emitter
+            .freshness(&active)
+            .map_or(Duration::MAX, |f| f.age)
+            .min(self.activated_at.elapsed());
+
+        let next = if silent_for > self.failover_after {
+            let delivering = self.chain.iter().find(|m| {
+                **m != active && emitter.freshness(m).is_some_and(|f| f.age <= STALE_AFTER)
+            });
+            if let Some(next) = delivering {
+                warn!(
+                    "Module {} has delivered no frames for {:?}; falling over to {}",
+                    active, silent_for, next
+                );
+            }
+            delivering
+        } else {
+            let recovered = self.chain[..position].iter().find(|m| {
+                emitter
+                    .freshness(m)
+                    .is_some_and(|f| f.streak >= self.failover_after)
+            });
+            if let Some(next) = recovered {
+                info!(
+                    "Module {} has recovered; switching back from {}",
+                    next, active
+                );
+            }
+            recovered
+        };
+        let Some(next) = next.cloned() else {
+            return;
+        };
+
+        self.context.emitter.crossfade(self.crossfade);
+        if let Err(e) = self.activate(&next) {
+            error!("✗ Failed to switch to module {}: {:#}", next, e);
+        }
+    }
+
+    /// Starts feeding the pipeline from loaded module `name`: its stream is let through, or
+    /// a worker starts polling it.
+    fn feed(&mut self, name: &str) -> Result<()> {
+        let emitter = self.context.emitter.clone();
+        let loaded = self
+            .modules
+            .iter_mut()
+            .find(|m| m.name == name)
+            .context("Module is not loaded")?;
+        if loaded.feeding.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        // Workers of earlier activations that have exited are joined without waiting.
+        loaded.retired.extend(loaded.worker.take());
+        loaded.retired.retain(|worker| !worker.is_finished());
+        let started = contain(|| {
+            if !loaded.streaming {
+                // The stream keeps running while the module is not fed; its frames are dropped.
+                let emitter_sink = emitter.sink(name);
+                let feeding = loaded.feeding.clone();
+                let host = loaded.host.clone();
+                let sink = FrameSink::new(move |data| {
+                    if !feeding.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    host.with_metrics(|metrics| metrics.add(HOST_FRAMES, 1));
+                    emitter_sink.submit(data);
+                });
+                // A module still in an earlier worker's `update` was polled, so it is polled
+                // again.
+                if let Some(mut module) = try_lock_module(&loaded.module) {
+                    loaded.streaming = module.start_streaming(sink);
+                }
+                if loaded.streaming {
+                    info!("Module {} is streaming frames", name);
+                }
+            }
+        });
+        if let Err(message) = started {
+            loaded.feeding.store(false, Ordering::SeqCst);
+            self.fault(name, "start_streaming", message);
+            bail!(
+                "Module {} panicked while starting to feed the pipeline",
+                name
+            );
+        }
+        if !loaded.streaming && !loaded.fatal {
+            loaded.worker = Some(PollWorker::spawn(
+                name,
+                loaded.module.clone(),
+                loaded.host.clone(),
+                emitter,
+                self.update_deadline,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Stops feeding the pipeline from `name`. It stays loaded.
+    fn stop_feeding(&mut self, name: &str) {
+        let Some(loaded) = self.modules.iter().find(|m| m.name == name) else {
+            return;
+        };
+        loaded.feeding.store(false, Ordering::SeqCst);
+        if let Some(worker) = &loaded.worker {
+            // Not joined, so a module stuck in `update` cannot hold up a switch.
+            worker.stop();
+        }
+        if self.stalls.remove(name).is_some() {
+            loaded.host.set_stalled(false);
+        }
+    }
+
+    /// Puts a module that was loaded again back to feeding the pipeline, as the active module
+    /// if it was, or else standing by if it does.
+    fn restore(&mut self, name: &str, was_active: bool) -> Result<()> {
+        if was_active {
+            self.activate(name)
+        } else if self.stands_by(name) {
+            self.feed(name)?;
+            self.update_descriptor();
+            self.publish();
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Only the modules feeding the pipeline decide what is tracked, each for the channels
+    /// it owns.
+    fn update_descriptor(&self) {
+        let descriptors: Vec<(&str, Option<ModuleDescriptor>)> = self
+            .modules
+            .iter()
+            .filter(|m| m.feeding.load(Ordering::SeqCst))
+            .map(|m| (m.name.as_str(), m.descriptor.clone()))
+            .collect();
+        *self.context.descriptor.write().unwrap() = self
+            .sources
+            .descriptor(self.active.as_deref(), &descriptors);
+    }
+
+    /// Applies commands queued through the HTTP API, and settings that waited for their
+    /// module to return from `update`.
+    pub fn apply_pending(&mut self) {
+        self.configure_pending();
+        let pending = self.control.write().unwrap().take_pending();
+        for command in pending {
+            debug!("Applying {:?}", command);
+            // Acting on a module by hand takes it out of automatic restarts.
+            let name = command.module();
+            match &command {
+                ModuleCommand::Deactivate(_) => {
+                    if let Some(supervision) = self.supervision.get_mut(name) {
+                        supervision.reactivate = false;
+                    }
+                }
+                _ => {
+                    self.supervision.remove(name);
+                    self.with_faults(name, |faults| faults.gave_up = false);
+                }
+            }
+            // Failures are logged and listed by `load`.
+            let _ = match &command {
+                ModuleCommand::Activate(name) => self.activate(name),
+                ModuleCommand::Deactivate(name) => {
+                    if self.active.as_deref() == Some(name.as_str()) {
+                        self.deactivate();
+                    }
+                    if self.stands_by(name) {
+                        self.stop_feeding(name);
+                        self.update_descriptor();
+                        self.publish();
+                    }
+                    Ok(())
+                }
+                ModuleCommand::Unload(name) => {
+                    // Unloaded on purpose, so a rebuild should not bring it back.
+                    self.watched.remove(name);
+                    self.unload(name);
+                    Ok(())
+                }
+                ModuleCommand::Reload(name) => self.reload(name),
+            };
+        }
+    }
+
+    /// Reloads native modules whose library file was replaced. Does nothing unless hot
+    /// reload is on; checks at most once per `HOT_RELOAD_POLL_INTERVAL`.
+    pub fn check_for_changes(&mut self) {
+        if !self.hot_reload || self.last_check.elapsed() < HOT_RELOAD_POLL_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let files = self.control.read().unwrap().discover();
+        let mut ready = Vec::new();
+        for (name, watched) in &mut self.watched {
+            let file = files.iter().find(|f| f.name == *name);
+            let Some(current) = file.and_then(|f| FileStamp::of(&f.path)) else {
+                continue;
+            };
+            if current == watched.loaded {
+                watched.changed = None;
+            } else if watched.changed == Some(current) {
+                ready.push(name.clone());
+            } else {
+                debug!("Module {} changed on disk; waiting for it to settle", name);
+                watched.changed = Some(current);
+            }
+        }
+
+        for name in ready {
+            info!("Module {} changed on disk. Reloading...", name);
+            let reactivate = self.active.as_deref() == Some(name.as_str())
+                || self.watched.get(&name).is_some_and(|w| w.reactivate);
+            self.unload(&name);
+            // A successful load records the new stamp; on failure, wait for the next change.
+            let mut result = self.load(&name);
+            if result.is_ok() {
+                result = self.restore(&name, reactivate);
+            }
+            if let Err(e) = result {
+                if let Some(watched) = self.watched.get_mut(&name) {
+                    watched.loaded = watched.changed.take().unwrap_or(watched.loaded);
+                    watched.reactivate = reactivate;
+                }
+                debug!("Hot reload of {} failed: {:#}", name, e);
+            }
+        }
+    }
+
+    /// Checks on the threads polling the modules that feed the pipeline. A panic in `update`
+    /// faults the module; an `update` that overruns the deadline is logged and shown in the
+    /// module's status, and its channels are fed according to `on_stall` until it returns.
+    pub fn watch(&mut self) {
+        let mut faults = Vec::new();
+        let mut workers = Vec::new();
+        for loaded in &mut self.modules {
+            if !loaded.feeding.load(Ordering::SeqCst) {
+                continue;
+            }
+            let Some(worker) = &loaded.worker else {
+                continue;
+            };
+            if let Some(message) = worker.take_fault() {
+                faults.push((loaded.name.clone(), message));
+                continue;
+            }
+            let busy = worker.busy_for();
+            if worker.is_fatal() {
+                loaded.fatal = true;
+            }
+            workers.push((loaded.name.clone(), loaded.host.clone(), busy));
+        }
+
+        for (name, message) in faults {
+            self.fault(&name, "update", message);
+        }
+        for (name, host, busy) in workers {
+            self.check_stall(&name, &host, busy);
+        }
+    }
+
+    fn check_stall(&mut self, name: &str, host: &ModuleHost, busy: Option<Duration>) {
+        match busy.filter(|busy| *busy > self.update_deadline) {
+            Some(busy) => {
+                if !self.stalls.contains_key(name) {
+                    warn!(
+                        "Module {} has been stuck in update for {:?}; {} its last frame until it \
+                         returns",
+                        name,
+                        busy,
+                        match self.on_stall {
+                            StallBehavior::Hold => "holding",
+                            StallBehavior::Decay => "relaxing",
+                        }
+                    );
+                    host.set_stalled(true);
+                    self.stalls.insert(
+                        name.to_string(),
+                        Stall {
+                            since: Instant::now(),
+                            held: self.context.emitter.last_frame(),
+                        },
+                    );
+                }
+                // Holding needs nothing here: the consumer repeats the last frame on its own,
+                // and merged output keeps the module's channels.
+                if self.on_stall == StallBehavior::Decay {
+                    if let Some(mut frame) = self.stalls[name].held.clone() {
+                        let elapsed = self.stalls[name].since.elapsed().as_secs_f32();
+                        let amount = 1.0 - (-elapsed / STALL_DECAY_TIME.as_secs_f32()).exp();
+                        decay_toward_neutral(&mut frame, amount);
+                        frame.timestamp_us = 0;
+                        self.context.emitter.emit(name, &mut frame);
+                    }
+                }
+            }
+            None => {
+                if let Some(stall) = self.stalls.remove(name) {
+                    info!(
+                        "Module {} returned from update after being stuck for {:?}",
+                        name,
+                        stall.since.elapsed() + self.update_deadline
+                    );
+                    host.set_stalled(false);
+                }
+            }
+        }
+    }
+
+    /// Records a panic in `call` of `name`, unloads the module and schedules its restart.
+    fn fault(&mut self, name: &str, call: &str, message: String) {
+        error!("✗ Module {} panicked in {}: {}", name, call, message);
+        self.record_fault(name, call, &message);
+        let was_active = self.active.as_deref() == Some(name);
+        self.unload(name);
+        self.schedule_restart(name, was_active);
+    }
+
+    fn record_fault(&self, name: &str, call: &str, message: &str) {
+        record_fault(&self.context.status, name, call, message);
+    }
+
+    fn with_faults(&self, name: &str, f: impl FnOnce(&mut FaultHistory)) {
+        with_faults(&self.context.status, name, f);
+    }
+
+    fn schedule_restart(&mut self, name: &str, reactivate: bool) {
+        let policy = self.restart_policy;
+        let supervision = self.supervision.entry(name.to_string()).or_default();
+        if supervision
+            .restarted
+            .is_some_and(|t| t.elapsed() >= policy.stable_after)
+        {
+            supervision.attempts = 0;
+        }
+        supervision.reactivate |= reactivate;
+
+        if supervision.attempts >= policy.max_attempts {
+            error!(
+                "✗ Module {} faulted {} times in a row; giving up. Reload it through the HTTP API \
+                 to try again.",
+                name, supervision.attempts
+            );
+            self.supervision.remove(name);
+            self.with_faults(name, |faults| faults.gave_up = true);
+            return;
+        }
+        let delay = policy.delay(supervision.attempts);
+        info!(
+            "Restarting module {} in {:?} (attempt {} of {})",
+            name,
+            delay,
+            supervision.attempts + 1,
+            policy.max_attempts
+        );
+        supervision.due = Some(Instant::now() + delay);
+    }
+
+    /// Recreates faulted modules whose restart delay has passed.
+    pub fn supervise(&mut self) {
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .supervision
+            .iter()
+            .filter(|(_, s)| s.due.is_some_and(|due| due <= now))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in due {
+            let Some(supervision) = self.supervision.get_mut(&name) else {
+                continue;
+            };
+            supervision.due = None;
+            supervision.attempts += 1;
+            supervision.restarted = Some(now);
+            let reactivate = supervision.reactivate;
+            self.with_faults(&name, |faults| faults.restarts += 1);
+
+            let mut result = self.load(&name);
+            if result.is_ok() {
+                result = self.restore(&name, reactivate);
+            }
+            match result {
+                Ok(()) => {
+                    info!("✓ Restarted module {}", name);
+                    if let Some(supervision) = self.supervision.get_mut(&name) {
+                        supervision.reactivate = false;
+                    }
+                }
+                // A fault while activating has already been rescheduled.
+                Err(_) if self.supervision.get(&name).is_some_and(|s| s.due.is_none()) => {
+                    self.schedule_restart(&name, reactivate);
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Hands new settings to `name` if it is loaded. On failure the module keeps its
+    /// previous settings. While a worker is in the module's `update`, the settings wait for
+    /// a later [`apply_pending`](Self::apply_pending).
+    pub fn apply_settings(&mut self, name: &str, settings: &ModuleSettings) {
+        if !self.is_loaded(name) {
+            return;
+        }
+        self.pending_settings
+            .insert(name.to_string(), settings.clone());
+        self.configure_pending();
+    }
+
+    /// Hands waiting settings to the modules that are not in `update`.
+    fn configure_pending(&mut self) {
+        for (name, settings) in std::mem::take(&mut self.pending_settings) {
+            let Some(loaded) = self.modules.iter().find(|m| m.name == name) else {
+                continue;
+            };
+            let Some(mut module) = try_lock_module(&loaded.module) else {
+                debug!("Module {} is in update; configuring it later", name);
+                self.pending_settings.insert(name, settings);
+                continue;
+            };
+            let result = contain(|| configure_module(module.as_mut(), &settings));
+            drop(module);
+            match result {
+                Ok(Ok(())) => info!("✓ Applied new settings to module {}", name),
+                Ok(Err(e)) => error!("✗ Failed to apply settings to module {}: {:#}", name, e),
+                Err(message) => self.fault(&name, "configure", message),
+            }
+        }
+    }
+
+    /// Unloads every module, e.g. on shutdown.
+    pub fn unload_all(&mut self) {
+        let names: Vec<String> = self.modules.iter().map(|m| m.name.clone()).collect();
+        for name in names {
+            self.unload(&name);
+        }
+    }
+
+    /// Mirrors the loaded modules into the control the HTTP API reads.
+    fn publish(&self) {
+        let mut control = self.control.write().unwrap();
+        control.active = self.active.clone();
+        control.feeding = self
+            .modules
+            .iter()
+            .filter(|m| m.feeding.load(Ordering::SeqCst))
+            .map(|m| m.name.clone())
+            .collect();
+        control.loaded = self
+            .modules
+            .iter()
+            .map(|m| (m.file.clone(), m.streaming))
+            .collect();
+    }
+}
+
+/// Unloads and destroys a module whose workers have stopped, recording panics as faults.
+fn tear_down(loaded: LoadedModule, status: &SharedModuleStatus) {
+    let name = loaded.name.clone();
+    if let Err(message) = contain(|| lock_module(&loaded.module).unload()) {
+        error!("✗ Module {} panicked in unload: {}", name, message);
+        record_fault(status, &name, "unload", &message);
+    }
+    if let Err(message) = contain(move || drop(loaded)) {
+        error!(
+            "✗ Module {} panicked while being destroyed: {}",
+            name, message
+        );
+        record_fault(status, &name, "destroy", &message);
+    }
+    info!("✓ Unloaded module: {}", name);
+}
+
+fn record_fault(status: &SharedModuleStatus, name: &str, call: &str, message: &str) {
+    with_faults(status, name, |faults| {
+        faults.record(ModuleFault {
+            timestamp_us: timestamp_us_now(),
+            call: call.to_string(),
+            message: message.to_string(),
+        })
+    });
+}
+
+fn with_faults(status: &SharedModuleStatus, name: &str, f: impl FnOnce(&mut FaultHistory)) {
+    let mut status = status.write().unwrap();
+    f(&mut status.entry(name.to_string()).or_default().faults);
+}
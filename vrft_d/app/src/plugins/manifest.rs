@@ -0,0 +1,98 @@
+//! Plugin manifests: a `plugin.json` in a plugin's own folder describes the module, so it
+//! can be listed, checked and configured without loading its library.
+
+use anyhow::{bail, Context, Result};
+use api::abi::VRFT_ABI_VERSION;
+use common::ModuleRuntime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name of a manifest, in a folder of its own under a plugin directory.
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// What a plugin declares about itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// The module name used in the config and the HTTP API.
+    pub id: String,
+    #[serde(default)]
+    pub version: String,
+    /// The [`VRFT_ABI_VERSION`] a native library was built against.
+    #[serde(default)]
+    pub abi_version: Option<u32>,
+    /// Operating systems the plugin runs on, as in `std::env::consts::OS`; empty for all.
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default = "default_runtime")]
+    pub runtime: ModuleRuntime,
+    /// The library, relative to the manifest.
+    pub library: LibraryPath,
+    /// JSON Schema for the module's settings, used until the module describes itself.
+    #[serde(default)]
+    pub settings_schema: Option<Value>,
+}
+
+fn default_runtime() -> ModuleRuntime {
+    ModuleRuntime::Native
+}
+
+/// One library for every platform, or one per platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LibraryPath {
+    Any(String),
+    /// Keyed by `std::env::consts::OS`.
+    PerPlatform(BTreeMap<String, String>),
+}
+
+impl PluginManifest {
+    pub fn read(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let manifest: Self =
+            serde_json::from_str(&text).with_context(|| format!("Invalid manifest {:?}", path))?;
+        if manifest.id.is_empty() {
+            bail!("Manifest {:?} has no id", path);
+        }
+        Ok(manifest)
+    }
+
+    /// The library for this platform, relative to the manifest.
+    pub fn library(&self) -> Option<&str> {
+        match &self.library {
+            LibraryPath::Any(path) => Some(path),
+            LibraryPath::PerPlatform(paths) => paths.get(std::env::consts::OS).map(String::as_str),
+        }
+    }
+
+    /// Checks that the plugin in `dir` can run here, and returns its library.
+    pub fn check(&self, dir: &Path) -> Result<PathBuf> {
+        let os = std::env::consts::OS;
+        if !self.platforms.is_empty() && !self.platforms.iter().any(|p| p == os) {
+            bail!(
+                "Plugin does not support {} (only {})",
+                os,
+                self.platforms.join(", ")
+            );
+        }
+        if self.runtime == ModuleRuntime::Native {
+            if let Some(version) = self.abi_version.filter(|v| *v != VRFT_ABI_VERSION) {
+                bail!(
+                    "ABI version mismatch: plugin was built for version {}, host expects {}",
+                    version,
+                    VRFT_ABI_VERSION
+                );
+            }
+        }
+        let library = self
+            .library()
+            .with_context(|| format!("Plugin has no library for {}", os))?;
+        let path = dir.join(library);
+        if !path.is_file() {
+            bail!("Plugin library {:?} not found", path);
+        }
+        Ok(path)
+    }
+}
@@ -0,0 +1,82 @@
+//! Per-module counters and gauges, published by modules through `api::HostServices` and by
+//! the host itself for each module (prefixed with `host.`).
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Frames the module delivered, polled or streamed.
+pub const HOST_FRAMES: &str = "host.frames";
+
+/// Polls that returned no frame, e.g. because the device had nothing new.
+pub const HOST_EMPTY_POLLS: &str = "host.empty_polls";
+
+/// How long each `update` call took, in milliseconds.
+pub const HOST_UPDATE_MS: &str = "host.update_ms";
+
+/// `update` calls that took longer than `module.update_deadline_ms`.
+pub const HOST_DEADLINE_MISSES: &str = "host.deadline_misses";
+
+/// Last value and range of a gauge since the last reset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Gauge {
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub samples: u64,
+}
+
+impl Gauge {
+    fn new(value: f64) -> Self {
+        Self {
+            value,
+            min: value,
+            max: value,
+            mean: value,
+            samples: 1,
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        self.samples += 1;
+        self.value = value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.mean += (value - self.mean) / self.samples as f64;
+    }
+}
+
+/// Metrics of one module.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModuleMetrics {
+    pub counters: BTreeMap<String, u64>,
+    pub gauges: BTreeMap<String, Gauge>,
+}
+
+impl ModuleMetrics {
+    pub fn add(&mut self, name: &str, delta: u64) {
+        match self.counters.get_mut(name) {
+            Some(total) => *total = total.saturating_add(delta),
+            None => {
+                self.counters.insert(name.to_string(), delta);
+            }
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        match self.gauges.get_mut(name) {
+            Some(gauge) => gauge.record(value),
+            None => {
+                self.gauges.insert(name.to_string(), Gauge::new(value));
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.counters.clear();
+        self.gauges.clear();
+    }
+}
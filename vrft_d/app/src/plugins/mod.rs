@@ -0,0 +1,13 @@
+pub mod conformance;
+pub mod emitter;
+pub mod faults;
+pub mod fusion;
+pub mod host;
+pub mod integrity;
+pub mod manager;
+pub mod manifest;
+pub mod metrics;
+pub mod native;
+pub mod sandbox;
+pub mod settings;
+pub mod worker;
@@ -0,0 +1,167 @@
+//! Loading of native (.dll/.so/.dylib) tracking modules through the C ABI in `api::abi`.
+
+use crate::plugins::integrity::encode_hex;
+use anyhow::{bail, Context, Result};
+use api::abi::{
+    AbiModule, AbiVersionFn, CreateModuleFn, VrftModuleVTable, ABI_VERSION_SYMBOL,
+    CREATE_MODULE_SYMBOL, VRFT_ABI_VERSION, VRFT_OK,
+};
+use api::TrackingModule;
+use libloading::Library;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// File extensions recognised as native module libraries.
+pub fn is_native_library(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "dll" || ext == "so" || ext == "dylib")
+}
+
+/// Loads `path`, checks its ABI version and creates one module instance.
+///
+/// The returned `Library` must outlive the module.
+pub fn load_native_module(path: &Path) -> Result<(Box<dyn TrackingModule + Send>, Library)> {
+    unsafe {
+        let lib = Library::new(path).context("Failed to load library")?;
+
+        let abi_version: AbiVersionFn = *lib.get::<AbiVersionFn>(ABI_VERSION_SYMBOL).context(
+            "Library does not export vrft_abi_version; rebuild it against the current api crate",
+        )?;
+        let module_version = abi_version();
+        if module_version != VRFT_ABI_VERSION {
+            bail!(
+                "ABI version mismatch: module was built for version {}, host expects {}",
+                module_version,
+                VRFT_ABI_VERSION
+            );
+        }
+
+        let create: CreateModuleFn = *lib
+            .get::<CreateModuleFn>(CREATE_MODULE_SYMBOL)
+            .context("Library does not export vrft_create_module")?;
+
+        let mut vtable = VrftModuleVTable::empty();
+        let code = create(&mut vtable);
+        if code != VRFT_OK {
+            bail!("vrft_create_module returned {}", code);
+        }
+
+        let module = AbiModule::from_vtable(vtable).context("Invalid module table")?;
+        Ok((Box::new(module), lib))
+    }
+}
+
+/// The daemon's own directory for shadow copies: under `XDG_RUNTIME_DIR` or the user's
+/// cache directory, local app data on Windows, or the temporary directory if none is set.
+pub fn shadow_dir() -> PathBuf {
+    let base = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+    };
+    base.unwrap_or_else(std::env::temp_dir)
+        .join("vrft_d")
+        .join("shadow")
+}
+
+/// A copy of a module library that is loaded instead of the original, so the original can
+/// be rebuilt while the module runs. The copy is deleted on drop; drop it after the library.
+#[derive(Debug)]
+pub struct ShadowCopy {
+    path: PathBuf,
+}
+
+impl ShadowCopy {
+    /// Copies `original` into `dir` under a random name, so the loader never hands back a
+    /// stale image of the same file and nobody can place a file there in advance. `dir` is
+    /// created private to the user; on Unix, the copy is refused if anyone else could
+    /// replace it, see [`check_private`].
+    pub fn create(original: &Path, dir: &Path) -> Result<Self> {
+        let stem = original
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .context("Module path has no file name")?;
+        let mut random = [0u8; 16];
+        SystemRandom::new()
+            .fill(&mut random)
+            .map_err(|_| anyhow::anyhow!("Failed to generate a shadow copy name"))?;
+        let mut name = format!("{}.{}", stem, encode_hex(&random));
+        if let Some(ext) = original.extension().and_then(|e| e.to_str()) {
+            name = format!("{}.{}", name, ext);
+        }
+
+        create_private_dir(dir).context("Failed to create shadow directory")?;
+        let path = dir.join(name);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut copy = options
+            .open(&path)
+            .with_context(|| format!("Failed to create {:?}", path))?;
+        // From here on the copy exists and is removed again on failure.
+        let shadow = Self { path };
+        #[cfg(unix)]
+        check_private(dir, &copy)?;
+        let mut source =
+            File::open(original).with_context(|| format!("Failed to open {:?}", original))?;
+        std::io::copy(&mut source, &mut copy)
+            .with_context(|| format!("Failed to copy {:?} to {:?}", original, shadow.path))?;
+        Ok(shadow)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ShadowCopy {
+    fn drop(&mut self) {
+        // Fails on Windows if the loader kept the image mapped; the directory is cleared on
+        // the next start.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Creates `dir` and its missing parents, readable only by the user on Unix.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// Fails unless only the owner of `copy`, or root, can replace files in `dir`: `dir` must
+/// be owned by them and not writable by group or others, and every directory above it
+/// owned by them or root and not writable by others unless it is sticky, like `/tmp`.
+#[cfg(unix)]
+fn check_private(dir: &Path, copy: &File) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = copy.metadata()?.uid();
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {:?}", dir))?;
+    for (i, ancestor) in dir.ancestors().enumerate() {
+        let metadata = std::fs::metadata(ancestor)?;
+        let writable_by_others = metadata.mode() & 0o022 != 0;
+        let sticky = metadata.mode() & 0o1000 != 0;
+        if metadata.uid() != uid && (i == 0 || metadata.uid() != 0) {
+            bail!(
+                "{:?} is owned by another user; shadow copies need a directory of their own",
+                ancestor
+            );
+        }
+        if writable_by_others && (i == 0 || !sticky) {
+            bail!(
+                "{:?} is writable by other users; shadow copies need a directory of their own",
+                ancestor
+            );
+        }
+    }
+    Ok(())
+}
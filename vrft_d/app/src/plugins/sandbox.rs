@@ -0,0 +1,508 @@
+//! Out-of-process native modules.
+//!
+//! A sandboxed module is loaded by a `vrft_module_host` child process instead of the daemon,
+//! so a module that crashes or hangs only takes its host down. The child connects back over a
+//! loopback socket and streams frames and host service calls as JSON lines;
+//! [`SandboxModule`] forwards them and restarts the child with backoff when it dies, like
+//! `ProxyModule` does for the .NET runtime. Any local process can connect to the socket, so
+//! the child greets with a random token it was handed in [`TOKEN_ENV`].
+
+use crate::plugins::integrity::encode_hex;
+use anyhow::{bail, Context, Result};
+use api::{
+    ConnectionState, HostServices, ModuleDescriptor, ModuleSettings, TrackingModule, TrackingState,
+    UnifiedTrackingData,
+};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// File name of the child host binary, without the platform's executable suffix.
+pub const HOST_EXE_NAME: &str = "vrft_module_host";
+
+/// Environment variable holding the token the child greets with. Unlike the command line,
+/// the environment of a process is not visible to other users.
+pub const TOKEN_ENV: &str = "VRFT_MODULE_HOST_TOKEN";
+
+/// How long the child may take to connect and load the module.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the child may take to answer a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The child sends something at least once per `HEARTBEAT_INTERVAL`.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A child that stays silent this long is considered hung and is restarted.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Restarts wait this long, doubling with each crash up to `MAX_RESTART_DELAY`.
+const MIN_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// A child that runs this long without crashing resets the backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+/// The child host next to the running executable.
+pub fn host_exe() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to locate the running executable")?;
+    Ok(exe.with_file_name(format!("{}{}", HOST_EXE_NAME, std::env::consts::EXE_SUFFIX)))
+}
+
+/// What the daemon sends to the child.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostMessage {
+    Configure { settings: ModuleSettings },
+    Initialize { state_dir: Option<PathBuf> },
+    Shutdown,
+}
+
+/// What the child sends to the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChildMessage {
+    /// Sent once, first, after loading the module; `error` says why that failed.
+    Hello {
+        pid: u32,
+        /// The token from [`TOKEN_ENV`].
+        token: String,
+        descriptor: Option<ModuleDescriptor>,
+        error: Option<String>,
+    },
+    /// Answers a `Configure` or `Initialize`.
+    Reply {
+        error: Option<String>,
+    },
+    /// A polled or streamed frame.
+    Frame {
+        data: Box<UnifiedTrackingData>,
+    },
+    /// `update` returned something other than a new frame; sent when it changes.
+    State {
+        state: TrackingState,
+    },
+    Heartbeat,
+    Connection {
+        state: ConnectionState,
+    },
+    Recalibrate,
+    Gauge {
+        name: String,
+        value: f64,
+    },
+    Counter {
+        name: String,
+        delta: u64,
+    },
+}
+
+/// Writes `message` as one JSON line.
+pub fn write_message(writer: &mut impl Write, message: &impl Serialize) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads JSON lines from `stream` on a new thread. The receiver disconnects when the stream
+/// closes or sends something unreadable.
+pub fn spawn_reader<T>(stream: TcpStream, name: &str) -> Result<Receiver<T>>
+where
+    T: for<'de> Deserialize<'de> + Send + 'static,
+{
+    let (tx, rx) = channel();
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str(&line) {
+                    Ok(message) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Unreadable message from the other side: {}", e);
+                        break;
+                    }
+                }
+            }
+        })
+        .context("Failed to start reader thread")?;
+    Ok(rx)
+}
+
+/// A running child host.
+struct Connection {
+    child: Child,
+    writer: TcpStream,
+    messages: Receiver<ChildMessage>,
+}
+
+impl Connection {
+    fn stop(mut self) {
+        let _ = write_message(&mut self.writer, &HostMessage::Shutdown);
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A native module run by a `vrft_module_host` child process.
+pub struct SandboxModule {
+    host_exe: PathBuf,
+    module_path: PathBuf,
+    connection: Option<Connection>,
+    descriptor: Option<ModuleDescriptor>,
+    /// Replayed to a restarted child.
+    settings: ModuleSettings,
+    services: Option<HostServices>,
+    frame: Option<Box<UnifiedTrackingData>>,
+    state: TrackingState,
+    last_message: Instant,
+    started: Instant,
+    /// Crashes and failed restarts since the child last ran stably.
+    crashes: u32,
+    next_restart: Option<Instant>,
+}
+
+impl SandboxModule {
+    pub fn new(host_exe: &Path, module_path: &Path) -> Self {
+        Self {
+            host_exe: host_exe.to_path_buf(),
+            module_path: module_path.to_path_buf(),
+            connection: None,
+            descriptor: None,
+            settings: ModuleSettings::default(),
+            services: None,
+            frame: None,
+            state: TrackingState::Reconnecting,
+            last_message: Instant::now(),
+            started: Instant::now(),
+            crashes: 0,
+            next_restart: None,
+        }
+    }
+
+    /// Starts the child and waits until it has loaded the module.
+    pub fn start(&mut self) -> Result<()> {
+        if !self.host_exe.exists() {
+            bail!("{} not found at {:?}", HOST_EXE_NAME, self.host_exe);
+        }
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .context("Failed to listen for the module host")?;
+        let port = listener.local_addr()?.port();
+        let mut token = [0u8; 16];
+        SystemRandom::new()
+            .fill(&mut token)
+            .map_err(|_| anyhow::anyhow!("Failed to generate a token for the module host"))?;
+        let token = encode_hex(&token);
+
+        let mut child = Command::new(&self.host_exe)
+            .arg(&self.module_path)
+            .arg(port.to_string())
+            .arg(log::max_level().as_str())
+            .env(TOKEN_ENV, &token)
+            .spawn()
+            .with_context(|| format!("Failed to spawn {}", HOST_EXE_NAME))?;
+
+        let (writer, messages, descriptor) = match Self::handshake(&listener, &mut child, &token) {
+            Ok(handshake) => handshake,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
+        log::info!(
+            "Module {:?} runs in {} (PID {})",
+            self.module_path.file_name().unwrap_or_default(),
+            HOST_EXE_NAME,
+            child.id()
+        );
+        self.connection = Some(Connection {
+            child,
+            writer,
+            messages,
+        });
+        self.descriptor = descriptor;
+        self.last_message = Instant::now();
+        self.started = Instant::now();
+        Ok(())
+    }
+
+    /// Waits for the child to connect and greet with `token`. Connections that greet
+    /// otherwise are dropped.
+    fn handshake(
+        listener: &TcpListener,
+        child: &mut Child,
+        token: &str,
+    ) -> Result<(TcpStream, Receiver<ChildMessage>, Option<ModuleDescriptor>)> {
+        listener.set_nonblocking(true)?;
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            let stream = Self::accept(listener, child, deadline)?;
+            let messages = spawn_reader(stream.try_clone()?, "sandbox-reader")?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match messages.recv_timeout(remaining) {
+                Ok(ChildMessage::Hello {
+                    pid,
+                    token: greeted_with,
+                    descriptor,
+                    error,
+                }) if greeted_with == token => {
+                    if pid != child.id() {
+                        bail!("Module host reported PID {}, expected {}", pid, child.id());
+                    }
+                    if let Some(error) = error {
+                        bail!(error);
+                    }
+                    return Ok((stream, messages, descriptor));
+                }
+                Err(RecvTimeoutError::Timeout) => bail!(
+                    "{} did not load the module within {:?}",
+                    HOST_EXE_NAME,
+                    STARTUP_TIMEOUT
+                ),
+                // The child exiting is noticed while accepting.
+                Err(RecvTimeoutError::Disconnected) => {}
+                Ok(_) => {
+                    log::warn!("Dropped a connection that did not greet as the module host");
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+            }
+        }
+    }
+
+    /// Accepts the next connection, until the child exits or `deadline` passes.
+    fn accept(listener: &TcpListener, child: &mut Child, deadline: Instant) -> Result<TcpStream> {
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+                    stream.set_nodelay(true)?;
+                    return Ok(stream);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e).context("Failed to accept the module host"),
+            }
+            if let Some(status) = child.try_wait()? {
+                bail!(
+                    "{} exited with {} before loading the module",
+                    HOST_EXE_NAME,
+                    status
+                );
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "{} did not connect within {:?}",
+                    HOST_EXE_NAME,
+                    STARTUP_TIMEOUT
+                );
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Sends `message` and waits for the child's reply, handling whatever arrives meanwhile.
+    fn request(&mut self, message: HostMessage) -> Result<()> {
+        let connection = self
+            .connection
+            .as_mut()
+            .context("Module host is not running")?;
+        write_message(&mut connection.writer, &message)
+            .context("Failed to send to the module host")?;
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            let connection = self
+                .connection
+                .as_mut()
+                .context("Module host is not running")?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match connection.messages.recv_timeout(remaining) {
+                Ok(ChildMessage::Reply { error }) => {
+                    self.last_message = Instant::now();
+                    return match error {
+                        Some(error) => Err(anyhow::anyhow!(error)),
+                        None => Ok(()),
+                    };
+                }
+                Ok(other) => self.handle(other),
+                Err(RecvTimeoutError::Timeout) => {
+                    bail!("Module host did not answer within {:?}", REPLY_TIMEOUT)
+                }
+                Err(RecvTimeoutError::Disconnected) => bail!("Module host exited"),
+            }
+        }
+    }
+
+    fn handle(&mut self, message: ChildMessage) {
+        self.last_message = Instant::now();
+        match message {
+            ChildMessage::Frame { data } => {
+                self.frame = Some(data);
+                self.state = TrackingState::NoNewFrame;
+            }
+            ChildMessage::State { state } => self.state = state,
+            ChildMessage::Heartbeat | ChildMessage::Hello { .. } | ChildMessage::Reply { .. } => {}
+            ChildMessage::Connection { state } => {
+                if let Some(services) = &self.services {
+                    services.set_connection_state(state);
+                }
+            }
+            ChildMessage::Recalibrate => {
+                if let Some(services) = &self.services {
+                    services.request_recalibration();
+                }
+            }
+            ChildMessage::Gauge { name, value } => {
+                if let Some(services) = &self.services {
+                    services.set_gauge(&name, value);
+                }
+            }
+            ChildMessage::Counter { name, delta } => {
+                if let Some(services) = &self.services {
+                    services.add_counter(&name, delta);
+                }
+            }
+        }
+    }
+
+    /// Why the child needs restarting, if it does.
+    fn check_child(&mut self, disconnected: bool) -> Option<String> {
+        let connection = self.connection.as_mut()?;
+        match connection.child.try_wait() {
+            Ok(Some(status)) => Some(format!("exited with {}", status)),
+            Err(e) => Some(format!("could not be checked: {}", e)),
+            Ok(None) if disconnected => Some("closed the connection".to_string()),
+            Ok(None) if self.last_message.elapsed() > HEARTBEAT_TIMEOUT => {
+                Some(format!("sent nothing for {:?}", HEARTBEAT_TIMEOUT))
+            }
+            Ok(None) => None,
+        }
+    }
+
+    /// Starts a new child and replays the settings and initialization.
+    fn restart(&mut self) -> Result<()> {
+        self.start()?;
+        let settings = self.settings.clone();
+        self.request(HostMessage::Configure { settings })
+            .context("Failed to configure module")?;
+        let state_dir = self
+            .services
+            .as_ref()
+            .and_then(|s| s.state_dir().map(Path::to_path_buf));
+        self.request(HostMessage::Initialize { state_dir })
+            .context("Failed to initialize module")
+    }
+
+    fn schedule_restart(&mut self) {
+        let delay = MIN_RESTART_DELAY
+            .saturating_mul(1 << self.crashes.min(5))
+            .min(MAX_RESTART_DELAY);
+        self.crashes += 1;
+        log::info!("Restarting {} in {:?}", HOST_EXE_NAME, delay);
+        self.next_restart = Some(Instant::now() + delay);
+    }
+
+    fn stop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.stop();
+        }
+        self.frame = None;
+        self.state = TrackingState::Reconnecting;
+    }
+}
+
+impl TrackingModule for SandboxModule {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        let state_dir = host.state_dir().map(Path::to_path_buf);
+        self.services = Some(host);
+        self.request(HostMessage::Initialize { state_dir })
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        if let Some(next_restart) = self.next_restart {
+            if Instant::now() < next_restart {
+                return TrackingState::Disconnected;
+            }
+            self.next_restart = None;
+            return match self.restart() {
+                Ok(()) => {
+                    log::info!("{} restarted successfully.", HOST_EXE_NAME);
+                    TrackingState::Reconnecting
+                }
+                Err(e) => {
+                    log::error!("Failed to restart {}: {:#}", HOST_EXE_NAME, e);
+                    self.stop();
+                    self.schedule_restart();
+                    TrackingState::Disconnected
+                }
+            };
+        }
+
+        let mut disconnected = false;
+        while let Some(connection) = &self.connection {
+            match connection.messages.try_recv() {
+                Ok(message) => self.handle(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+
+        if self.connection.is_some() && self.started.elapsed() > STABLE_AFTER {
+            self.crashes = 0;
+        }
+
+        if let Some(frame) = self.frame.take() {
+            *data = *frame;
+            return TrackingState::NewFrame;
+        }
+
+        if let Some(reason) = self.check_child(disconnected) {
+            log::warn!("{} {}. Restarting...", HOST_EXE_NAME, reason);
+            self.stop();
+            if let Some(services) = &self.services {
+                services.set_connection_state(ConnectionState::Disconnected);
+            }
+            self.schedule_restart();
+            return TrackingState::Disconnected;
+        }
+        self.state
+    }
+
+    fn unload(&mut self) {
+        self.next_restart = None;
+        self.stop();
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+        self.request(HostMessage::Configure {
+            settings: settings.clone(),
+        })?;
+        self.settings = settings.clone();
+        Ok(())
+    }
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        self.descriptor.clone()
+    }
+}
@@ -0,0 +1,137 @@
+//! Per-module settings shared between the HTTP API and the producer loop.
+
+use anyhow::{Context, Result};
+use api::{ModuleSettings, TrackingModule};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Current settings and schemas of all modules, plus the changes the producer loop still
+/// has to hand to the modules.
+#[derive(Debug, Default)]
+pub struct ModuleSettingsStore {
+    settings: HashMap<String, ModuleSettings>,
+    schemas: HashMap<String, Value>,
+    pending: Vec<String>,
+    /// Where updates are written back to, if anywhere.
+    config_file: Option<PathBuf>,
+}
+
+impl ModuleSettingsStore {
+    /// Starts from the `module.settings` section of the config.
+    pub fn new(settings: &HashMap<String, Value>) -> Self {
+        Self {
+            settings: settings
+                .iter()
+                .map(|(name, value)| (name.clone(), ModuleSettings::new(value.clone())))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Writes settings updated through [`update`](Self::update) to `module.settings` in the
+    /// JSON config at `path`.
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    pub fn set_schema(&mut self, module: &str, schema: Option<Value>) {
+        match schema {
+            Some(schema) => self.schemas.insert(module.to_string(), schema),
+            None => self.schemas.remove(module),
+        };
+    }
+
+    pub fn schema(&self, module: &str) -> Option<&Value> {
+        self.schemas.get(module)
+    }
+
+    pub fn schemas(&self) -> &HashMap<String, Value> {
+        &self.schemas
+    }
+
+    /// Settings of `module`; an empty object if none are configured.
+    pub fn get(&self, module: &str) -> ModuleSettings {
+        self.settings.get(module).cloned().unwrap_or_default()
+    }
+
+    pub fn all(&self) -> &HashMap<String, ModuleSettings> {
+        &self.settings
+    }
+
+    /// Validates and stores new settings for `module`, queues them for the module and writes
+    /// them to the config file, if there is one. Returns whether they were written; if not,
+    /// they last until the daemon restarts.
+    pub fn update(&mut self, module: &str, settings: ModuleSettings) -> Result<bool> {
+        if let Some(schema) = self.schemas.get(module) {
+            settings.validate(schema)?;
+        }
+        let saved = match &self.config_file {
+            Some(path) => match save_settings(path, module, &settings) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!(
+                        "Could not write the settings of module {} to {:?}: {:#}",
+                        module,
+                        path,
+                        e
+                    );
+                    false
+                }
+            },
+            None => false,
+        };
+        self.settings.insert(module.to_string(), settings);
+        if !self.pending.iter().any(|name| name == module) {
+            self.pending.push(module.to_string());
+        }
+        Ok(saved)
+    }
+
+    /// Settings changed since the last call, in the order they were changed.
+    pub fn take_pending(&mut self) -> Vec<(String, ModuleSettings)> {
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|name| {
+                let settings = self.get(&name);
+                (name, settings)
+            })
+            .collect()
+    }
+}
+
+/// Sets `module.settings.<module>` in the JSON config at `path`, leaving everything else in
+/// the file as it is. The file is replaced in one step, so a crash cannot truncate it.
+fn save_settings(path: &Path, module: &str, settings: &ModuleSettings) -> Result<()> {
+    let text = std::fs::read_to_string(path).context("Failed to read the config")?;
+    let mut config: Value = serde_json::from_str(&text).context("Invalid config")?;
+    let all = config
+        .as_object_mut()
+        .context("The config is not an object")?
+        .entry("module")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .context("`module` is not an object")?
+        .entry("settings")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .context("`module.settings` is not an object")?;
+    all.insert(module.to_string(), settings.value().clone());
+
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, serde_json::to_string_pretty(&config)?)
+        .context("Failed to write the config")?;
+    std::fs::rename(&temp, path).context("Failed to replace the config")?;
+    Ok(())
+}
+
+/// Validates `settings` against the module's schema, if it publishes one, and applies them.
+pub fn configure_module(module: &mut dyn TrackingModule, settings: &ModuleSettings) -> Result<()> {
+    if let Some(schema) = module.descriptor().and_then(|d| d.settings_schema) {
+        settings
+            .validate(&schema)
+            .context("Settings do not match the module's schema")?;
+    }
+    module.configure(settings)
+}
@@ -0,0 +1,255 @@
+//! Polls each module feeding the pipeline on its own thread, so a module that blocks in
+//! `update` holds up neither the producer loop nor the other modules. The manager watches
+//! the thread and notices an `update` that overruns its deadline.
+
+use crate::plugins::emitter::FrameEmitter;
+use crate::plugins::faults::contain;
+use crate::plugins::host::ModuleHost;
+use crate::plugins::metrics::{
+    HOST_DEADLINE_MISSES, HOST_EMPTY_POLLS, HOST_FRAMES, HOST_UPDATE_MS,
+};
+use anyhow::{Context, Result};
+use api::{gaze, TrackingModule, TrackingState, UnifiedTrackingData};
+use log::error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often to poll a module while it reports its device as disconnected.
+pub const DISCONNECTED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often to poll a module that is connected but had no new frame.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A module shared between the manager and the thread polling it.
+pub type SharedModule = Arc<Mutex<Box<dyn TrackingModule + Send>>>;
+
+/// Locks `module`. A panic while it was locked has already been recorded as a fault, so the
+/// poison is ignored.
+pub fn lock_module(module: &SharedModule) -> MutexGuard<'_, Box<dyn TrackingModule + Send>> {
+    module.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Locks `module` unless a worker is in its `update`. Like [`lock_module`], ignores poison.
+pub fn try_lock_module(
+    module: &SharedModule,
+) -> Option<MutexGuard<'_, Box<dyn TrackingModule + Send>>> {
+    match module.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Waits for `workers` on a thread of its own and runs `then` once they have stopped, so a
+/// module stuck in `update` holds up nothing else. The workers must have been stopped.
+pub fn after_workers(workers: Vec<PollWorker>, then: impl FnOnce() + Send + 'static) {
+    let spawned = thread::Builder::new()
+        .name("module-teardown".to_string())
+        .spawn(move || {
+            drop(workers);
+            then();
+        });
+    if let Err(e) = spawned {
+        error!("✗ Failed to start the module teardown thread: {}", e);
+    }
+}
+
+/// What the polling thread reports back to the manager.
+#[derive(Debug, Default)]
+struct Progress {
+    /// When the `update` in progress started.
+    busy_since: Option<Instant>,
+    /// Panic message of the `update` that faulted. The thread has stopped.
+    fault: Option<String>,
+    /// The module returned `TrackingState::Fatal`. The thread has stopped.
+    fatal: bool,
+}
+
+/// The thread polling one module. Dropping it waits for the `update` in progress.
+pub struct PollWorker {
+    running: Arc<AtomicBool>,
+    progress: Arc<Mutex<Progress>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PollWorker {
+    /// Starts polling `module`, sending its frames to `emitter` until stopped. Updates that
+    /// take longer than `deadline` are counted as misses.
+    pub fn spawn(
+        name: &str,
+        module: SharedModule,
+        host: Arc<ModuleHost>,
+        emitter: Arc<FrameEmitter>,
+        deadline: Duration,
+    ) -> Result<Self> {
+        let running = Arc::new(AtomicBool::new(true));
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let poller = Poller {
+            name: name.to_string(),
+            module,
+            host,
+            emitter,
+            deadline,
+            running: running.clone(),
+            progress: progress.clone(),
+        };
+        let thread = thread::Builder::new()
+            .name(format!("module-{}", name))
+            .spawn(move || poller.run())
+            .context("Failed to start the module thread")?;
+        Ok(Self {
+            running,
+            progress,
+            thread: Some(thread),
+        })
+    }
+
+    /// Asks the thread to stop after the `update` in progress, without waiting for it.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// How long the `update` in progress has been running.
+    pub fn busy_for(&self) -> Option<Duration> {
+        self.progress
+            .lock()
+            .unwrap()
+            .busy_since
+            .map(|since| since.elapsed())
+    }
+
+    /// The panic message of an `update` that faulted, once.
+    pub fn take_fault(&self) -> Option<String> {
+        self.progress.lock().unwrap().fault.take()
+    }
+
+    /// Whether the module returned `TrackingState::Fatal`.
+    pub fn is_fatal(&self) -> bool {
+        self.progress.lock().unwrap().fatal
+    }
+
+    /// Whether the thread has exited, so dropping the worker does not wait.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+}
+
+impl Drop for PollWorker {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Poller {
+    name: String,
+    module: SharedModule,
+    host: Arc<ModuleHost>,
+    emitter: Arc<FrameEmitter>,
+    deadline: Duration,
+    running: Arc<AtomicBool>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl Poller {
+    fn run(self) {
+        let mut data = UnifiedTrackingData::default();
+        let mut last_frame = Instant::now();
+
+        while self.running.load(Ordering::SeqCst) {
+            data.timestamp_us = 0;
+            let started = Instant::now();
+            self.progress.lock().unwrap().busy_since = Some(started);
+            let result = {
+                let mut module = lock_module(&self.module);
+                contain(|| module.update(&mut data))
+            };
+            let took = started.elapsed();
+            let mut progress = self.progress.lock().unwrap();
+            progress.busy_since = None;
+            let state = match result {
+                Ok(state) => state,
+                Err(message) => {
+                    progress.fault = Some(message);
+                    return;
+                }
+            };
+            drop(progress);
+
+            let counter = if state == TrackingState::NewFrame {
+                HOST_FRAMES
+            } else {
+                HOST_EMPTY_POLLS
+            };
+            self.host.with_metrics(|metrics| {
+                metrics.add(counter, 1);
+                metrics.set(HOST_UPDATE_MS, took.as_secs_f64() * 1000.0);
+                if took > self.deadline {
+                    metrics.add(HOST_DEADLINE_MISSES, 1);
+                }
+            });
+            self.host.tracking_changed(state);
+
+            match state {
+                TrackingState::NewFrame => {
+                    // A frame that arrives after a stop belongs to a module no longer active.
+                    if self.running.load(Ordering::SeqCst) {
+                        self.emitter.emit(&self.name, &mut data);
+                    }
+                    if let Some(interval) = self.emitter.min_interval() {
+                        let elapsed = last_frame.elapsed();
+                        if elapsed < interval {
+                            thread::sleep(interval - elapsed);
+                        }
+                    }
+                    last_frame = Instant::now();
+                }
+                TrackingState::NoNewFrame | TrackingState::Reconnecting => {
+                    thread::sleep(IDLE_POLL_INTERVAL);
+                }
+                TrackingState::Disconnected => {
+                    // Wait for the device instead of busy-polling, but stop promptly.
+                    let retry_at = Instant::now() + DISCONNECTED_POLL_INTERVAL;
+                    while self.running.load(Ordering::SeqCst) && Instant::now() < retry_at {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                }
+                TrackingState::Fatal => {
+                    error!("✗ Module {} failed and will no longer be polled", self.name);
+                    self.progress.lock().unwrap().fatal = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Moves `data` towards a neutral face by `amount` in `[0, 1]`: expressions relaxed, eyes
+/// open and looking forward, head centered. Used while a module is stuck.
+pub fn decay_toward_neutral(data: &mut UnifiedTrackingData, amount: f32) {
+    let amount = amount.clamp(0.0, 1.0);
+    let lerp = |value: &mut f32, target: f32| *value += (target - *value) * amount;
+
+    for shape in &mut data.shapes {
+        lerp(&mut shape.weight, 0.0);
+    }
+    for eye in [&mut data.eye.left, &mut data.eye.right] {
+        lerp(&mut eye.openness, 1.0);
+        eye.gaze = gaze::normalize(eye.gaze.lerp(gaze::FORWARD, amount));
+    }
+    let head = &mut data.head;
+    for value in [
+        &mut head.head_yaw,
+        &mut head.head_pitch,
+        &mut head.head_roll,
+        &mut head.head_pos_x,
+        &mut head.head_pos_y,
+        &mut head.head_pos_z,
+    ] {
+        lerp(value, 0.0);
+    }
+}
@@ -4,14 +4,21 @@ pub mod vrchat;
 
 use anyhow::Result;
 use axum::Router;
-use common::{IntegrationAdapter, MutationConfig, OutputMode, UnifiedTrackingData};
+use common::{
+    IntegrationAdapter, ModuleDescriptor, MutationConfig, OutputMode, UnifiedTrackingData,
+};
 use generic_udp::GenericUdpStrategy;
 use resonite::ResoniteOscStrategy;
 use std::sync::{Arc, RwLock};
 use vrchat::VRChatOscStrategy;
 
+/// Combined descriptor of the loaded modules; `None` while any of them does not describe
+/// itself.
+pub type SharedDescriptor = Arc<RwLock<Option<ModuleDescriptor>>>;
+
 pub struct OscContext {
     pub tracking_data: Arc<RwLock<UnifiedTrackingData>>,
+    pub module_descriptor: SharedDescriptor,
 }
 
 pub enum PlatformBackend {
@@ -17,7 +17,7 @@ impl VRChatOscStrategy {
         receive_port: u16,
         context: OscContext,
     ) -> (Self, Router, Option<Receiver<String>>) {
-        let inner = VRChatOsc::new(target_addr, receive_port);
+        let inner = VRChatOsc::new(target_addr, receive_port, context.module_descriptor);
         let router = vrchat::get_router(context.tracking_data, 9001);
 
         let change_rx = inner.change_rx.lock().unwrap().take();
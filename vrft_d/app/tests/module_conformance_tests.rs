@@ -0,0 +1,185 @@
+//! Module conformance suite tests
+//!
+//! Modules are exported through the C ABI in-process, so no library has to be built.
+
+use anyhow::Result;
+use api::abi::{export_module, AbiModule, VrftModuleVTable, VRFT_OK};
+use api::{HostServices, TrackingModule, TrackingState, UnifiedExpressions, UnifiedTrackingData};
+use std::process::Command;
+use std::time::Duration;
+use vrft_d::plugins::conformance::{
+    frame_problems, CheckStatus, ConformanceReport, Instance, Suite, SuiteOptions, CHECKS,
+};
+
+#[derive(Clone, Default)]
+struct Scripted {
+    jaw: f32,
+    delay: Duration,
+    panic_in_initialize: bool,
+    /// Threads started in `initialize` and never stopped.
+    leaked_threads: usize,
+}
+
+impl TrackingModule for Scripted {
+    fn initialize(&mut self, _host: HostServices) -> Result<()> {
+        if self.panic_in_initialize {
+            panic!("no device");
+        }
+        for _ in 0..self.leaked_threads {
+            std::thread::spawn(|| std::thread::sleep(Duration::from_secs(30)));
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        std::thread::sleep(self.delay);
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight = self.jaw;
+        TrackingState::NewFrame
+    }
+
+    fn unload(&mut self) {}
+}
+
+fn run(name: &str, module: Scripted, options: SuiteOptions) -> ConformanceReport {
+    let create = || {
+        let mut vtable = VrftModuleVTable::empty();
+        unsafe {
+            assert_eq!(export_module(&mut vtable, module.clone()), VRFT_OK);
+            Ok(Instance {
+                module: Box::new(AbiModule::from_vtable(vtable)?),
+                library: None,
+            })
+        }
+    };
+    let mut reported = Vec::new();
+    let report = Suite::new(name, create, options).run(|r| reported.push(r.name.clone()));
+    assert_eq!(reported, CHECKS);
+    report
+}
+
+fn options() -> SuiteOptions {
+    SuiteOptions {
+        updates: 20,
+        cycles: 0,
+        ..Default::default()
+    }
+}
+
+fn status(report: &ConformanceReport, check: &str) -> CheckStatus {
+    report.check(check).unwrap().status
+}
+
+#[test]
+fn a_well_behaved_module_passes() {
+    let module = Scripted {
+        jaw: 0.5,
+        ..Default::default()
+    };
+    let report = run("well_behaved", module, options());
+
+    assert!(report.passed);
+    for check in [
+        "load",
+        "configure",
+        "initialize",
+        "update_latency",
+        "output",
+    ] {
+        assert_eq!(status(&report, check), CheckStatus::Pass, "{}", check);
+    }
+    assert_eq!(status(&report, "descriptor"), CheckStatus::Warn);
+    assert_eq!(status(&report, "panic_containment"), CheckStatus::Pass);
+    assert_eq!(status(&report, "unload_reload"), CheckStatus::Skip);
+    let latency = &report.check("update_latency").unwrap().data;
+    assert_eq!(latency["calls"], 20);
+    assert_eq!(latency["states"]["new_frame"], 20);
+}
+
+#[test]
+fn out_of_range_output_fails() {
+    let module = Scripted {
+        jaw: 1.5,
+        ..Default::default()
+    };
+    let report = run("out_of_range", module, options());
+    assert!(!report.passed);
+    let output = report.check("output").unwrap();
+    assert_eq!(output.status, CheckStatus::Fail);
+    assert!(output.detail.contains("outside [0, 1]"));
+
+    let mut data = UnifiedTrackingData::default();
+    assert!(frame_problems(&data).is_empty());
+    data.head.head_yaw = f32::NAN;
+    data.eye.left.status.confidence = f32::INFINITY;
+    data.shape_confidences.pop();
+    let problems = frame_problems(&data);
+    assert_eq!(problems.len(), 3, "{:?}", problems);
+    assert!(problems.iter().any(|p| p == "head.head_yaw is NaN"));
+}
+
+#[test]
+fn slow_updates_miss_the_budget() {
+    let module = Scripted {
+        delay: Duration::from_millis(20),
+        ..Default::default()
+    };
+    let options = SuiteOptions {
+        updates: 3,
+        budget: Duration::from_millis(5),
+        ..options()
+    };
+    let report = run("slow", module, options);
+    let latency = report.check("update_latency").unwrap();
+    assert_eq!(latency.status, CheckStatus::Fail);
+    assert_eq!(latency.data["over_budget"], 3);
+}
+
+#[test]
+fn contained_panics_are_reported() {
+    let module = Scripted {
+        panic_in_initialize: true,
+        ..Default::default()
+    };
+    let report = run("panicking", module, options());
+
+    let initialize = report.check("initialize").unwrap();
+    assert_eq!(initialize.status, CheckStatus::Fail);
+    assert!(initialize.detail.contains("Panicked in initialize"));
+    assert_eq!(status(&report, "update_latency"), CheckStatus::Skip);
+    assert_eq!(status(&report, "panic_containment"), CheckStatus::Warn);
+    assert_eq!(status(&report, "unload_reload"), CheckStatus::Skip);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn leaked_threads_fail_the_reload_check() {
+    let module = Scripted {
+        leaked_threads: 4,
+        ..Default::default()
+    };
+    let options = SuiteOptions {
+        cycles: 6,
+        ..options()
+    };
+    let report = run("leaky", module, options);
+    let reload = report.check("unload_reload").unwrap();
+    assert_eq!(reload.status, CheckStatus::Fail);
+    assert!(reload.detail.contains("thread(s)"), "{}", reload.detail);
+}
+
+#[test]
+fn the_runner_reports_missing_modules() {
+    let output = Command::new(env!("CARGO_BIN_EXE_vrft_conformance"))
+        .arg("does_not_exist.so")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let report: ConformanceReport = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(!report.passed);
+    assert_eq!(report.checks.len(), CHECKS.len());
+    assert_eq!(report.checks[0].status, CheckStatus::Fail);
+    assert!(report.checks[1..]
+        .iter()
+        .all(|c| c.status == CheckStatus::Skip));
+}
@@ -0,0 +1,98 @@
+//! Module failover chain tests
+
+use api::{UnifiedExpressions, UnifiedTrackingData};
+use common::ModuleConfig;
+use std::sync::mpsc::sync_channel;
+use std::thread;
+use std::time::Duration;
+use vrft_d::plugins::emitter::FrameEmitter;
+use vrft_d::plugins::fusion::{blend, Fusion, STALE_AFTER};
+
+fn frame(weight: f32) -> UnifiedTrackingData {
+    let mut data = UnifiedTrackingData::default();
+    data.shapes[UnifiedExpressions::JawOpen as usize].weight = weight;
+    data.head.head_yaw = weight * 10.0;
+    data
+}
+
+fn jaw(data: &UnifiedTrackingData) -> f32 {
+    data.shapes[UnifiedExpressions::JawOpen as usize].weight
+}
+
+#[test]
+fn active_accepts_one_module_or_a_chain() {
+    let config: ModuleConfig = serde_json::from_str(r#"{ "active": "headset.dll" }"#).unwrap();
+    assert_eq!(config.active, ["headset.dll"]);
+
+    let config: ModuleConfig =
+        serde_json::from_str(r#"{ "active": ["headset.dll", "webcam.dll"] }"#).unwrap();
+    assert_eq!(config.active, ["headset.dll", "webcam.dll"]);
+
+    let config: ModuleConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.active, ["vd_module.dll"]);
+    assert_eq!(config.failover_after_ms, 1000);
+}
+
+#[test]
+fn standby_frames_are_tracked_but_not_sent() {
+    let (tx, rx) = sync_channel(4);
+    let emitter = FrameEmitter::new(tx, None);
+    emitter.set_active(Some("headset.dll"));
+
+    emitter.emit("webcam.dll", &mut frame(0.3));
+    assert!(rx.try_recv().is_err());
+    assert!(emitter.freshness("webcam.dll").is_some());
+    assert!(emitter.freshness("headset.dll").is_none());
+
+    emitter.emit("headset.dll", &mut frame(0.7));
+    assert_eq!(jaw(&rx.try_recv().unwrap()), 0.7);
+}
+
+#[test]
+fn a_gap_restarts_the_streak() {
+    let mut fusion = Fusion::default();
+    fusion.merge("webcam.dll", &frame(0.0));
+    thread::sleep(Duration::from_millis(50));
+    fusion.merge("webcam.dll", &frame(0.0));
+    let steady = fusion.freshness("webcam.dll").unwrap();
+    assert!(steady.streak >= Duration::from_millis(50));
+
+    thread::sleep(STALE_AFTER + Duration::from_millis(10));
+    assert_eq!(
+        fusion.freshness("webcam.dll").unwrap().streak,
+        Duration::ZERO
+    );
+    fusion.merge("webcam.dll", &frame(0.0));
+    let after_gap = fusion.freshness("webcam.dll").unwrap();
+    assert!(after_gap.age < STALE_AFTER);
+    assert!(after_gap.streak < Duration::from_millis(50));
+}
+
+#[test]
+fn switching_cross_fades_from_the_last_output() {
+    let mut fusion = Fusion::default();
+    fusion.set_active(Some("headset.dll"));
+    fusion.merge("headset.dll", &frame(0.0)).unwrap();
+
+    fusion.crossfade(Duration::from_secs(10));
+    fusion.set_active(Some("webcam.dll"));
+    let fading = fusion.merge("webcam.dll", &frame(1.0)).unwrap();
+    assert!(jaw(&fading) < 0.1);
+
+    fusion.crossfade(Duration::ZERO);
+    let done = fusion.merge("webcam.dll", &frame(1.0)).unwrap();
+    assert_eq!(jaw(&done), 1.0);
+}
+
+#[test]
+fn blend_interpolates_between_frames() {
+    let from = frame(0.0);
+    let mut to = frame(1.0);
+    blend(&from, &mut to, 0.25);
+    assert!((jaw(&to) - 0.25).abs() < 1e-6);
+    assert!((to.head.head_yaw - 2.5).abs() < 1e-5);
+
+    let mut to = frame(1.0);
+    blend(&from, &mut to, 1.0);
+    assert_eq!(jaw(&to), 1.0);
+}
@@ -0,0 +1,47 @@
+//! Module panic containment tests
+
+use std::time::Duration;
+use vrft_d::plugins::faults::{
+    contain, FaultHistory, ModuleFault, RestartPolicy, MAX_RECENT_FAULTS,
+};
+
+#[test]
+fn contain_returns_the_panic_message() {
+    assert_eq!(contain(|| 7), Ok(7));
+    assert_eq!(
+        contain(|| -> () { panic!("static message") }),
+        Err("static message".to_string())
+    );
+    let device = "eye tracker";
+    assert_eq!(
+        contain(|| -> () { panic!("{} went away", device) }),
+        Err("eye tracker went away".to_string())
+    );
+}
+
+#[test]
+fn history_keeps_the_most_recent_faults() {
+    let mut history = FaultHistory::default();
+    for i in 0..MAX_RECENT_FAULTS as u64 + 5 {
+        history.record(ModuleFault {
+            timestamp_us: i,
+            call: "update".to_string(),
+            message: format!("fault {}", i),
+        });
+    }
+    assert_eq!(history.total, MAX_RECENT_FAULTS as u64 + 5);
+    assert_eq!(history.recent.len(), MAX_RECENT_FAULTS);
+    assert_eq!(history.recent.front().unwrap().timestamp_us, 5);
+    assert_eq!(
+        history.recent.back().unwrap().timestamp_us,
+        MAX_RECENT_FAULTS as u64 + 4
+    );
+}
+
+#[test]
+fn restart_delay_doubles_up_to_the_limit() {
+    let policy = RestartPolicy::default();
+    let delays: Vec<u64> = (0..7).map(|a| policy.delay(a).as_secs()).collect();
+    assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+    assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+}
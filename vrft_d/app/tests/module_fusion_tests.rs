@@ -0,0 +1,202 @@
+//! Multi-module fusion tests
+
+use api::{
+    ChannelStatus, FacialRegion, ModuleCapabilities, ModuleDescriptor, UnifiedExpressions,
+    UnifiedTrackingData,
+};
+use common::{ModuleConfig, ModuleSources};
+use std::thread;
+use vrft_d::plugins::fusion::{Fusion, SourceMap, STALE_AFTER};
+
+/// Eye gaze and openness from `eyes`, the lips from `lips`, everything else from the active
+/// module.
+fn sources() -> SourceMap {
+    let mut sources = ModuleSources {
+        eye_gaze: Some("eyes".to_string()),
+        eye_openness: Some("eyes".to_string()),
+        ..Default::default()
+    };
+    sources
+        .regions
+        .insert(FacialRegion::Lip, "lips".to_string());
+    SourceMap::from_config(&sources)
+}
+
+fn frame(weight: f32, openness: f32) -> UnifiedTrackingData {
+    let mut data = UnifiedTrackingData {
+        timestamp_us: 1,
+        ..Default::default()
+    };
+    for shape in &mut data.shapes {
+        shape.weight = weight;
+    }
+    data.eye.left.openness = openness;
+    data.eye.right.openness = openness;
+    data.head.head_yaw = weight;
+    data
+}
+
+fn weight(data: &UnifiedTrackingData, expr: UnifiedExpressions) -> f32 {
+    data.shapes[expr as usize].weight
+}
+
+#[test]
+fn each_module_only_updates_the_channels_it_owns() {
+    let mut fusion = Fusion::new(sources());
+    fusion.set_active(Some("face"));
+
+    fusion.merge("face", &frame(0.2, 0.2)).unwrap();
+    fusion.merge("eyes", &frame(0.9, 0.9)).unwrap();
+    let merged = fusion.merge("lips", &frame(0.6, 0.6)).unwrap();
+
+    assert_eq!(merged.eye.left.openness, 0.9);
+    assert_eq!(
+        weight(&merged, UnifiedExpressions::LipPuckerUpperRight),
+        0.6
+    );
+    assert_eq!(weight(&merged, UnifiedExpressions::JawOpen), 0.2);
+    assert_eq!(merged.head.head_yaw, 0.2);
+
+    // A module that owns nothing produces no output.
+    assert!(fusion.merge("other", &frame(1.0, 1.0)).is_none());
+}
+
+#[test]
+fn frames_pass_through_without_sources() {
+    let mut fusion = Fusion::default();
+    fusion.set_active(Some("face"));
+    assert!(!fusion.is_fused());
+
+    let mut data = frame(0.4, 0.4);
+    data.face = ChannelStatus::LOST;
+    assert_eq!(fusion.merge("face", &data), Some(data));
+    assert!(fusion.status().is_empty());
+}
+
+#[test]
+fn channels_go_stale_when_their_module_stops() {
+    let mut fusion = Fusion::new(sources());
+    fusion.set_active(Some("face"));
+
+    fusion.merge("eyes", &frame(0.0, 0.9)).unwrap();
+    fusion.merge("lips", &frame(0.6, 0.0)).unwrap();
+    let fresh = fusion.merge("face", &frame(0.2, 0.0)).unwrap();
+    assert_ne!(fresh.eye.left.status, ChannelStatus::LOST);
+
+    thread::sleep(STALE_AFTER);
+    let merged = fusion.merge("face", &frame(0.2, 0.0)).unwrap();
+    assert_eq!(merged.eye.left.status, ChannelStatus::LOST);
+    assert_eq!(
+        merged.shape_confidences[UnifiedExpressions::LipPuckerUpperRight as usize],
+        0.0
+    );
+    assert_ne!(merged.head.status, ChannelStatus::LOST);
+
+    let status = fusion.status();
+    let lip = status.iter().find(|s| s.channel == "region.lip").unwrap();
+    assert_eq!(lip.module.as_deref(), Some("lips"));
+    assert!(lip.stale);
+    let head = status.iter().find(|s| s.channel == "head").unwrap();
+    assert_eq!(head.module.as_deref(), Some("face"));
+    assert!(!head.stale);
+}
+
+#[test]
+fn the_face_is_lost_once_no_region_owner_tracks_it() {
+    let mut fusion = Fusion::new(sources());
+    fusion.set_active(Some("face"));
+
+    fusion.merge("face", &frame(0.2, 0.0)).unwrap();
+    let mut lost = frame(0.6, 0.0);
+    lost.face = ChannelStatus::LOST;
+    let merged = fusion.merge("lips", &lost).unwrap();
+    assert!(merged.face.valid);
+    assert_eq!(
+        merged.shape_confidence(UnifiedExpressions::LipPuckerUpperRight as usize),
+        0.0
+    );
+    assert_eq!(
+        merged.shape_confidence(UnifiedExpressions::JawOpen as usize),
+        1.0
+    );
+
+    let mut lost_too = frame(0.2, 0.0);
+    lost_too.face = ChannelStatus::LOST;
+    let merged = fusion.merge("face", &lost_too).unwrap();
+    assert!(!merged.face.valid);
+
+    // Eye frames carry no face, so they do not bring it back.
+    let merged = fusion.merge("eyes", &frame(0.0, 0.9)).unwrap();
+    assert!(!merged.face.valid);
+}
+
+#[test]
+fn descriptors_are_limited_to_owned_channels() {
+    let full = |name: &str| ModuleDescriptor {
+        name: name.to_string(),
+        capabilities: ModuleCapabilities::ALL,
+        expressions: UnifiedExpressions::ALL.to_vec(),
+        ..Default::default()
+    };
+    let sources = sources();
+
+    let descriptor = sources
+        .descriptor(Some("face"), &[("eyes", Some(full("eyes")))])
+        .unwrap();
+    assert!(descriptor.capabilities.eye_gaze);
+    assert!(!descriptor.capabilities.head);
+    assert!(!descriptor.capabilities.pupil);
+    assert!(!descriptor.capabilities.expressions);
+
+    let descriptor = sources
+        .descriptor(
+            Some("face"),
+            &[
+                ("face", Some(full("face"))),
+                ("lips", Some(full("lips"))),
+                ("eyes", None),
+            ],
+        )
+        .unwrap();
+    assert!(descriptor.capabilities.head);
+    assert!(descriptor.capabilities.pupil);
+    assert!(!descriptor.capabilities.eye_gaze);
+    assert!(descriptor
+        .expressions
+        .contains(&UnifiedExpressions::LipPuckerUpperRight));
+    assert!(descriptor
+        .expressions
+        .contains(&UnifiedExpressions::JawOpen));
+
+    assert!(SourceMap::default()
+        .descriptor(Some("face"), &[("eyes", Some(full("eyes")))])
+        .is_none());
+}
+
+#[test]
+fn sources_are_read_from_config() {
+    let config: ModuleConfig = serde_json::from_str(
+        r#"{
+            "active": "face",
+            "sources": {
+                "eye_gaze": "eyes",
+                "pupil": "eyes",
+                "regions": { "lip": "lips", "mouth": "lips" }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.sources.eye_gaze.as_deref(), Some("eyes"));
+    assert_eq!(config.sources.head, None);
+    assert_eq!(config.sources.regions[&FacialRegion::Mouth], "lips");
+
+    let sources = SourceMap::from_config(&config.sources);
+    assert!(sources.is_source("lips"));
+    assert!(!sources.is_source("face"));
+    assert_eq!(
+        sources.modules().into_iter().collect::<Vec<_>>(),
+        ["eyes", "lips"]
+    );
+
+    assert!(SourceMap::from_config(&ModuleConfig::default().sources).is_empty());
+}
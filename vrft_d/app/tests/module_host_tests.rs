@@ -0,0 +1,78 @@
+//! Module host services tests
+
+use api::{ConnectionState, Host, TrackingState};
+use std::sync::{Arc, RwLock};
+use vrft_d::osc::query::extensions::DEFAULT_CALIBRATION_SECONDS;
+use vrft_d::plugins::host::{module_logger, module_state_dir, ModuleHost, SharedModuleStatus};
+
+#[test]
+fn reports_end_up_in_module_status() {
+    let status = SharedModuleStatus::default();
+    let host = ModuleHost::new("a.dll", status.clone(), Arc::default());
+    assert_eq!(
+        status.read().unwrap()["a.dll"].connection,
+        ConnectionState::Disconnected
+    );
+
+    host.connection_changed(ConnectionState::Connected);
+    host.set_gauge("device_fps", 90.0);
+    host.set_gauge("device_fps", 72.0);
+    host.add_counter("reconnects", 2);
+    host.with_metrics(|metrics| metrics.add("host.frames", 1));
+
+    let status = status.read().unwrap();
+    assert_eq!(status["a.dll"].connection, ConnectionState::Connected);
+    let metrics = &status["a.dll"].metrics;
+    assert_eq!(metrics.gauges["device_fps"].value, 72.0);
+    assert_eq!(metrics.counters["reconnects"], 2);
+    assert_eq!(metrics.counters["host.frames"], 1);
+}
+
+#[test]
+fn tracking_state_drives_connection() {
+    let status = SharedModuleStatus::default();
+    let host = ModuleHost::new("a.dll", status.clone(), Arc::default());
+    assert_eq!(status.read().unwrap()["a.dll"].tracking, None);
+
+    host.tracking_changed(TrackingState::NoNewFrame);
+    assert_eq!(
+        status.read().unwrap()["a.dll"].connection,
+        ConnectionState::Connected
+    );
+
+    host.tracking_changed(TrackingState::Reconnecting);
+    let status = status.read().unwrap();
+    assert_eq!(status["a.dll"].tracking, Some(TrackingState::Reconnecting));
+    assert_eq!(status["a.dll"].connection, ConnectionState::Connecting);
+}
+
+#[test]
+fn recalibration_keeps_a_pending_request() {
+    let request = Arc::new(RwLock::new(None));
+    let host = ModuleHost::new("a.dll", SharedModuleStatus::default(), request.clone());
+
+    host.request_recalibration();
+    assert_eq!(*request.read().unwrap(), Some(DEFAULT_CALIBRATION_SECONDS));
+
+    *request.write().unwrap() = Some(5.0);
+    host.request_recalibration();
+    assert_eq!(*request.read().unwrap(), Some(5.0));
+}
+
+#[test]
+fn state_dir_is_named_after_the_module() {
+    let root = std::env::temp_dir().join(format!("vrft_state_{}", std::process::id()));
+    let dir = module_state_dir(&root, "vd_module.dll").unwrap();
+    assert_eq!(dir, root.join("vd_module.dll"));
+    assert!(dir.is_dir());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn the_log_callback_takes_whatever_a_c_module_passes() {
+    let callback = module_logger("a.dll").callback();
+    let message = c"hello";
+    callback(0, std::ptr::null(), message.as_ptr());
+    callback(99, message.as_ptr(), std::ptr::null());
+    callback(u32::MAX, std::ptr::null(), std::ptr::null());
+}
@@ -0,0 +1,133 @@
+//! Native library integrity policy tests
+
+use common::IntegrityConfig;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, RwLock};
+use vrft_d::plugins::emitter::FrameEmitter;
+use vrft_d::plugins::host::SharedModuleStatus;
+use vrft_d::plugins::integrity::{encode_hex, signature_path, IntegrityPolicy, Refused};
+use vrft_d::plugins::manager::{ModuleContext, ModuleControl, ModuleDirs, ModuleManager};
+use vrft_d::plugins::settings::ModuleSettingsStore;
+
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vrft_integrity_{}_{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn policy(sha256: Vec<String>, signing_keys: Vec<String>) -> IntegrityPolicy {
+    IntegrityPolicy::from_config(&IntegrityConfig {
+        enabled: true,
+        sha256,
+        signing_keys,
+    })
+}
+
+fn is_refused(result: anyhow::Result<Option<String>>) -> bool {
+    result.is_err_and(|e| e.downcast_ref::<Refused>().is_some())
+}
+
+#[test]
+fn disabled_policy_allows_everything() {
+    let dir = temp_dir("disabled");
+    let library = dir.join("module.so");
+    std::fs::write(&library, b"anything").unwrap();
+
+    let policy = IntegrityPolicy::from_config(&IntegrityConfig::default());
+    assert!(!policy.is_enabled());
+    assert_eq!(policy.verify(&library, &library).unwrap(), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn only_allowlisted_digests_load() {
+    let dir = temp_dir("allowlist");
+    let library = dir.join("module.so");
+    std::fs::write(&library, b"trusted build").unwrap();
+    let sha256 = encode_hex(digest(&SHA256, b"trusted build").as_ref());
+
+    let policy = policy(
+        vec![sha256.to_uppercase(), "not hex".to_string()],
+        Vec::new(),
+    );
+    assert!(policy.verify(&library, &library).unwrap().is_some());
+
+    std::fs::write(&library, b"tampered build").unwrap();
+    assert!(is_refused(policy.verify(&library, &library)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn signed_libraries_load() {
+    let dir = temp_dir("signature");
+    let library = dir.join("module.so");
+    let copy = dir.join("copy.so");
+    std::fs::write(&library, b"signed build").unwrap();
+    std::fs::copy(&library, &copy).unwrap();
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+    let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = encode_hex(key.public_key().as_ref());
+    let policy = policy(Vec::new(), vec![public_key]);
+
+    // No signature yet.
+    assert!(is_refused(policy.verify(&library, &copy)));
+
+    // Raw and hex signatures are accepted; the copy is checked, the signature is found
+    // next to the original.
+    let signature = key.sign(b"signed build");
+    std::fs::write(signature_path(&library), signature.as_ref()).unwrap();
+    assert!(policy.verify(&library, &copy).unwrap().is_some());
+    std::fs::write(signature_path(&library), encode_hex(signature.as_ref())).unwrap();
+    assert!(policy.verify(&library, &copy).unwrap().is_some());
+
+    std::fs::write(&copy, b"tampered build").unwrap();
+    assert!(is_refused(policy.verify(&library, &copy)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn refusals_are_listed() {
+    let root = temp_dir("listing");
+    let dirs = ModuleDirs {
+        native: root.join("native"),
+        vrcft: root.join("dotnet/modules"),
+        vrcft_host: root.join("dotnet/host/VrcftRuntime"),
+        shadow: root.join("shadow"),
+    };
+    std::fs::create_dir_all(&dirs.native).unwrap();
+    std::fs::write(dirs.native.join("unknown.so"), b"dropped by someone").unwrap();
+    let control = Arc::new(RwLock::new(ModuleControl::new(dirs)));
+
+    let (tx, _rx) = sync_channel(1);
+    let mut manager = ModuleManager::new(
+        ModuleContext {
+            status: SharedModuleStatus::default(),
+            calibration_request: Arc::default(),
+            settings: Arc::new(RwLock::new(ModuleSettingsStore::default())),
+            descriptor: Arc::default(),
+            emitter: Arc::new(FrameEmitter::new(tx, None)),
+        },
+        control.clone(),
+    )
+    .with_integrity(policy(Vec::new(), Vec::new()));
+
+    let error = manager.activate("unknown.so").unwrap_err();
+    assert!(error.downcast_ref::<Refused>().is_some());
+    let listing = control.read().unwrap().listing();
+    assert!(listing[0].refused);
+    assert!(listing[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("integrity policy"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,408 @@
+//! Module discovery and runtime control tests
+
+use common::{ModuleConfig, ModuleSources};
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use vrft_d::plugins::emitter::FrameEmitter;
+use vrft_d::plugins::fusion::SourceMap;
+use vrft_d::plugins::host::{module_state_dir, SharedModuleStatus};
+use vrft_d::plugins::manager::{
+    ModuleCommand, ModuleContext, ModuleControl, ModuleDirs, ModuleKind, ModuleManager,
+};
+use vrft_d::plugins::native::ShadowCopy;
+use vrft_d::plugins::settings::ModuleSettingsStore;
+
+/// Plugin directories under a fresh temporary root.
+fn plugin_dirs(test: &str) -> (PathBuf, ModuleDirs) {
+    let root = std::env::temp_dir().join(format!("vrft_{}_{}", test, std::process::id()));
+    let dirs = ModuleDirs {
+        native: root.join("native"),
+        vrcft: root.join("dotnet/modules"),
+        vrcft_host: root.join("dotnet/host/VrcftRuntime"),
+        shadow: root.join("shadow"),
+    };
+    std::fs::create_dir_all(&dirs.native).unwrap();
+    std::fs::create_dir_all(&dirs.vrcft).unwrap();
+    (root, dirs)
+}
+
+#[test]
+fn discovers_native_and_vrcft_modules() {
+    let (root, dirs) = plugin_dirs("discover");
+    std::fs::write(dirs.native.join("b_module.so"), b"").unwrap();
+    std::fs::write(dirs.native.join("shared.dll"), b"").unwrap();
+    std::fs::write(dirs.native.join("readme.txt"), b"").unwrap();
+    std::fs::write(dirs.vrcft.join("a_module.dll"), b"").unwrap();
+    std::fs::write(dirs.vrcft.join("shared.dll"), b"").unwrap();
+
+    let files = dirs.discover();
+    let names: Vec<_> = files.iter().map(|f| (f.name.as_str(), f.kind)).collect();
+    assert_eq!(
+        names,
+        [
+            ("a_module.dll", ModuleKind::Vrcft),
+            ("b_module.so", ModuleKind::Native),
+            ("shared.dll", ModuleKind::Native),
+        ]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn control_queues_commands_for_known_modules() {
+    let (root, dirs) = plugin_dirs("control");
+    std::fs::write(dirs.vrcft.join("a_module.dll"), b"").unwrap();
+    let mut control = ModuleControl::new(dirs);
+
+    assert!(control
+        .request(ModuleCommand::Activate("missing.dll".to_string()))
+        .is_err());
+    control
+        .request(ModuleCommand::Activate("a_module.dll".to_string()))
+        .unwrap();
+    control
+        .request(ModuleCommand::Unload("a_module.dll".to_string()))
+        .unwrap();
+
+    assert_eq!(
+        control.take_pending(),
+        [
+            ModuleCommand::Activate("a_module.dll".to_string()),
+            ModuleCommand::Unload("a_module.dll".to_string()),
+        ]
+    );
+    assert!(control.take_pending().is_empty());
+
+    let listing = control.listing();
+    assert_eq!(listing.len(), 1);
+    assert!(!listing[0].loaded);
+    assert!(!listing[0].active);
+    assert_eq!(control.active(), None);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn failed_loads_are_listed() {
+    let (root, dirs) = plugin_dirs("failed_load");
+    std::fs::write(dirs.native.join("broken.so"), b"not a library").unwrap();
+    std::fs::write(dirs.vrcft.join("a_module.dll"), b"").unwrap();
+    let control = Arc::new(RwLock::new(ModuleControl::new(dirs)));
+
+    let (tx, _rx) = sync_channel(1);
+    let mut manager = ModuleManager::new(
+        ModuleContext {
+            status: SharedModuleStatus::default(),
+            calibration_request: Arc::default(),
+            settings: Arc::new(RwLock::new(ModuleSettingsStore::default())),
+            descriptor: Arc::default(),
+            emitter: Arc::new(FrameEmitter::new(tx, None)),
+        },
+        control.clone(),
+    );
+
+    manager.load_native_modules();
+    assert!(manager.modules().is_empty());
+    // No VrcftRuntime in the test tree.
+    assert!(manager.activate("a_module.dll").is_err());
+    assert_eq!(manager.active_name(), None);
+    assert!(!manager.unload("broken.so"));
+
+    let listing = control.read().unwrap().listing();
+    assert_eq!(listing.len(), 2);
+    assert!(listing.iter().all(|m| !m.loaded && m.error.is_some()));
+    assert!(listing[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("VrcftRuntime not found"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn only_one_vrcft_module_stands_by() {
+    let (root, dirs) = plugin_dirs("one_vrcft");
+    for name in ["a.dll", "b.dll", "c.dll"] {
+        std::fs::write(dirs.vrcft.join(name), b"").unwrap();
+    }
+    let control = Arc::new(RwLock::new(ModuleControl::new(dirs)));
+    let sources = ModuleSources {
+        head: Some("c.dll".to_string()),
+        ..Default::default()
+    };
+
+    let (tx, _rx) = sync_channel(1);
+    let mut manager = ModuleManager::new(
+        ModuleContext {
+            status: SharedModuleStatus::default(),
+            calibration_request: Arc::default(),
+            settings: Arc::new(RwLock::new(ModuleSettingsStore::default())),
+            descriptor: Arc::default(),
+            emitter: Arc::new(FrameEmitter::new(tx, None)),
+        },
+        control.clone(),
+    )
+    .with_sources(SourceMap::from_config(&sources))
+    .with_failover(
+        vec!["a.dll".to_string(), "b.dll".to_string()],
+        Duration::from_secs(1),
+        Duration::ZERO,
+    );
+    manager.start_standby();
+
+    // The source comes first and is tried; the chain is skipped instead of stopping it.
+    let listing = control.read().unwrap().listing();
+    let error = |name: &str| {
+        listing
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(|m| m.error.clone())
+            .unwrap()
+    };
+    assert!(error("c.dll").contains("VrcftRuntime not found"));
+    for name in ["a.dll", "b.dll"] {
+        assert!(error(name).contains("Only one VRCFT module"), "{}", name);
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn shadow_copies_get_unique_names_and_are_removed_on_drop() {
+    let (root, dirs) = plugin_dirs("shadow");
+    let original = dirs.native.join("module.so");
+    std::fs::write(&original, b"v1").unwrap();
+
+    let first = ShadowCopy::create(&original, &dirs.shadow).unwrap();
+    std::fs::write(&original, b"v2").unwrap();
+    let second = ShadowCopy::create(&original, &dirs.shadow).unwrap();
+
+    assert_ne!(first.path(), second.path());
+    assert_eq!(first.path().extension().unwrap(), "so");
+    assert_eq!(std::fs::read(first.path()).unwrap(), b"v1");
+    assert_eq!(std::fs::read(second.path()).unwrap(), b"v2");
+    assert!(!first.path().starts_with(&dirs.native));
+
+    let first_path = first.path().to_path_buf();
+    drop(first);
+    assert!(!first_path.exists());
+    assert!(second.path().exists());
+
+    drop(second);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn shadow_copies_need_a_private_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (root, dirs) = plugin_dirs("shadow_private");
+    let original = dirs.native.join("module.so");
+    std::fs::write(&original, b"v1").unwrap();
+
+    let copy = ShadowCopy::create(&original, &dirs.shadow).unwrap();
+    let mode = std::fs::metadata(&dirs.shadow)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o700);
+    drop(copy);
+
+    std::fs::set_permissions(&dirs.shadow, std::fs::Permissions::from_mode(0o777)).unwrap();
+    let error = ShadowCopy::create(&original, &dirs.shadow).unwrap_err();
+    assert!(
+        error.to_string().contains("writable by other users"),
+        "{}",
+        error
+    );
+    assert_eq!(std::fs::read_dir(&dirs.shadow).unwrap().count(), 0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// Writes `manifest` as the `plugin.json` of plugin folder `folder`.
+fn write_plugin(dir: &std::path::Path, folder: &str, manifest: &str) -> PathBuf {
+    let folder = dir.join(folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join("plugin.json"), manifest).unwrap();
+    folder
+}
+
+#[test]
+fn plugins_are_discovered_from_manifests() {
+    let (root, dirs) = plugin_dirs("manifest");
+    let eye = write_plugin(
+        &dirs.native,
+        "eye_folder",
+        r#"{
+            "id": "eye_tracker",
+            "version": "1.2.0",
+            "abi_version": 12,
+            "library": "libeye.so",
+            "settings_schema": { "type": "object" }
+        }"#,
+    );
+    std::fs::write(eye.join("libeye.so"), b"").unwrap();
+    write_plugin(
+        &dirs.native,
+        "other_os",
+        &format!(
+            r#"{{ "id": "other_os", "platforms": ["{}_not"], "library": "x.so" }}"#,
+            std::env::consts::OS
+        ),
+    );
+    write_plugin(
+        &dirs.native,
+        "old_abi",
+        r#"{ "id": "old_abi", "abi_version": 1, "library": "x.so" }"#,
+    );
+    write_plugin(&dirs.native, "broken", "{ not json");
+    write_plugin(
+        &dirs.vrcft,
+        "legacy",
+        r#"{ "id": "legacy", "runtime": "Vrcft", "library": "Legacy.dll" }"#,
+    );
+    std::fs::write(dirs.vrcft.join("legacy/Legacy.dll"), b"").unwrap();
+
+    let files = dirs.discover();
+    let names: Vec<_> = files
+        .iter()
+        .map(|f| (f.name.as_str(), f.kind, f.unavailable.is_none()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("broken", ModuleKind::Native, false),
+            ("eye_tracker", ModuleKind::Native, true),
+            ("legacy", ModuleKind::Vrcft, true),
+            ("old_abi", ModuleKind::Native, false),
+            ("other_os", ModuleKind::Native, false),
+        ]
+    );
+    let eye_file = &files[1];
+    assert_eq!(eye_file.path, eye.join("libeye.so"));
+    assert_eq!(eye_file.manifest.as_ref().unwrap().version, "1.2.0");
+    assert!(files[3]
+        .unavailable
+        .as_deref()
+        .unwrap()
+        .contains("ABI version mismatch"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn discovery_loads_nothing() {
+    let (root, dirs) = plugin_dirs("lazy");
+    let eye = write_plugin(
+        &dirs.native,
+        "eye",
+        r#"{ "id": "eye", "library": "libeye.so", "settings_schema": { "type": "object" } }"#,
+    );
+    std::fs::write(eye.join("libeye.so"), b"not a library").unwrap();
+    write_plugin(
+        &dirs.native,
+        "other_os",
+        &format!(
+            r#"{{ "id": "other_os", "platforms": ["{}_not"], "library": "x.so" }}"#,
+            std::env::consts::OS
+        ),
+    );
+    let control = Arc::new(RwLock::new(ModuleControl::new(dirs)));
+    let settings = Arc::new(RwLock::new(ModuleSettingsStore::default()));
+
+    let (tx, _rx) = sync_channel(1);
+    let mut manager = ModuleManager::new(
+        ModuleContext {
+            status: SharedModuleStatus::default(),
+            calibration_request: Arc::default(),
+            settings: settings.clone(),
+            descriptor: Arc::default(),
+            emitter: Arc::new(FrameEmitter::new(tx, None)),
+        },
+        control.clone(),
+    );
+
+    manager.discover();
+    assert!(manager.modules().is_empty());
+    assert!(settings.read().unwrap().schema("eye").is_some());
+    let listing = control.read().unwrap().listing();
+    assert_eq!(listing.len(), 2);
+    assert!(listing[0].available && listing[0].error.is_none() && !listing[0].loaded);
+    assert!(!listing[1].available);
+
+    // Only a selected module is loaded, and an unavailable one is refused up front.
+    assert!(manager.activate("eye").is_err());
+    let error = manager.activate("other_os").unwrap_err();
+    assert!(format!("{:#}", error).contains("does not support"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn instances_are_listed_under_their_own_name() {
+    let (root, dirs) = plugin_dirs("instances");
+    std::fs::write(dirs.native.join("net_input.so"), b"").unwrap();
+    let config: ModuleConfig = serde_json::from_str(
+        r#"{
+            "instances": {
+                "net_left": { "module": "net_input.so" },
+                "net_right": { "module": "net_input.so" },
+                "orphan": { "module": "missing.so" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut control = ModuleControl::new(dirs.clone()).with_instances(config.instances);
+
+    let listing = control.listing();
+    let names: Vec<_> = listing
+        .iter()
+        .map(|m| (m.name.as_str(), m.instance_of.as_deref(), m.available))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("net_input.so", None, true),
+            ("net_left", Some("net_input.so"), true),
+            ("net_right", Some("net_input.so"), true),
+            ("orphan", Some("missing.so"), false),
+        ]
+    );
+    assert_eq!(listing[1].path, dirs.native.join("net_input.so"));
+    assert!(listing[3].error.as_deref().unwrap().contains("missing.so"));
+
+    control
+        .request(ModuleCommand::Activate("net_right".to_string()))
+        .unwrap();
+    assert_eq!(
+        control.find("net_left").unwrap().instance_of.as_deref(),
+        Some("net_input.so")
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn dotted_instances_get_their_own_state_directory() {
+    let (root, _) = plugin_dirs("instance_state");
+    let left = module_state_dir(&root, "net.left").unwrap();
+    let right = module_state_dir(&root, "net.right").unwrap();
+    assert_ne!(left, right);
+    assert_eq!(left, root.join("net.left"));
+    assert_ne!(
+        module_state_dir(&root, "vd_module"),
+        module_state_dir(&root, "vd_module.dll")
+    );
+    assert_eq!(
+        module_state_dir(&root, "../up").unwrap(),
+        root.join(".._up")
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,41 @@
+//! Module metrics aggregation tests
+
+use vrft_d::plugins::metrics::ModuleMetrics;
+
+#[test]
+fn counters_add_up() {
+    let mut metrics = ModuleMetrics::default();
+    metrics.add("frames", 1);
+    metrics.add("frames", 2);
+    metrics.add("reconnects", 0);
+    assert_eq!(metrics.counters["frames"], 3);
+    assert_eq!(metrics.counters["reconnects"], 0);
+
+    metrics.add("frames", u64::MAX);
+    assert_eq!(metrics.counters["frames"], u64::MAX);
+}
+
+#[test]
+fn gauges_keep_last_value_and_range() {
+    let mut metrics = ModuleMetrics::default();
+    for value in [0.5, 1.0, 0.0, 0.5] {
+        metrics.set("confidence", value);
+    }
+    metrics.set("confidence", f64::NAN);
+
+    let gauge = metrics.gauges["confidence"];
+    assert_eq!(gauge.value, 0.5);
+    assert_eq!(gauge.min, 0.0);
+    assert_eq!(gauge.max, 1.0);
+    assert_eq!(gauge.mean, 0.5);
+    assert_eq!(gauge.samples, 4);
+}
+
+#[test]
+fn reset_clears_everything() {
+    let mut metrics = ModuleMetrics::default();
+    metrics.add("frames", 1);
+    metrics.set("confidence", 1.0);
+    metrics.reset();
+    assert_eq!(metrics, ModuleMetrics::default());
+}
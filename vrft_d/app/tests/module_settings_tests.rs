@@ -0,0 +1,80 @@
+//! Module settings store tests
+
+use common::ModuleSettings;
+use serde_json::json;
+use std::collections::HashMap;
+use vrft_d::plugins::settings::ModuleSettingsStore;
+
+#[test]
+fn starts_from_config_and_defaults_to_empty() {
+    let config = HashMap::from([("a.dll".to_string(), json!({ "x": 1 }))]);
+    let store = ModuleSettingsStore::new(&config);
+    assert_eq!(store.get("a.dll").value(), &json!({ "x": 1 }));
+    assert_eq!(store.get("b.dll").value(), &json!({}));
+}
+
+#[test]
+fn updates_are_validated_and_queued_once() {
+    let mut store = ModuleSettingsStore::new(&HashMap::new());
+    store.set_schema(
+        "a.dll",
+        Some(json!({ "type": "object", "additionalProperties": false, "properties": { "x": { "type": "number" } } })),
+    );
+
+    assert!(store
+        .update("a.dll", ModuleSettings::new(json!({ "y": 1 })))
+        .is_err());
+    assert!(store.take_pending().is_empty());
+
+    store
+        .update("a.dll", ModuleSettings::new(json!({ "x": 1 })))
+        .unwrap();
+    store
+        .update("a.dll", ModuleSettings::new(json!({ "x": 2 })))
+        .unwrap();
+    store
+        .update("b.dll", ModuleSettings::new(json!({ "anything": true })))
+        .unwrap();
+
+    let pending = store.take_pending();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].0, "a.dll");
+    assert_eq!(pending[0].1.value(), &json!({ "x": 2 }));
+    assert_eq!(pending[1].0, "b.dll");
+    assert!(store.take_pending().is_empty());
+}
+
+#[test]
+fn updates_are_written_to_the_config_file() {
+    let dir = std::env::temp_dir().join(format!("vrft_settings_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.json");
+    std::fs::write(
+        &path,
+        json!({
+            "osc": { "port": 9000 },
+            "module": { "active": "a.dll", "settings": { "b.dll": { "z": 0 } } }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let mut store = ModuleSettingsStore::new(&HashMap::new()).with_config_file(&path);
+    assert!(store
+        .update("a.dll", ModuleSettings::new(json!({ "x": 1 })))
+        .unwrap());
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(config["osc"]["port"], 9000);
+    assert_eq!(config["module"]["active"], "a.dll");
+    assert_eq!(config["module"]["settings"]["a.dll"], json!({ "x": 1 }));
+    assert_eq!(config["module"]["settings"]["b.dll"], json!({ "z": 0 }));
+
+    // A failed write keeps the change in memory only.
+    std::fs::remove_file(&path).unwrap();
+    assert!(!store
+        .update("a.dll", ModuleSettings::new(json!({ "x": 2 })))
+        .unwrap());
+    assert_eq!(store.get("a.dll").value(), &json!({ "x": 2 }));
+    let _ = std::fs::remove_dir_all(&dir);
+}
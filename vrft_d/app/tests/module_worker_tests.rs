@@ -0,0 +1,257 @@
+//! Module poll thread tests
+
+use anyhow::Result;
+use api::{HostServices, TrackingModule, TrackingState, UnifiedExpressions, UnifiedTrackingData};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use vrft_d::plugins::emitter::FrameEmitter;
+use vrft_d::plugins::host::{ModuleHost, SharedModuleStatus};
+use vrft_d::plugins::metrics::{HOST_DEADLINE_MISSES, HOST_FRAMES};
+use vrft_d::plugins::worker::{
+    after_workers, decay_toward_neutral, try_lock_module, PollWorker, SharedModule,
+};
+
+/// Delivers `frames` frames, one per update, then returns `then`. Blocks while `blocked`
+/// is set.
+struct Scripted {
+    blocked: Arc<AtomicBool>,
+    then: TrackingState,
+    frames: u32,
+}
+
+impl TrackingModule for Scripted {
+    fn initialize(&mut self, _host: HostServices) -> Result<()> {
+        Ok(())
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        while self.blocked.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        if self.frames == 0 {
+            return self.then;
+        }
+        self.frames -= 1;
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight = 0.5;
+        TrackingState::NewFrame
+    }
+
+    fn unload(&mut self) {}
+}
+
+struct Fixture {
+    status: SharedModuleStatus,
+    rx: Receiver<UnifiedTrackingData>,
+    module: SharedModule,
+    worker: PollWorker,
+}
+
+fn start(module: Scripted, deadline: Duration) -> Fixture {
+    let status = SharedModuleStatus::default();
+    let host = Arc::new(ModuleHost::new(
+        "scripted",
+        status.clone(),
+        Arc::new(RwLock::new(None)),
+    ));
+    let (tx, rx) = sync_channel(16);
+    let emitter = Arc::new(FrameEmitter::new(tx, None));
+    emitter.set_active(Some("scripted"));
+    let module: SharedModule = Arc::new(Mutex::new(Box::new(module)));
+    let worker = PollWorker::spawn("scripted", module.clone(), host, emitter, deadline).unwrap();
+    Fixture {
+        status,
+        rx,
+        module,
+        worker,
+    }
+}
+
+fn counter(status: &SharedModuleStatus, name: &str) -> u64 {
+    status.read().unwrap()["scripted"]
+        .metrics
+        .counters
+        .get(name)
+        .copied()
+        .unwrap_or(0)
+}
+
+fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn frames_are_emitted_from_the_worker_thread() {
+    let fixture = start(
+        Scripted {
+            blocked: Arc::default(),
+            then: TrackingState::NoNewFrame,
+            frames: 3,
+        },
+        Duration::from_secs(1),
+    );
+
+    for _ in 0..3 {
+        let frame = fixture.rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            frame.shapes[UnifiedExpressions::JawOpen as usize].weight,
+            0.5
+        );
+        assert_ne!(frame.timestamp_us, 0);
+    }
+    wait_for("the frames to be counted", || {
+        counter(&fixture.status, HOST_FRAMES) == 3
+    });
+    assert_eq!(counter(&fixture.status, HOST_DEADLINE_MISSES), 0);
+}
+
+#[test]
+fn stuck_updates_are_visible_and_counted() {
+    let blocked = Arc::new(AtomicBool::new(true));
+    let fixture = start(
+        Scripted {
+            blocked: blocked.clone(),
+            then: TrackingState::NoNewFrame,
+            frames: 1,
+        },
+        Duration::from_millis(20),
+    );
+
+    wait_for("the update to overrun", || {
+        fixture
+            .worker
+            .busy_for()
+            .is_some_and(|busy| busy > Duration::from_millis(20))
+    });
+    assert!(fixture.rx.try_recv().is_err());
+
+    blocked.store(false, Ordering::SeqCst);
+    fixture.rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    wait_for("the miss to be counted", || {
+        counter(&fixture.status, HOST_DEADLINE_MISSES) == 1
+    });
+}
+
+#[test]
+fn stopping_does_not_wait_for_a_stuck_update() {
+    let blocked = Arc::new(AtomicBool::new(true));
+    let fixture = start(
+        Scripted {
+            blocked: blocked.clone(),
+            then: TrackingState::NoNewFrame,
+            frames: 1,
+        },
+        Duration::from_millis(20),
+    );
+    wait_for("the update to start", || {
+        fixture.worker.busy_for().is_some()
+    });
+
+    fixture.worker.stop();
+    blocked.store(false, Ordering::SeqCst);
+    // Dropping joins the thread once the update returns; its frame is not emitted.
+    drop(fixture.worker);
+    assert!(fixture.rx.try_recv().is_err());
+}
+
+#[test]
+fn stuck_workers_are_waited_for_off_the_calling_thread() {
+    let blocked = Arc::new(AtomicBool::new(true));
+    let fixture = start(
+        Scripted {
+            blocked: blocked.clone(),
+            then: TrackingState::NoNewFrame,
+            frames: 1,
+        },
+        Duration::from_millis(20),
+    );
+    wait_for("the update to start", || {
+        fixture.worker.busy_for().is_some()
+    });
+    assert!(try_lock_module(&fixture.module).is_none());
+
+    fixture.worker.stop();
+    let (done_tx, done_rx) = sync_channel(1);
+    after_workers(vec![fixture.worker], move || done_tx.send(()).unwrap());
+    assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+    blocked.store(false, Ordering::SeqCst);
+    done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(try_lock_module(&fixture.module).is_some());
+}
+
+#[test]
+fn panics_and_fatal_states_stop_polling() {
+    let fixture = start(
+        Scripted {
+            blocked: Arc::default(),
+            then: TrackingState::Fatal,
+            frames: 0,
+        },
+        Duration::from_secs(1),
+    );
+    wait_for("the module to fail", || fixture.worker.is_fatal());
+    assert_eq!(fixture.worker.take_fault(), None);
+
+    struct Panicking;
+    impl TrackingModule for Panicking {
+        fn initialize(&mut self, _host: HostServices) -> Result<()> {
+            Ok(())
+        }
+        fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+            panic!("sensor unplugged");
+        }
+        fn unload(&mut self) {}
+    }
+    let (tx, _rx) = sync_channel(1);
+    let module: SharedModule = Arc::new(Mutex::new(Box::new(Panicking)));
+    let worker = PollWorker::spawn(
+        "panicking",
+        module.clone(),
+        Arc::new(ModuleHost::new(
+            "panicking",
+            SharedModuleStatus::default(),
+            Arc::new(RwLock::new(None)),
+        )),
+        Arc::new(FrameEmitter::new(tx, None)),
+        Duration::from_secs(1),
+    )
+    .unwrap();
+    let mut fault = None;
+    wait_for("the panic to be reported", || {
+        fault = worker.take_fault();
+        fault.is_some()
+    });
+    assert_eq!(fault.as_deref(), Some("sensor unplugged"));
+    // The module stays usable for unloading.
+    module.lock().unwrap().unload();
+}
+
+#[test]
+fn decay_moves_towards_a_neutral_face() {
+    let mut data = UnifiedTrackingData::default();
+    data.shapes[UnifiedExpressions::JawOpen as usize].weight = 0.8;
+    data.eye.left.openness = 0.2;
+    data.head.head_yaw = 30.0;
+
+    let mut half = data.clone();
+    decay_toward_neutral(&mut half, 0.5);
+    assert!((half.shapes[UnifiedExpressions::JawOpen as usize].weight - 0.4).abs() < 1e-6);
+    assert!((half.eye.left.openness - 0.6).abs() < 1e-6);
+    assert!((half.head.head_yaw - 15.0).abs() < 1e-6);
+
+    decay_toward_neutral(&mut data, 1.0);
+    assert_eq!(
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight,
+        0.0
+    );
+    assert_eq!(data.eye.left.openness, 1.0);
+    assert_eq!(data.eye.left.gaze, api::gaze::FORWARD);
+    assert_eq!(data.head.head_yaw, 0.0);
+}
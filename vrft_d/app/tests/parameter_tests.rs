@@ -416,3 +416,39 @@ mod send_on_load {
         );
     }
 }
+
+mod native_params {
+    use api::gaze;
+    use common::UnifiedTrackingData;
+    use rosc::OscType;
+    use std::collections::{HashMap, HashSet};
+    use vrft_d::osc::parameters::native_param::create_native_parameters;
+
+    #[test]
+    fn eye_pitch_yaw_is_sent_in_degrees_with_pitch_down() {
+        let mut params = create_native_parameters();
+        let param = &mut params[0];
+        // Sent to avatars without eye X/Y parameters.
+        param.reset(&HashSet::new(), &HashMap::new());
+
+        let mut data = UnifiedTrackingData::default();
+        // Left eye 10° up and 20° right, right eye 5° down and 15° left.
+        data.eye.left.gaze = gaze::from_yaw_pitch(20f32.to_radians(), 10f32.to_radians());
+        data.eye.right.gaze = gaze::from_yaw_pitch(-15f32.to_radians(), -5f32.to_radians());
+
+        let messages = param.process(&data);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].addr, "/tracking/eye/LeftRightPitchYaw");
+        let values: Vec<f32> = messages[0]
+            .args
+            .iter()
+            .map(|arg| match arg {
+                OscType::Float(v) => *v,
+                other => panic!("unexpected argument {:?}", other),
+            })
+            .collect();
+        for (value, expected) in values.iter().zip([-10.0, 20.0, 5.0, -15.0]) {
+            assert!((value - expected).abs() < 1e-3, "{:?}", values);
+        }
+    }
+}
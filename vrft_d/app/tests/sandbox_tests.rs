@@ -0,0 +1,107 @@
+//! Out-of-process module host tests
+
+use api::{TrackingState, UnifiedTrackingData};
+use std::io::{BufRead, BufReader};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use vrft_d::plugins::sandbox::{
+    write_message, ChildMessage, HostMessage, SandboxModule, TOKEN_ENV,
+};
+
+fn host_exe() -> &'static Path {
+    Path::new(env!("CARGO_BIN_EXE_vrft_module_host"))
+}
+
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vrft_sandbox_{}_{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn messages_are_json_lines() {
+    let mut data = UnifiedTrackingData::default();
+    data.eye.left.openness = 0.25;
+    data.shapes[3].weight = 0.5;
+    data.timestamp_us = 42;
+
+    let messages = [
+        ChildMessage::Frame {
+            data: Box::new(data),
+        },
+        ChildMessage::State {
+            state: TrackingState::Disconnected,
+        },
+        ChildMessage::Reply { error: None },
+    ];
+    let mut buffer = Vec::new();
+    for message in &messages {
+        write_message(&mut buffer, message).unwrap();
+    }
+
+    let text = String::from_utf8(buffer).unwrap();
+    let parsed: Vec<ChildMessage> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(parsed, messages);
+
+    let shutdown: HostMessage = serde_json::from_str(r#"{"type":"shutdown"}"#).unwrap();
+    assert_eq!(shutdown, HostMessage::Shutdown);
+}
+
+#[test]
+fn load_failures_in_the_child_are_reported() {
+    let dir = temp_dir("broken");
+    let module = dir.join("broken.so");
+    std::fs::write(&module, b"not a library").unwrap();
+
+    let mut sandbox = SandboxModule::new(host_exe(), &module);
+    let error = format!("{:#}", sandbox.start().unwrap_err());
+    assert!(error.contains("Failed to load module"), "{}", error);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_child_greets_with_its_token() {
+    let dir = temp_dir("token");
+    let module = dir.join("broken.so");
+    std::fs::write(&module, b"not a library").unwrap();
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+    let mut child = Command::new(host_exe())
+        .arg(&module)
+        .arg(listener.local_addr().unwrap().port().to_string())
+        .env(TOKEN_ENV, "0123abcd")
+        .spawn()
+        .unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    child.wait().unwrap();
+
+    match serde_json::from_str(&line).unwrap() {
+        ChildMessage::Hello {
+            pid, token, error, ..
+        } => {
+            assert_eq!(pid, child.id());
+            assert_eq!(token, "0123abcd");
+            assert!(error.unwrap().contains("Failed to load module"));
+        }
+        other => panic!("expected a greeting, got {:?}", other),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_host_is_reported() {
+    let dir = temp_dir("missing_host");
+    let mut sandbox = SandboxModule::new(&dir.join("no_such_host"), &dir.join("module.so"));
+    let error = format!("{:#}", sandbox.start().unwrap_err());
+    assert!(error.contains("not found"), "{}", error);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
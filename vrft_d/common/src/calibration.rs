@@ -248,24 +248,21 @@ pub struct CalibrationData {
 
 impl Default for CalibrationData {
     fn default() -> Self {
-        let mut shapes = Vec::with_capacity(UnifiedExpressions::Max as usize);
-        for i in 0..UnifiedExpressions::Max as usize {
-            let expr = UnifiedExpressions::try_from(i).expect("index within valid enum range");
-            shapes.push(CalibrationParameter {
-                name: format!("{:?}", expr),
+        let shapes = UnifiedExpressions::iter()
+            .map(|expr| CalibrationParameter {
+                name: expr.name().to_string(),
                 ..Default::default()
-            });
-        }
+            })
+            .collect();
         Self { shapes }
     }
 }
 
 impl CalibrationData {
     pub fn clear(&mut self) {
-        for i in 0..self.shapes.len() {
-            let expr = UnifiedExpressions::try_from(i).expect("index within valid enum range");
-            self.shapes[i] = CalibrationParameter {
-                name: format!("{:?}", expr),
+        for (shape, expr) in self.shapes.iter_mut().zip(UnifiedExpressions::iter()) {
+            *shape = CalibrationParameter {
+                name: expr.name().to_string(),
                 ..Default::default()
             };
         }
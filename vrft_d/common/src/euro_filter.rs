@@ -54,6 +54,11 @@ impl EuroFilter {
         hat_x
     }
 
+    /// The last filtered value, if the filter has seen any input.
+    pub fn last(&self) -> Option<f32> {
+        self.initialized.then_some(self.x_prev)
+    }
+
     pub fn filter(&mut self, x: f32) -> f32 {
         if x.is_nan() {
             return 0.0;
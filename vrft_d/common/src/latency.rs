@@ -0,0 +1,175 @@
+//! Latency histograms for the capture → mutate → send path.
+//!
+//! Frames carry their capture time (`UnifiedTrackingData::timestamp_us`), so each stage
+//! can measure how far behind the device it is running.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Upper bounds of the histogram buckets in microseconds. Samples above the last bound
+/// land in an overflow bucket.
+pub const LATENCY_BUCKETS_US: [u64; 12] = [
+    250, 500, 1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000, 128_000, 256_000, 512_000,
+];
+
+/// Stage of the pipeline a latency sample belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyStage {
+    /// Capture until the consumer starts mutating the frame.
+    CaptureToMutate,
+    /// Time spent in the mutation pipeline.
+    Mutate,
+    /// Time spent handing the frame to the output backend.
+    Send,
+    /// Capture until the frame has been sent.
+    CaptureToSend,
+}
+
+impl LatencyStage {
+    pub const ALL: [LatencyStage; 4] = [
+        LatencyStage::CaptureToMutate,
+        LatencyStage::Mutate,
+        LatencyStage::Send,
+        LatencyStage::CaptureToSend,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatencyStage::CaptureToMutate => "capture_to_mutate",
+            LatencyStage::Mutate => "mutate",
+            LatencyStage::Send => "send",
+            LatencyStage::CaptureToSend => "capture_to_send",
+        }
+    }
+}
+
+/// Fixed-bucket histogram of microsecond samples.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS_US.len() + 1],
+    count: u64,
+    sum_us: u64,
+    min_us: u64,
+    max_us: u64,
+}
+
+/// Serializable view of a [`LatencyHistogram`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub mean_us: f64,
+    pub min_us: u64,
+    pub max_us: u64,
+    /// Percentiles are bucket upper bounds, so they are conservative.
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    /// `(upper bound, count)` pairs; the overflow bucket has an upper bound of `u64::MAX`.
+    pub buckets: Vec<(u64, u64)>,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, us: u64) {
+        let index = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.buckets[index] += 1;
+
+        if self.count == 0 || us < self.min_us {
+            self.min_us = us;
+        }
+        self.max_us = self.max_us.max(us);
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Upper bound of the bucket containing the `q` quantile (`0.0..=1.0`).
+    pub fn percentile(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = ((self.count as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return LATENCY_BUCKETS_US
+                    .get(index)
+                    .copied()
+                    .unwrap_or(self.max_us);
+            }
+        }
+        self.max_us
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            count: self.count,
+            mean_us: if self.count == 0 {
+                0.0
+            } else {
+                self.sum_us as f64 / self.count as f64
+            },
+            min_us: self.min_us,
+            max_us: self.max_us,
+            p50_us: self.percentile(0.50),
+            p95_us: self.percentile(0.95),
+            p99_us: self.percentile(0.99),
+            buckets: LATENCY_BUCKETS_US
+                .iter()
+                .copied()
+                .chain(std::iter::once(u64::MAX))
+                .zip(self.buckets.iter().copied())
+                .collect(),
+        }
+    }
+}
+
+/// One histogram per [`LatencyStage`], shared between the consumer thread and the HTTP API.
+#[derive(Debug, Default)]
+pub struct PipelineLatency {
+    stages: Mutex<[LatencyHistogram; 4]>,
+}
+
+impl PipelineLatency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, stage: LatencyStage, us: u64) {
+        if let Ok(mut stages) = self.stages.lock() {
+            stages[stage as usize].record(us);
+        }
+    }
+
+    /// Records the time elapsed since `timestamp_us` (a capture timestamp) for `stage`.
+    ///
+    /// Frames without a timestamp and timestamps from the future are ignored.
+    pub fn record_since(&self, stage: LatencyStage, timestamp_us: u64, now_us: u64) {
+        if timestamp_us != 0 && now_us >= timestamp_us {
+            self.record(stage, now_us - timestamp_us);
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<(LatencyStage, LatencySnapshot)> {
+        let stages = match self.stages.lock() {
+            Ok(stages) => stages.clone(),
+            Err(_) => return Vec::new(),
+        };
+        LatencyStage::ALL
+            .iter()
+            .map(|&stage| (stage, stages[stage as usize].snapshot()))
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut stages) = self.stages.lock() {
+            *stages = Default::default();
+        }
+    }
+}
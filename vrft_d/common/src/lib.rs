@@ -1,11 +1,13 @@
 pub use api::{
-    TrackingModule, UnifiedExpressionShape, UnifiedExpressions, UnifiedEyeData, UnifiedHeadData,
-    UnifiedSingleEyeData, UnifiedTrackingData,
+    gaze, timestamp_us_now, FacialRegion, ModuleCapabilities, ModuleDescriptor, ModuleSettings,
+    Side, TrackingModule, UnifiedExpressionShape, UnifiedExpressions, UnifiedEyeData,
+    UnifiedHeadData, UnifiedSingleEyeData, UnifiedTrackingData,
 };
 
 mod calibration;
 pub mod calibration_manager;
 mod euro_filter;
+mod latency;
 mod mutator;
 
 pub mod mutation_trait;
@@ -17,7 +19,11 @@ pub use calibration::{
     S_DELTA,
 };
 pub use euro_filter::EuroFilter;
+pub use latency::{
+    LatencyHistogram, LatencySnapshot, LatencyStage, PipelineLatency, LATENCY_BUCKETS_US,
+};
 pub use mutator::{
-    CalibrationConfig, IntegrationAdapter, ModuleConfig, ModuleRuntime, MutationConfig,
-    MutatorConfig, OscConfig, OutputMode, UnifiedTrackingMutator,
+    CalibrationConfig, IntegrationAdapter, IntegrityConfig, ModuleConfig, ModuleInstance,
+    ModuleRuntime, ModuleSources, MutationConfig, MutatorConfig, OscConfig, OutputMode,
+    StallBehavior, UnifiedTrackingMutator,
 };
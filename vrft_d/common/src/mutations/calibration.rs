@@ -102,11 +102,14 @@ impl Mutation for CalibrationMutation {
             if i < self.manager.data.shapes.len() {
                 let raw_weight = data.shapes[i].weight;
 
-                self.manager.data.shapes[i].update_calibration(
-                    raw_weight,
-                    self.config.continuous,
-                    dt,
-                );
+                // Low-confidence samples would skew the collected range.
+                if data.shape_confidence(i) >= self.config.min_confidence {
+                    self.manager.data.shapes[i].update_calibration(
+                        raw_weight,
+                        self.config.continuous,
+                        dt,
+                    );
+                }
 
                 data.shapes[i].weight =
                     self.manager.data.shapes[i].calculate_parameter(raw_weight, self.config.blend);
@@ -1,15 +1,18 @@
 use crate::mutation_trait::Mutation;
 use crate::mutator::MutationConfig;
-use crate::{EuroFilter, UnifiedExpressions, UnifiedTrackingData};
+use crate::{gaze, EuroFilter, UnifiedExpressions, UnifiedTrackingData};
 use anyhow::Result;
+use glam::Vec3;
 use std::any::Any;
 
 pub struct SmoothingMutation {
     shapes: Vec<EuroFilter>,
     gaze_left_x: EuroFilter,
     gaze_left_y: EuroFilter,
+    gaze_left_z: EuroFilter,
     gaze_right_x: EuroFilter,
     gaze_right_y: EuroFilter,
+    gaze_right_z: EuroFilter,
     pupil_left: EuroFilter,
     pupil_right: EuroFilter,
     openness_left: EuroFilter,
@@ -27,8 +30,10 @@ impl SmoothingMutation {
             ],
             gaze_left_x: EuroFilter::new_with_config(min_cutoff, beta),
             gaze_left_y: EuroFilter::new_with_config(min_cutoff, beta),
+            gaze_left_z: EuroFilter::new_with_config(min_cutoff, beta),
             gaze_right_x: EuroFilter::new_with_config(min_cutoff, beta),
             gaze_right_y: EuroFilter::new_with_config(min_cutoff, beta),
+            gaze_right_z: EuroFilter::new_with_config(min_cutoff, beta),
             pupil_left: EuroFilter::new_with_config(min_cutoff, beta),
             pupil_right: EuroFilter::new_with_config(min_cutoff, beta),
             openness_left: EuroFilter::new_with_config(min_cutoff, beta),
@@ -60,21 +65,51 @@ impl Mutation for SmoothingMutation {
     }
 
     fn mutate(&mut self, data: &mut UnifiedTrackingData, _dt: f32) {
-        data.eye.left.openness = self.openness_left.filter(data.eye.left.openness);
-        data.eye.right.openness = self.openness_right.filter(data.eye.right.openness);
-
-        data.eye.left.gaze.x = self.gaze_left_x.filter(data.eye.left.gaze.x);
-        data.eye.left.gaze.y = self.gaze_left_y.filter(data.eye.left.gaze.y);
-        data.eye.right.gaze.x = self.gaze_right_x.filter(data.eye.right.gaze.x);
-        data.eye.right.gaze.y = self.gaze_right_y.filter(data.eye.right.gaze.y);
+        // Channels the device lost this frame hold their last smoothed value instead of
+        // feeding garbage into the filters.
+        if data.eye.left.status.valid {
+            data.eye.left.openness = self.openness_left.filter(data.eye.left.openness);
+            data.eye.left.gaze = gaze::normalize(Vec3::new(
+                self.gaze_left_x.filter(data.eye.left.gaze.x),
+                self.gaze_left_y.filter(data.eye.left.gaze.y),
+                self.gaze_left_z.filter(data.eye.left.gaze.z),
+            ));
+            data.eye.left.pupil_diameter_mm =
+                self.pupil_left.filter(data.eye.left.pupil_diameter_mm);
+        } else {
+            hold(&self.openness_left, &mut data.eye.left.openness);
+            hold_gaze(
+                [&self.gaze_left_x, &self.gaze_left_y, &self.gaze_left_z],
+                &mut data.eye.left.gaze,
+            );
+            hold(&self.pupil_left, &mut data.eye.left.pupil_diameter_mm);
+        }
 
-        data.eye.left.pupil_diameter_mm = self.pupil_left.filter(data.eye.left.pupil_diameter_mm);
-        data.eye.right.pupil_diameter_mm =
-            self.pupil_right.filter(data.eye.right.pupil_diameter_mm);
+        if data.eye.right.status.valid {
+            data.eye.right.openness = self.openness_right.filter(data.eye.right.openness);
+            data.eye.right.gaze = gaze::normalize(Vec3::new(
+                self.gaze_right_x.filter(data.eye.right.gaze.x),
+                self.gaze_right_y.filter(data.eye.right.gaze.y),
+                self.gaze_right_z.filter(data.eye.right.gaze.z),
+            ));
+            data.eye.right.pupil_diameter_mm =
+                self.pupil_right.filter(data.eye.right.pupil_diameter_mm);
+        } else {
+            hold(&self.openness_right, &mut data.eye.right.openness);
+            hold_gaze(
+                [&self.gaze_right_x, &self.gaze_right_y, &self.gaze_right_z],
+                &mut data.eye.right.gaze,
+            );
+            hold(&self.pupil_right, &mut data.eye.right.pupil_diameter_mm);
+        }
 
         for i in 0..data.shapes.len() {
             if i < self.shapes.len() {
-                data.shapes[i].weight = self.shapes[i].filter(data.shapes[i].weight);
+                if data.face.valid {
+                    data.shapes[i].weight = self.shapes[i].filter(data.shapes[i].weight);
+                } else {
+                    hold(&self.shapes[i], &mut data.shapes[i].weight);
+                }
             }
         }
     }
@@ -91,3 +126,16 @@ impl Mutation for SmoothingMutation {
         self
     }
 }
+
+/// Replaces `value` with the filter's last output, if it has one.
+fn hold(filter: &EuroFilter, value: &mut f32) {
+    if let Some(last) = filter.last() {
+        *value = last;
+    }
+}
+
+fn hold_gaze(filters: [&EuroFilter; 3], direction: &mut Vec3) {
+    if let [Some(x), Some(y), Some(z)] = filters.map(EuroFilter::last) {
+        *direction = gaze::normalize(Vec3::new(x, y, z));
+    }
+}
@@ -1,9 +1,11 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use crate::mutation_trait::Mutation;
 use crate::mutations::{CalibrationMutation, NormalizationMutation, SmoothingMutation};
-use crate::{CalibrationData, CalibrationState, UnifiedTrackingData};
+use crate::{CalibrationData, CalibrationState, FacialRegion, UnifiedTrackingData};
 use anyhow::Result;
 use log::info;
 
@@ -19,7 +21,7 @@ pub enum OutputMode {
 }
 
 /// Which module runtime to use for loading tracking modules.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum ModuleRuntime {
     /// Only load native (.dll/.so) modules from plugins/native
     #[serde(alias = "native")]
@@ -30,28 +32,118 @@ pub enum ModuleRuntime {
     Vrcft,
 }
 
+/// What the output shows while the active module is stuck in `update`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StallBehavior {
+    /// Keep the last frame.
+    #[default]
+    Hold,
+    /// Relax the last frame towards a neutral face over a few seconds.
+    Decay,
+}
+
+/// A named instance of a module, with its own settings, state and logger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleInstance {
+    /// The module to instantiate: a library file name or a plugin id.
+    pub module: String,
+}
+
+/// Checks a native module library must pass before it is loaded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct IntegrityConfig {
+    /// Refuse libraries that pass neither check.
+    pub enabled: bool,
+    /// Hex SHA-256 digests of the libraries allowed to load.
+    pub sha256: Vec<String>,
+    /// Hex Ed25519 public keys; a library with a detached `<library>.sig` signature by one
+    /// of them may load.
+    pub signing_keys: Vec<String>,
+}
+
+/// Modules that feed channels of the output next to the active module, e.g. an eye tracker
+/// next to a lip tracker. Channels left out come from the active module.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ModuleSources {
+    pub eye_gaze: Option<String>,
+    pub eye_openness: Option<String>,
+    pub pupil: Option<String>,
+    pub head: Option<String>,
+    /// Expression shapes by facial region, e.g. `{"lip": "lip_tracker.dll"}`.
+    pub regions: BTreeMap<FacialRegion, String>,
+}
+
 /// Module loading configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ModuleConfig {
     /// Which module runtime to use (Native or .NET)
     pub runtime: ModuleRuntime,
-    /// The active module/plugin to load
-    #[serde(default = "default_active_module")]
-    pub active: String,
+    /// The module to activate, or a failover chain of modules in order of preference
+    #[serde(default = "default_active_modules", deserialize_with = "one_or_many")]
+    pub active: Vec<String>,
+    /// Settings handed to each module, keyed by module name (e.g. `"vd_module.dll"`)
+    pub settings: HashMap<String, Value>,
+    /// Reload native modules when their library file changes. Meant for module development.
+    pub hot_reload: bool,
+    /// Run each native module in its own `vrft_module_host` process, so a crash or hang in
+    /// a module does not take vrft_d down.
+    pub sandbox: bool,
+    /// How long a module's `update` may take before it counts as stuck.
+    pub update_deadline_ms: u64,
+    /// What the output shows while the active module is stuck.
+    pub on_stall: StallBehavior,
+    /// Modules owning some channels instead of the active one.
+    pub sources: ModuleSources,
+    /// How long the active module of a chain may go without frames before falling over to
+    /// the next one, and how long a preferred module must deliver frames to take over again.
+    pub failover_after_ms: u64,
+    /// How long the output cross-fades between two modules of a chain.
+    pub crossfade_ms: u64,
+    /// Allowlist or signature check of native libraries.
+    pub integrity: IntegrityConfig,
+    /// Further instances of modules, keyed by instance name.
+    pub instances: BTreeMap<String, ModuleInstance>,
 }
 
 impl Default for ModuleConfig {
     fn default() -> Self {
         Self {
             runtime: ModuleRuntime::default(),
-            active: default_active_module(),
+            active: default_active_modules(),
+            settings: HashMap::new(),
+            hot_reload: false,
+            sandbox: false,
+            update_deadline_ms: 250,
+            on_stall: StallBehavior::default(),
+            sources: ModuleSources::default(),
+            failover_after_ms: 1000,
+            crossfade_ms: 300,
+            integrity: IntegrityConfig::default(),
+            instances: BTreeMap::new(),
         }
     }
 }
 
-fn default_active_module() -> String {
-    "vd_module.dll".to_string()
+fn default_active_modules() -> Vec<String> {
+    vec!["vd_module.dll".to_string()]
+}
+
+/// Accepts a single module name as well as a list of them.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => vec![name],
+        OneOrMany::Many(names) => names,
+    })
 }
 
 /// Configuration for a single pipeline step
@@ -101,6 +193,8 @@ pub struct CalibrationConfig {
     pub continuous: bool,
     /// Blend factor for calibration (0.0-1.0)
     pub blend: f32,
+    /// Samples with a lower shape confidence are not collected (0.0-1.0)
+    pub min_confidence: f32,
 }
 
 impl Default for CalibrationConfig {
@@ -109,6 +203,7 @@ impl Default for CalibrationConfig {
             enabled: false,
             continuous: false,
             blend: 1.0,
+            min_confidence: 0.5,
         }
     }
 }
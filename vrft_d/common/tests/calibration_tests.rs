@@ -115,7 +115,7 @@ fn test_curve_adjustment_behavior() {
     let adjusted = p.calculate_parameter(raw, 0.0);
 
     // Should be in valid range
-    assert!(adjusted >= 0.0 && adjusted <= 1.0);
+    assert!((0.0..=1.0).contains(&adjusted));
 }
 
 #[test]
@@ -0,0 +1,78 @@
+use common::mutation_trait::Mutation;
+use common::mutations::{CalibrationMutation, SmoothingMutation};
+use common::{gaze, MutationConfig, UnifiedExpressions, UnifiedTrackingData};
+use glam::Vec3;
+
+const JAW: usize = UnifiedExpressions::JawOpen as usize;
+
+#[test]
+fn test_smoothing_holds_lost_eye() {
+    let mut smoothing = SmoothingMutation::new(&MutationConfig::default());
+
+    let mut data = UnifiedTrackingData::default();
+    data.eye.left.openness = 0.8;
+    data.eye.left.gaze = gaze::from_yaw_pitch(0.2, 0.1);
+    smoothing.mutate(&mut data, 0.016);
+    let held = data.eye.left;
+
+    data.eye.left.status.valid = false;
+    data.eye.left.openness = 0.0;
+    data.eye.left.gaze = Vec3::ZERO;
+    smoothing.mutate(&mut data, 0.016);
+
+    assert_eq!(data.eye.left.openness, held.openness);
+    assert!(data.eye.left.gaze.abs_diff_eq(held.gaze, 1e-6));
+}
+
+#[test]
+fn test_smoothing_holds_lost_face() {
+    let mut smoothing = SmoothingMutation::new(&MutationConfig::default());
+
+    let mut data = UnifiedTrackingData::default();
+    data.shapes[JAW].weight = 0.6;
+    smoothing.mutate(&mut data, 0.016);
+
+    data.face.valid = false;
+    data.shapes[JAW].weight = 0.0;
+    smoothing.mutate(&mut data, 0.016);
+
+    assert_eq!(data.shapes[JAW].weight, 0.6);
+}
+
+#[test]
+fn test_shape_confidence_combines_face_status() {
+    let mut data = UnifiedTrackingData::default();
+    assert_eq!(data.shape_confidence(JAW), 1.0);
+
+    data.shape_confidences[JAW] = 0.4;
+    data.face.confidence = 0.9;
+    assert_eq!(data.shape_confidence(JAW), 0.4);
+
+    data.face.valid = false;
+    assert_eq!(data.shape_confidence(JAW), 0.0);
+}
+
+#[test]
+fn test_calibration_skips_low_confidence_samples() {
+    let mut config = MutationConfig::default();
+    config.calibration.enabled = true;
+    config.calibration.min_confidence = 0.5;
+
+    let mut calibration = CalibrationMutation::new(&config);
+    calibration.start_calibration(10.0);
+
+    let mut data = UnifiedTrackingData::default();
+    data.shape_confidences.fill(0.2);
+    for i in 0..100 {
+        data.shapes[JAW].weight = (i % 10) as f32 * 0.1;
+        calibration.mutate(&mut data, 0.05);
+    }
+    assert!(!calibration.has_calibration_data());
+
+    data.shape_confidences.fill(0.9);
+    for i in 0..100 {
+        data.shapes[JAW].weight = (i % 10) as f32 * 0.1;
+        calibration.mutate(&mut data, 0.05);
+    }
+    assert!(calibration.has_calibration_data());
+}
@@ -0,0 +1,65 @@
+use common::{LatencyHistogram, LatencyStage, PipelineLatency, UnifiedTrackingData};
+
+#[test]
+fn test_histogram_buckets_and_percentiles() {
+    let mut h = LatencyHistogram::default();
+    for _ in 0..90 {
+        h.record(800);
+    }
+    for _ in 0..10 {
+        h.record(20_000);
+    }
+
+    let snap = h.snapshot();
+    assert_eq!(snap.count, 100);
+    assert_eq!(snap.min_us, 800);
+    assert_eq!(snap.max_us, 20_000);
+    assert_eq!(snap.p50_us, 1_000);
+    assert_eq!(snap.p95_us, 32_000);
+    assert!((snap.mean_us - 2_720.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_histogram_overflow_bucket() {
+    let mut h = LatencyHistogram::default();
+    h.record(5_000_000);
+
+    let snap = h.snapshot();
+    assert_eq!(snap.buckets.last(), Some(&(u64::MAX, 1)));
+    assert_eq!(snap.p99_us, 5_000_000);
+}
+
+#[test]
+fn test_empty_histogram() {
+    let snap = LatencyHistogram::default().snapshot();
+    assert_eq!(snap.count, 0);
+    assert_eq!(snap.p50_us, 0);
+    assert_eq!(snap.mean_us, 0.0);
+}
+
+#[test]
+fn test_record_since_ignores_missing_and_future_timestamps() {
+    let latency = PipelineLatency::new();
+    latency.record_since(LatencyStage::CaptureToSend, 0, 1_000);
+    latency.record_since(LatencyStage::CaptureToSend, 2_000, 1_000);
+    latency.record_since(LatencyStage::CaptureToSend, 1_000, 1_500);
+
+    let snapshot = latency.snapshot();
+    let (_, send) = snapshot
+        .iter()
+        .find(|(stage, _)| *stage == LatencyStage::CaptureToSend)
+        .unwrap();
+    assert_eq!(send.count, 1);
+    assert_eq!(send.max_us, 500);
+
+    latency.reset();
+    assert!(latency.snapshot().iter().all(|(_, s)| s.count == 0));
+}
+
+#[test]
+fn test_frames_default_to_unstamped() {
+    let data = UnifiedTrackingData::default();
+    assert_eq!(data.timestamp_us, 0);
+    assert_eq!(data.sequence, 0);
+    assert!(common::timestamp_us_now() > 0);
+}
@@ -8,9 +8,36 @@ using Microsoft.Extensions.Logging;
 
 namespace VrcftRuntime;
 
+[StructLayout(LayoutKind.Sequential, Pack = 1)]
+public struct ShmemHeader
+{
+    // "VRFT" in little-endian; must match api::proxy::SHMEM_MAGIC.
+    public const uint Magic = 0x54465256;
+    // Bump together with api::proxy::SHMEM_LAYOUT_VERSION whenever the layout changes.
+    public const uint LayoutVersion = 1;
+    public const int ShapeCount = 200;
+
+    public uint magic;
+    public uint layout_version;
+    public uint struct_size;
+    public uint shape_count;
+    public uint writer_pid;
+
+    public static unsafe ShmemHeader Current() => new ShmemHeader
+    {
+        magic = Magic,
+        layout_version = LayoutVersion,
+        struct_size = (uint)sizeof(MarshaledTrackingData),
+        shape_count = ShapeCount,
+        writer_pid = (uint)Environment.ProcessId,
+    };
+}
+
 [StructLayout(LayoutKind.Sequential, Pack = 1)]
 public unsafe struct MarshaledTrackingData
 {
+    public ShmemHeader header;
+
     public float left_eye_gaze_x;
     public float left_eye_gaze_y;
     public float left_eye_pupil_diameter_mm;
@@ -33,7 +60,7 @@ public unsafe struct MarshaledTrackingData
     public float head_pos_y;
     public float head_pos_z;
 
-    public fixed float shapes[200];
+    public fixed float shapes[ShmemHeader.ShapeCount];
     public ulong main_app_heartbeat;
     public ulong runtime_heartbeat;
 }
@@ -208,15 +235,31 @@ class Program
 
     static unsafe void SetupSharedMemory()
     {
-        _mmf = MemoryMappedFile.CreateOrOpen(@"Local\VRCFT_TrackingData", sizeof(MarshaledTrackingData));
+        string name;
+        if (OperatingSystem.IsWindows())
+        {
+            name = @"Local\VRCFT_TrackingData";
+            _mmf = MemoryMappedFile.CreateOrOpen(name, sizeof(MarshaledTrackingData));
+        }
+        else
+        {
+            // Named maps are Windows-only; this file is what shm_open("/VRCFT_TrackingData") opens.
+            name = "/dev/shm/VRCFT_TrackingData";
+            _mmf = MemoryMappedFile.CreateFromFile(name, FileMode.OpenOrCreate, null, sizeof(MarshaledTrackingData));
+        }
         _accessor = _mmf.CreateViewAccessor();
-        _logger.LogInformation(@"Shared memory setup complete: Local\VRCFT_TrackingData");
+
+        // Start from a clean block with our header; the main app checks it before reading.
+        var data = new MarshaledTrackingData { header = ShmemHeader.Current() };
+        _accessor.Write(0, ref data);
+        _logger.LogInformation("Shared memory setup complete: {Name}", name);
     }
 
     static unsafe void RunLoop()
     {
         _logger.LogInformation("Entering update loop...");
-        var data = new MarshaledTrackingData();
+        var data = new MarshaledTrackingData { header = ShmemHeader.Current() };
+        bool warnedShapeCount = false;
         ulong lastMainAppHeartbeat = 0;
         DateTime lastMainAppUpdate = DateTime.UtcNow;
 
@@ -259,7 +302,12 @@ class Program
                 data.head_pos_z = (float)head.HeadPosZ;
 
                 // Sync shapes
-                int count = Math.Min(200, shapes.Length);
+                int count = Math.Min(ShmemHeader.ShapeCount, shapes.Length);
+                if (shapes.Length > ShmemHeader.ShapeCount && !warnedShapeCount)
+                {
+                    _logger.LogWarning("Module reports {Count} shapes; only the first {Max} are sent", (int)shapes.Length, ShmemHeader.ShapeCount);
+                    warnedShapeCount = true;
+                }
                 for (int i = 0; i < count; i++)
                 {
                     data.shapes[i] = (float)shapes[i].Weight;
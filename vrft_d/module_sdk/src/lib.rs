@@ -0,0 +1,162 @@
+//! SDK for native tracking modules written in Rust.
+//!
+//! A module implements [`TrackingModule`] and hands its constructor to [`export_module!`],
+//! which generates the C ABI entry points `vrft_d` looks for. On top of
+//! [`api::abi::export_module`], the generated code
+//!
+//! - catches a panic in the constructor and reports it as [`VRFT_FAULT`](api::abi::VRFT_FAULT),
+//!   like the panics of every other call,
+//! - logs panic messages through the host logger, so they show up next to the host's own
+//!   report of the fault,
+//! - routes the `log` crate's macros to the host logger of the module instance they are
+//!   called for, so `log::info!` works like [`ModuleLogger::info`],
+//! - fills in the name, version and vendor the module's descriptor leaves empty, from the
+//!   macro arguments or the crate's `Cargo.toml`.
+//!
+//! [`testing::Harness`] drives a module through the same entry points with a fake host.
+
+pub use api;
+pub use api::*;
+pub use log;
+
+mod logging;
+pub mod testing;
+
+use anyhow::Result;
+use api::abi::{VrftModuleVTable, VRFT_FAULT};
+
+/// Name, version and vendor filled into a module's descriptor where it leaves them empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleIdentity {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub vendor: &'static str,
+}
+
+impl ModuleIdentity {
+    fn fill(&self, mut descriptor: ModuleDescriptor) -> ModuleDescriptor {
+        for (field, value) in [
+            (&mut descriptor.name, self.name),
+            (&mut descriptor.version, self.version),
+            (&mut descriptor.vendor, self.vendor),
+        ] {
+            if field.is_empty() {
+                *field = value.to_string();
+            }
+        }
+        descriptor
+    }
+}
+
+/// The [`ModuleIdentity`] of the calling crate: its package name and version, no vendor.
+#[macro_export]
+macro_rules! module_identity {
+    () => {
+        $crate::ModuleIdentity {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            vendor: "",
+        }
+    };
+}
+
+/// Exports a [`TrackingModule`] from a `cdylib`.
+///
+/// Takes an expression creating one module instance, evaluated for every instance the host
+/// creates, optionally followed by `name = ...`, `version = ...` or `vendor = ...` to
+/// override the [`module_identity!`] of the crate. Use it once per library.
+#[macro_export]
+macro_rules! export_module {
+    ($module:expr $(, $field:ident = $value:expr)* $(,)?) => {
+        #[no_mangle]
+        pub extern "C" fn vrft_abi_version() -> u32 {
+            $crate::api::abi::VRFT_ABI_VERSION
+        }
+
+        /// # Safety
+        ///
+        /// `out` must be null or valid for writes of a `VrftModuleVTable`.
+        #[no_mangle]
+        pub unsafe extern "C" fn vrft_create_module(
+            out: *mut $crate::api::abi::VrftModuleVTable,
+        ) -> i32 {
+            let identity = $crate::ModuleIdentity {
+                $($field: $value,)*
+                ..$crate::module_identity!()
+            };
+            $crate::export(out, identity, || $module)
+        }
+    };
+}
+
+/// Creates a module with `create` and writes its function table into `out`. Called by the
+/// code [`export_module!`] generates.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of a `VrftModuleVTable`.
+#[doc(hidden)]
+pub unsafe fn export<T: TrackingModule + 'static>(
+    out: *mut VrftModuleVTable,
+    identity: ModuleIdentity,
+    create: impl FnOnce() -> T,
+) -> i32 {
+    logging::install_panic_hook();
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(create)) {
+        Ok(module) => api::abi::export_module(out, SdkModule::new(module, identity)),
+        Err(_) => VRFT_FAULT,
+    }
+}
+
+/// Wraps a module to log through the host on its behalf and complete its descriptor.
+struct SdkModule<T> {
+    module: T,
+    identity: ModuleIdentity,
+    logger: Option<ModuleLogger>,
+}
+
+impl<T: TrackingModule> SdkModule<T> {
+    fn new(module: T, identity: ModuleIdentity) -> Self {
+        Self {
+            module,
+            identity,
+            logger: None,
+        }
+    }
+
+    fn scoped<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        let module = &mut self.module;
+        logging::scoped(self.logger.as_ref(), || f(module))
+    }
+}
+
+impl<T: TrackingModule> TrackingModule for SdkModule<T> {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        let logger = host.logger().clone();
+        logging::attach(&logger);
+        self.logger = Some(logger);
+        self.scoped(|module| module.initialize(host))
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        self.scoped(|module| module.update(data))
+    }
+
+    fn unload(&mut self) {
+        self.scoped(|module| module.unload())
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+        self.scoped(|module| module.configure(settings))
+    }
+
+    fn start_streaming(&mut self, sink: FrameSink) -> bool {
+        self.scoped(|module| module.start_streaming(sink))
+    }
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        self.module
+            .descriptor()
+            .map(|descriptor| self.identity.fill(descriptor))
+    }
+}
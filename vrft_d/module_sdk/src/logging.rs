@@ -0,0 +1,89 @@
+//! Routes the `log` crate and panic messages to the host logger.
+//!
+//! Every call from the host runs with the logger of the instance it is for. Threads the
+//! module starts itself log through the logger of the instance initialized last.
+
+use api::ModuleLogger;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::cell::RefCell;
+use std::sync::{Once, RwLock};
+
+thread_local! {
+    static CURRENT: RefCell<Option<ModuleLogger>> = const { RefCell::new(None) };
+}
+
+static FALLBACK: RwLock<Option<ModuleLogger>> = RwLock::new(None);
+
+struct HostLog;
+
+static HOST_LOG: HostLog = HostLog;
+
+impl Log for HostLog {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        // The host filters by level.
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let Some(logger) = current() else {
+            return;
+        };
+        let message = record.args().to_string().replace('\0', "");
+        match record.level() {
+            Level::Error => logger.error(&message),
+            Level::Warn => logger.warn(&message),
+            Level::Info => logger.info(&message),
+            Level::Debug => logger.debug(&message),
+            Level::Trace => logger.trace(&message),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Makes `logger` the one for threads outside host calls, and installs the `log` backend
+/// unless the process already has one.
+pub(crate) fn attach(logger: &ModuleLogger) {
+    *FALLBACK.write().unwrap() = Some(logger.clone());
+    if log::set_logger(&HOST_LOG).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
+
+/// Runs `f` with `logger` as the current thread's logger.
+pub(crate) fn scoped<R>(logger: Option<&ModuleLogger>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<ModuleLogger>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let Some(logger) = logger else {
+        return f();
+    };
+    // Restored on unwind too, before the panic is caught at the ABI boundary.
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(logger.clone()))));
+    f()
+}
+
+fn current() -> Option<ModuleLogger> {
+    CURRENT
+        .with(|current| current.borrow().clone())
+        .or_else(|| FALLBACK.read().ok()?.clone())
+}
+
+/// Logs every panic as an error through the host logger, then runs the previous hook.
+pub(crate) fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(logger) = current() {
+                logger.error(&info.to_string().replace('\0', ""));
+            }
+            previous(info);
+        }));
+    });
+}
@@ -0,0 +1,233 @@
+//! Test harness that drives a module with a fake host.
+//!
+//! [`Harness`] exports the module through the same C ABI function table `vrft_d` loads, so
+//! settings, frames, descriptors and panics cross the same boundary they do in the host.
+//! A panic in the module surfaces as a panic of the harness call, after the module's panic
+//! message was logged.
+
+use anyhow::Result;
+use api::abi::{AbiModule, VrftModuleVTable, VRFT_OK};
+use api::{
+    ConnectionState, FrameSink, Host, HostServices, LogLevel, ModuleDescriptor, ModuleLogger,
+    ModuleSettings, TrackingModule, TrackingState, UnifiedTrackingData,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+
+use crate::ModuleIdentity;
+
+/// Host services that record what the module reports.
+#[derive(Debug, Default)]
+pub struct TestHost {
+    connection_states: Mutex<Vec<ConnectionState>>,
+    recalibrations: AtomicUsize,
+    gauges: Mutex<BTreeMap<String, f64>>,
+    counters: Mutex<BTreeMap<String, u64>>,
+}
+
+impl TestHost {
+    /// Every connection state the module reported, in order.
+    pub fn connection_states(&self) -> Vec<ConnectionState> {
+        self.connection_states.lock().unwrap().clone()
+    }
+
+    pub fn recalibrations(&self) -> usize {
+        self.recalibrations.load(Ordering::Relaxed)
+    }
+
+    /// The last value of a gauge.
+    pub fn gauge(&self, name: &str) -> Option<f64> {
+        self.gauges.lock().unwrap().get(name).copied()
+    }
+
+    /// The running total of a counter; 0 if it was never incremented.
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Host for TestHost {
+    fn connection_changed(&self, state: ConnectionState) {
+        self.connection_states.lock().unwrap().push(state);
+    }
+
+    fn request_recalibration(&self) {
+        self.recalibrations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn set_gauge(&self, name: &str, value: f64) {
+        self.gauges.lock().unwrap().insert(name.to_string(), value);
+    }
+
+    fn add_counter(&self, name: &str, delta: u64) {
+        *self
+            .counters
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default() += delta;
+    }
+}
+
+/// One message the module logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Messages of every harness, tagged with the harness's log target.
+static LOGS: Mutex<Vec<(String, LogLine)>> = Mutex::new(Vec::new());
+
+extern "C" fn capture_log(level: u32, target: *const i8, message: *const i8) {
+    if target.is_null() || message.is_null() {
+        return;
+    }
+    let (target, message) = unsafe {
+        (
+            CStr::from_ptr(target.cast()).to_string_lossy().into_owned(),
+            CStr::from_ptr(message.cast())
+                .to_string_lossy()
+                .into_owned(),
+        )
+    };
+    LOGS.lock().unwrap_or_else(|e| e.into_inner()).push((
+        target,
+        LogLine {
+            level: LogLevel::from_raw(level),
+            message,
+        },
+    ));
+}
+
+/// Drives one module instance the way `vrft_d` does.
+pub struct Harness {
+    module: AbiModule,
+    host: Arc<TestHost>,
+    target: String,
+    state_dir: Option<PathBuf>,
+    data: UnifiedTrackingData,
+    initialized: bool,
+}
+
+impl Harness {
+    /// Exports `module` with its descriptor as it is.
+    pub fn new<T: TrackingModule + 'static>(module: T) -> Self {
+        Self::with_identity(module, ModuleIdentity::default())
+    }
+
+    /// Exports `module` like [`export_module!`](crate::export_module) does with `identity`,
+    /// e.g. [`module_identity!()`](crate::module_identity).
+    pub fn with_identity<T: TrackingModule + 'static>(module: T, identity: ModuleIdentity) -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        let mut vtable = VrftModuleVTable::empty();
+        // SAFETY: The table points into this binary, which outlives the harness.
+        let module = unsafe {
+            let code = crate::export(&mut vtable, identity, || module);
+            assert_eq!(code, VRFT_OK, "vrft_create_module returned {}", code);
+            AbiModule::from_vtable(vtable).expect("the SDK exports a valid table")
+        };
+        Self {
+            module,
+            host: Arc::default(),
+            target: format!("harness.{}", NEXT.fetch_add(1, Ordering::Relaxed)),
+            state_dir: None,
+            data: UnifiedTrackingData::default(),
+            initialized: false,
+        }
+    }
+
+    /// The directory handed to the module as [`HostServices::state_dir`].
+    pub fn with_state_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.state_dir = Some(dir.into());
+        self
+    }
+
+    pub fn descriptor(&self) -> Option<ModuleDescriptor> {
+        self.module.descriptor()
+    }
+
+    /// Checks `settings` against the descriptor's schema, as the host does, and hands them
+    /// to the module.
+    pub fn configure(&mut self, settings: Value) -> Result<()> {
+        let settings = ModuleSettings::new(settings);
+        if let Some(schema) = self.descriptor().and_then(|d| d.settings_schema) {
+            settings.validate(&schema)?;
+        }
+        self.module.configure(&settings)
+    }
+
+    pub fn initialize(&mut self) -> Result<()> {
+        let logger = ModuleLogger::new(capture_log, self.target.clone());
+        let services = HostServices::new(logger, self.state_dir.clone(), self.host.clone());
+        self.module.initialize(services)?;
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Polls the module once. The frame carries over from the previous call, with its
+    /// timestamp cleared, as on the host's poll thread.
+    pub fn update(&mut self) -> TrackingState {
+        self.data.timestamp_us = 0;
+        self.module.update(&mut self.data)
+    }
+
+    /// The frame as the last [`update`](Self::update) left it.
+    pub fn frame(&self) -> &UnifiedTrackingData {
+        &self.data
+    }
+
+    /// Switches the module to push mode. Returns the frames it submits, or `None` if it
+    /// stays polled.
+    pub fn start_streaming(&mut self) -> Option<Receiver<UnifiedTrackingData>> {
+        let (tx, rx) = channel();
+        let tx = Mutex::new(tx);
+        let sink = FrameSink::new(move |data| {
+            let _ = tx.lock().unwrap().send(data.clone());
+        });
+        self.module.start_streaming(sink).then_some(rx)
+    }
+
+    /// Unloads the module. Also done when the harness is dropped.
+    pub fn unload(&mut self) {
+        if std::mem::take(&mut self.initialized) {
+            self.module.unload();
+        }
+    }
+
+    pub fn host(&self) -> &TestHost {
+        &self.host
+    }
+
+    /// Everything the module logged so far.
+    pub fn logs(&self) -> Vec<LogLine> {
+        LOGS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(target, _)| *target == self.target)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.unload();
+        }
+        LOGS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(target, _)| *target != self.target);
+    }
+}
@@ -0,0 +1,166 @@
+//! Module SDK tests
+//!
+//! Modules are driven through the exported function table, as the host drives them.
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use vrft_module_sdk::abi::{AbiModule, VrftModuleVTable, VRFT_ABI_VERSION, VRFT_OK};
+use vrft_module_sdk::testing::Harness;
+use vrft_module_sdk::{
+    export_module, module_identity, ConnectionState, FrameSink, HostServices, LogLevel,
+    ModuleDescriptor, ModuleSettings, TrackingModule, TrackingState, UnifiedExpressions,
+    UnifiedTrackingData,
+};
+
+#[derive(Default)]
+struct JawModule {
+    host: Option<HostServices>,
+    weight: f32,
+}
+
+impl TrackingModule for JawModule {
+    fn initialize(&mut self, host: HostServices) -> Result<()> {
+        log::info!("connecting with weight {}", self.weight);
+        host.set_connection_state(ConnectionState::Connected);
+        self.host = Some(host);
+        Ok(())
+    }
+
+    fn update(&mut self, data: &mut UnifiedTrackingData) -> TrackingState {
+        if self.weight > 1.0 {
+            panic!("weight out of range");
+        }
+        data.shapes[UnifiedExpressions::JawOpen as usize].weight = self.weight;
+        if let Some(host) = &self.host {
+            host.increment_counter("frames");
+        }
+        TrackingState::NewFrame
+    }
+
+    fn unload(&mut self) {
+        log::debug!("unloading");
+    }
+
+    fn configure(&mut self, settings: &ModuleSettings) -> Result<()> {
+        let weight = settings.f32_or("weight", 0.0);
+        if weight < 0.0 {
+            bail!("weight must not be negative");
+        }
+        self.weight = weight;
+        Ok(())
+    }
+
+    fn descriptor(&self) -> Option<ModuleDescriptor> {
+        Some(ModuleDescriptor {
+            vendor: "Tests".to_string(),
+            settings_schema: Some(json!({
+                "type": "object",
+                "properties": { "weight": { "type": "number", "maximum": 2.0 } }
+            })),
+            ..Default::default()
+        })
+    }
+}
+
+export_module!(JawModule::default(), name = "Jaw");
+
+#[test]
+fn the_macro_exports_the_abi_entry_points() {
+    assert_eq!(vrft_abi_version(), VRFT_ABI_VERSION);
+
+    let mut vtable = VrftModuleVTable::empty();
+    let module = unsafe {
+        assert_eq!(vrft_create_module(&mut vtable), VRFT_OK);
+        AbiModule::from_vtable(vtable).unwrap()
+    };
+    let descriptor = module.descriptor().unwrap();
+    assert_eq!(descriptor.name, "Jaw");
+    assert_eq!(descriptor.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(descriptor.vendor, "Tests");
+}
+
+#[test]
+fn the_harness_drives_a_polled_module() {
+    let mut harness = Harness::with_identity(JawModule::default(), module_identity!());
+    assert_eq!(harness.descriptor().unwrap().name, "vrft_module_sdk");
+
+    assert!(harness.configure(json!({ "weight": 3.0 })).is_err());
+    assert!(harness.configure(json!({ "weight": -1.0 })).is_err());
+    harness.configure(json!({ "weight": 0.5 })).unwrap();
+    harness.initialize().unwrap();
+
+    assert_eq!(harness.update(), TrackingState::NewFrame);
+    assert_eq!(
+        harness.frame().shapes[UnifiedExpressions::JawOpen as usize].weight,
+        0.5
+    );
+    assert_eq!(harness.host().counter("frames"), 1);
+    assert_eq!(
+        harness.host().connection_states(),
+        [ConnectionState::Connected]
+    );
+
+    harness.unload();
+    let logs = harness.logs();
+    assert_eq!(logs[0].level, LogLevel::Info);
+    assert_eq!(logs[0].message, "connecting with weight 0.5");
+    assert_eq!(logs[1].message, "unloading");
+}
+
+#[test]
+fn panics_are_logged_and_contained() {
+    let mut harness = Harness::new(JawModule::default());
+    harness.configure(json!({ "weight": 1.5 })).unwrap();
+    harness.initialize().unwrap();
+
+    let fault = catch_unwind(AssertUnwindSafe(|| harness.update())).unwrap_err();
+    assert_eq!(
+        fault.downcast_ref::<String>().unwrap(),
+        "module faulted in update"
+    );
+    assert!(harness
+        .logs()
+        .iter()
+        .any(|line| line.level == LogLevel::Error && line.message.contains("weight out of range")));
+}
+
+struct StreamingModule {
+    sink: Option<FrameSink>,
+}
+
+impl TrackingModule for StreamingModule {
+    fn initialize(&mut self, _host: HostServices) -> Result<()> {
+        Ok(())
+    }
+
+    fn update(&mut self, _data: &mut UnifiedTrackingData) -> TrackingState {
+        TrackingState::NoNewFrame
+    }
+
+    fn unload(&mut self) {
+        self.sink = None;
+    }
+
+    fn start_streaming(&mut self, sink: FrameSink) -> bool {
+        let mut data = UnifiedTrackingData::default();
+        data.head.head_yaw = 0.25;
+        sink.submit(&data);
+        self.sink = Some(sink);
+        true
+    }
+}
+
+#[test]
+fn the_harness_collects_streamed_frames() {
+    let mut harness = Harness::new(StreamingModule { sink: None });
+    assert!(harness.descriptor().is_none());
+    harness.initialize().unwrap();
+
+    let frames = harness.start_streaming().unwrap();
+    assert_eq!(frames.try_recv().unwrap().head.head_yaw, 0.25);
+
+    let mut polled = Harness::new(JawModule::default());
+    polled.initialize().unwrap();
+    assert!(polled.start_streaming().is_none());
+}